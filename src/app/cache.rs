@@ -0,0 +1,45 @@
+//! Enrichment cache keyed by `pushed_at`.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+use crate::model::Repo;
+
+/// On-disk cache of enriched repositories, one JSON file per repository id.
+///
+/// A cached entry is only reused while the repository's `pushed_at` is unchanged,
+/// so re-runs skip commit/issue/fork fetches for repos nobody has pushed to.
+#[derive(Debug, Clone)]
+pub struct RepoCache {
+    dir: PathBuf,
+}
+
+impl RepoCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn entry_path(&self, repo: &Repo) -> PathBuf {
+        self.dir.join(format!("{}.json", repo.id))
+    }
+
+    /// Returns the cached copy of `repo` if it was stored with the same `pushed_at`.
+    pub fn load_unchanged(&self, repo: &Repo) -> Option<Repo> {
+        let pushed_at = repo.pushed_at.as_ref()?;
+        let contents = std::fs::read_to_string(self.entry_path(repo)).ok()?;
+        let cached: Repo = serde_json::from_str(&contents).ok()?;
+
+        (cached.pushed_at.as_ref() == Some(pushed_at)).then_some(cached)
+    }
+
+    pub fn store(&self, repo: &Repo) -> Result<(), AppError> {
+        std::fs::create_dir_all(&self.dir)?;
+        let contents = serde_json::to_string(repo)?;
+        std::fs::write(self.entry_path(repo), contents)?;
+        Ok(())
+    }
+}
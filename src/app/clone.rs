@@ -1,11 +1,19 @@
 //! Repository cloning.
 
-use std::collections::HashSet;
-use std::path::Path;
-use std::process::Command;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+
+use crate::app::dependencies::{declared_dependencies, ManifestDependencies};
+use crate::app::phase_error::{Phase, PhaseError};
+use crate::config::{CloneConfig, SelectionMetric};
 use crate::error::AppError;
+pub use crate::model::CodeAnalysis;
 use crate::model::Repo;
+use crate::util::languages::source_extensions;
+use crate::{errln, outln};
 
 /// Rules to determine if a repository contains actual source code vs tutorials/documentation
 #[derive(Debug, Clone)]
@@ -16,6 +24,12 @@ pub struct CodeDetectionRules {
     pub min_source_ratio: f64,
     /// Maximum directory depth to scan
     pub max_depth: usize,
+    /// File names that mark the root of a package or project
+    pub manifest_files: HashSet<String>,
+    /// Directory names whose contents count as test files
+    pub test_dirs: HashSet<String>,
+    /// Leave files under test directories out of the source ratio
+    pub exclude_tests_from_ratio: bool,
 }
 
 impl CodeDetectionRules {
@@ -55,11 +69,38 @@ impl CodeDetectionRules {
 
         let source_extensions = extensions.iter().map(|s| s.to_string()).collect();
 
+        let manifests = [
+            "Cargo.toml",
+            "package.json",
+            "pom.xml",
+            "build.gradle",
+            "build.gradle.kts",
+            "go.mod",
+            "pyproject.toml",
+            "setup.py",
+        ];
+
         Self {
             source_extensions,
             min_source_ratio,
             max_depth,
+            manifest_files: manifests.iter().map(|s| s.to_string()).collect(),
+            test_dirs: ["tests", "test", "spec", "__tests__"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            exclude_tests_from_ratio: false,
+        }
+    }
+
+    /// These rules with `source_extensions` narrowed to `language`'s own extensions
+    /// (e.g. only `.rs` for Rust); unchanged for languages without a known set.
+    pub fn for_language(&self, language: &str) -> Self {
+        let mut rules = self.clone();
+        if let Some(extensions) = source_extensions(language) {
+            rules.source_extensions = extensions.iter().map(|s| s.to_string()).collect();
         }
+        rules
     }
 }
 
@@ -76,7 +117,10 @@ pub fn check_for_source_code(
 ) -> Result<CodeAnalysis, AppError> {
     let mut source_files = 0;
     let mut total_files = 0;
-    let mut file_extensions: HashSet<String> = HashSet::new();
+    let mut file_extensions: HashMap<String, usize> = HashMap::new();
+    let mut nested_manifests = 0;
+    let mut test_files = 0;
+    let mut files_in_tests = 0;
 
     if let Ok(entries) = walkdir::WalkDir::new(repo_path)
         .max_depth(rules.max_depth)
@@ -89,95 +133,317 @@ pub fn check_for_source_code(
             if path.is_file() {
                 total_files += 1;
 
+                // Depth 1 is the repository root; manifests below it belong to subprojects.
+                let is_manifest = entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| rules.manifest_files.contains(name));
+                if is_manifest && entry.depth() > 1 {
+                    nested_manifests += 1;
+                }
+
+                let in_tests = path
+                    .strip_prefix(repo_path)
+                    .ok()
+                    .and_then(Path::parent)
+                    .is_some_and(|dir| {
+                        dir.components().any(|c| {
+                            c.as_os_str()
+                                .to_str()
+                                .is_some_and(|name| rules.test_dirs.contains(name))
+                        })
+                    });
+                if in_tests {
+                    files_in_tests += 1;
+                }
+
                 if let Some(ext_str) = path.extension().and_then(|ext| ext.to_str()) {
                     let ext_lower = ext_str.to_lowercase();
-                    file_extensions.insert(ext_lower.clone());
+                    *file_extensions.entry(ext_lower.clone()).or_default() += 1;
 
                     if rules.source_extensions.contains(&ext_lower) {
                         source_files += 1;
+                        if in_tests {
+                            test_files += 1;
+                        }
                     }
                 }
             }
         }
     }
 
-    let source_ratio = if total_files > 0 {
-        source_files as f64 / total_files as f64
+    let production_files = source_files - test_files;
+    let (ratio_sources, ratio_total) = if rules.exclude_tests_from_ratio {
+        (production_files, total_files - files_in_tests)
+    } else {
+        (source_files, total_files)
+    };
+    let source_ratio = if ratio_total > 0 {
+        ratio_sources as f64 / ratio_total as f64
     } else {
         0.0
     };
 
-    let is_source_code_repo = source_ratio >= rules.min_source_ratio && source_files > 0;
+    let is_source_code_repo = source_ratio >= rules.min_source_ratio && ratio_sources > 0;
 
     Ok(CodeAnalysis {
         source_files,
         total_files,
         source_ratio,
         is_source_code_repo,
-        file_extensions: file_extensions.into_iter().collect(),
+        file_extensions,
+        is_monorepo: nested_manifests >= 2,
+        test_files,
+        production_files,
     })
 }
 
-#[derive(Debug)]
-pub struct CodeAnalysis {
-    pub source_files: usize,
-    pub total_files: usize,
-    pub source_ratio: f64,
-    pub is_source_code_repo: bool,
-    pub file_extensions: Vec<String>,
+/// A repository kept by the clone phase together with its source analysis
+#[derive(Debug, Clone)]
+pub struct ClonedRepo {
+    pub repo: Repo,
+    pub analysis: CodeAnalysis,
 }
 
-pub async fn clone_repository(repo: &Repo, clone_dir: &Path) -> Result<(), AppError> {
-    let clone_url = format!("https://github.com/{}.git", repo.slug());
+/// Why no repository was cloned for a language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CloneSkipReason {
+    /// The language had no repositories to try.
+    NoRepos,
+    /// Every candidate that could be inspected looked like documentation or tutorials.
+    NoSourceCode,
+    /// No candidate could be cloned or inspected; holds the last error.
+    CloneFailed(String),
+    /// Every candidate was larger than `MAX_CLONE_SIZE_KB`.
+    TooLarge,
+    /// Candidates passed, but the repo selector declined all of them.
+    NoneSelected,
+}
 
-    println!("  Cloning {} to {:?}...", repo.slug(), clone_dir);
+impl CloneSkipReason {
+    /// Short machine-readable name, used in the JSON summary.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NoRepos => "no_repos",
+            Self::NoSourceCode => "no_source_code",
+            Self::CloneFailed(_) => "clone_failed",
+            Self::TooLarge => "too_large",
+            Self::NoneSelected => "none_selected",
+        }
+    }
+
+    /// Why a single candidate was passed over, as printed after its name.
+    fn candidate_message(&self) -> String {
+        match self {
+            Self::NoSourceCode => "appears to be documentation/tutorial only".to_string(),
+            Self::CloneFailed(err) => format!("could not be cloned or inspected: {err}"),
+            Self::TooLarge => "exceeds the clone size limit".to_string(),
+            Self::NoRepos | Self::NoneSelected => format!("was skipped ({})", self.code()),
+        }
+    }
+
+    /// Keeps the more telling of two candidate failures: a repo that was cloned and
+    /// found to be documentation says more than one that failed to clone or was too large.
+    fn most_telling(self, other: Self) -> Self {
+        fn rank(reason: &CloneSkipReason) -> u8 {
+            match reason {
+                CloneSkipReason::NoSourceCode => 3,
+                CloneSkipReason::CloneFailed(_) => 2,
+                CloneSkipReason::TooLarge => 1,
+                CloneSkipReason::NoRepos | CloneSkipReason::NoneSelected => 0,
+            }
+        }
+        if rank(&other) >= rank(&self) {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl std::fmt::Display for CloneSkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoRepos => write!(f, "no repositories to check"),
+            Self::NoSourceCode => write!(f, "every candidate looked like documentation"),
+            Self::CloneFailed(err) => write!(f, "no candidate could be cloned: {err}"),
+            Self::TooLarge => write!(f, "every candidate exceeded the clone size limit"),
+            Self::NoneSelected => write!(f, "the selector declined every passing candidate"),
+        }
+    }
+}
+
+/// Result of the clone phase for one language.
+#[derive(Debug, Clone)]
+pub struct CloneOutcome {
+    pub language: String,
+    pub result: Result<ClonedRepo, CloneSkipReason>,
+    /// Candidates that could not be cloned or inspected, even if another one passed.
+    pub errors: Vec<PhaseError>,
+}
+
+/// State shared by the candidates of one language during the clone phase.
+struct CloneRun<'a> {
+    /// Limits the `git` processes running at once.
+    processes: &'a Semaphore,
+    /// Candidates that failed to clone or to be inspected.
+    errors: Vec<PhaseError>,
+}
+
+impl<'a> CloneRun<'a> {
+    fn new(processes: &'a Semaphore) -> Self {
+        Self {
+            processes,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Keeps a candidate's clone failure for the error summary.
+    fn record(&mut self, language: &str, repo: &Repo, skipped: &CloneSkipReason) {
+        if let CloneSkipReason::CloneFailed(message) = skipped {
+            self.errors.push(
+                PhaseError::new(Phase::Clone, Some(repo.slug()), message).in_language(language),
+            );
+        }
+    }
+}
+
+impl CloneOutcome {
+    /// The successfully cloned repositories, in outcome order.
+    pub fn cloned(outcomes: &[CloneOutcome]) -> Vec<ClonedRepo> {
+        outcomes
+            .iter()
+            .filter_map(|outcome| outcome.result.as_ref().ok().cloned())
+            .collect()
+    }
+}
+
+/// Stderr fragments from `git clone` that point at a transient network problem.
+const NETWORK_ERROR_PATTERNS: &[&str] = &[
+    "Could not resolve host",
+    "Connection timed out",
+    "Connection reset",
+    "Connection refused",
+    "Operation timed out",
+    "early EOF",
+    "The remote end hung up unexpectedly",
+];
+
+fn is_network_error(stderr: &str) -> bool {
+    NETWORK_ERROR_PATTERNS
+        .iter()
+        .any(|pattern| stderr.contains(pattern))
+}
+
+/// Shallow-clones `repo` into `clone_dir`, retrying up to `config.retries` times when
+/// git reports a network error. The partial directory is removed before each retry.
+/// Each `git` process runs while holding a permit from `processes`.
+pub async fn clone_repository(
+    repo: &Repo,
+    clone_dir: &Path,
+    config: &CloneConfig,
+    processes: &Semaphore,
+) -> Result<(), AppError> {
+    let clone_url = format!(
+        "{}/{}.git",
+        config.git_base_url.trim_end_matches('/'),
+        repo.slug()
+    );
+
+    outln!("  Cloning {} to {:?}...", repo.slug(), clone_dir);
 
     if let Some(parent) = clone_dir.parent() {
         std::fs::create_dir_all(parent).map_err(AppError::from)?;
     }
 
-    let output = Command::new("git")
-        .args(&[
-            "clone",
-            "--depth",
-            "1",
-            &clone_url,
-            clone_dir.to_str().unwrap(),
-        ])
-        .output()
-        .map_err(|_| {
-            AppError::Io(std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                "git command not found. Please install git.",
-            ))
-        })?;
-
-    if !output.status.success() {
+    let mut attempt = 0;
+    loop {
+        let permit = processes
+            .acquire()
+            .await
+            .map_err(|_| AppError::Git("git process limit was closed".to_string()))?;
+        let output = Command::new(&config.git_program)
+            .args([
+                "clone",
+                "--depth",
+                "1",
+                &clone_url,
+                clone_dir.to_str().unwrap(),
+            ])
+            .output()
+            .await
+            .map_err(|_| {
+                AppError::Io(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "git command not found. Please install git.",
+                ))
+            })?;
+        drop(permit);
+
+        if output.status.success() {
+            break;
+        }
+
         let error_msg = String::from_utf8_lossy(&output.stderr);
-        return Err(AppError::Git(format!(
-            "Failed to clone repository {}: {}",
+        if attempt >= config.retries || !is_network_error(&error_msg) {
+            return Err(AppError::Git(format!(
+                "Failed to clone repository {}: {}",
+                repo.slug(),
+                error_msg
+            )));
+        }
+
+        attempt += 1;
+        errln!(
+            "    ⚠ Network error cloning {}, retrying ({}/{})...",
             repo.slug(),
-            error_msg
-        )));
+            attempt,
+            config.retries
+        );
+        if clone_dir.exists() {
+            std::fs::remove_dir_all(clone_dir).map_err(AppError::from)?;
+        }
+        tokio::time::sleep(config.retry_delay).await;
     }
 
-    println!("  ✓ Successfully cloned {}", repo.slug());
+    outln!("  ✓ Successfully cloned {}", repo.slug());
     Ok(())
 }
 
 async fn clone_and_check_repo(
     repo: &Repo,
+    language: &str,
     clone_dir: &Path,
     rules: &CodeDetectionRules,
-) -> Result<Option<(Repo, CodeAnalysis)>, AppError> {
-    if let Err(e) = clone_repository(repo, clone_dir).await {
-        eprintln!("    ⚠ Failed to clone {}: {}", repo.slug(), e);
-        return Ok(None);
+    config: &CloneConfig,
+    processes: &Semaphore,
+) -> Result<(Repo, CodeAnalysis), CloneSkipReason> {
+    let language_rules;
+    let rules = if config.use_repo_language {
+        language_rules = rules.for_language(effective_language(repo, language, config));
+        &language_rules
+    } else {
+        rules
+    };
+
+    if let Some(max_size_kb) = config.max_size_kb.filter(|&max| repo.size > max) {
+        outln!(
+            "    ✗ {} is too large to clone ({} KB > {} KB), skipping",
+            repo.slug(),
+            repo.size,
+            max_size_kb
+        );
+        return Err(CloneSkipReason::TooLarge);
+    }
+
+    if let Err(e) = clone_repository(repo, clone_dir, config, processes).await {
+        errln!("    ⚠ Failed to clone {}: {}", repo.slug(), e);
+        return Err(CloneSkipReason::CloneFailed(e.to_string()));
     }
 
-    match check_for_source_code(clone_dir, rules) {
+    let reason = match check_for_source_code(clone_dir, rules) {
         Ok(analysis) => {
-            println!(
+            outln!(
                 "    {}: {} source files, {:.1}% source ratio",
                 repo.slug(),
                 analysis.source_files,
@@ -185,41 +451,220 @@ async fn clone_and_check_repo(
             );
 
             if analysis.is_source_code_repo {
-                println!(
+                outln!(
                     "    ✓ {} appears to contain actual source code!",
                     repo.slug()
                 );
                 // Keep the cloned directory - don't clean up
-                return Ok(Some((repo.clone(), analysis)));
-            } else {
-                println!("    ✗ {} appears to be documentation/tutorial", repo.slug());
+                return Ok((repo.clone(), analysis));
             }
+            outln!("    ✗ {} appears to be documentation/tutorial", repo.slug());
+            CloneSkipReason::NoSourceCode
         }
-        Err(e) => eprintln!("    ⚠ Failed to analyze {}: {}", repo.slug(), e),
-    }
+        Err(e) => {
+            errln!("    ⚠ Failed to analyze {}: {}", repo.slug(), e);
+            CloneSkipReason::CloneFailed(e.to_string())
+        }
+    };
 
     if let Err(e) = std::fs::remove_dir_all(clone_dir) {
-        eprintln!("    ⚠ Failed to clean up {}: {}", clone_dir.display(), e);
+        errln!("    ⚠ Failed to clean up {}: {}", clone_dir.display(), e);
+    }
+
+    Err(reason)
+}
+
+fn clone_dir_for(
+    clone_base_dir: &Path,
+    language: &str,
+    repo: &Repo,
+    config: &CloneConfig,
+) -> PathBuf {
+    let language = effective_language(repo, language, config);
+    // Same-named repos from different owners can be cloned side by side in one batch.
+    clone_base_dir.join(format!(
+        "{}-{}-{}",
+        language.to_lowercase(),
+        repo.owner.login,
+        repo.name
+    ))
+}
+
+/// The repo's own primary language with `use_repo_language`, otherwise the searched one.
+fn effective_language<'a>(repo: &'a Repo, language: &'a str, config: &CloneConfig) -> &'a str {
+    match &repo.language {
+        Some(own) if config.use_repo_language => own,
+        _ => language,
+    }
+}
+
+/// Picks the repository to keep among the candidates that passed source detection.
+pub trait RepoSelector {
+    /// Chooses one of `candidates` (in search order, i.e. by stars descending), with
+    /// `analyses[i]` describing `candidates[i]`. `None` keeps none of them.
+    fn select(&self, candidates: &[Repo], analyses: &[CodeAnalysis]) -> Option<Repo>;
+
+    /// Whether the first passing candidates settle the choice, so the remaining ones
+    /// needn't be cloned. Selectors that compare every candidate return `false`.
+    fn stops_at_first_pass(&self) -> bool {
+        false
+    }
+}
+
+/// The most starred passing repository; later candidates aren't cloned once one passes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultSelector;
+
+impl RepoSelector for DefaultSelector {
+    fn select(&self, candidates: &[Repo], analyses: &[CodeAnalysis]) -> Option<Repo> {
+        best_by_score(candidates, analyses, |repo, _| repo.stargazers_count as f64)
+    }
+
+    fn stops_at_first_pass(&self) -> bool {
+        true
+    }
+}
+
+impl RepoSelector for SelectionMetric {
+    fn select(&self, candidates: &[Repo], analyses: &[CodeAnalysis]) -> Option<Repo> {
+        match self {
+            SelectionMetric::Stars => DefaultSelector.select(candidates, analyses),
+            SelectionMetric::StarsTimesRatio => {
+                best_by_score(candidates, analyses, |repo, analysis| {
+                    self.score(repo, analysis)
+                })
+            }
+        }
+    }
+
+    fn stops_at_first_pass(&self) -> bool {
+        *self == SelectionMetric::Stars
     }
+}
 
-    Ok(None)
+/// Highest scoring candidate, the earliest one on ties.
+fn best_by_score(
+    candidates: &[Repo],
+    analyses: &[CodeAnalysis],
+    score: impl Fn(&Repo, &CodeAnalysis) -> f64,
+) -> Option<Repo> {
+    candidates
+        .iter()
+        .zip(analyses)
+        .map(|(repo, analysis)| (repo, score(repo, analysis)))
+        .fold(
+            None,
+            |best: Option<(&Repo, f64)>, (repo, score)| match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((repo, score)),
+            },
+        )
+        .map(|(repo, _)| repo.clone())
+}
+
+impl SelectionMetric {
+    /// Ranking score for a candidate that passed source detection; higher wins.
+    pub fn score(&self, repo: &Repo, analysis: &CodeAnalysis) -> f64 {
+        match self {
+            SelectionMetric::Stars => repo.stargazers_count as f64,
+            SelectionMetric::StarsTimesRatio => {
+                repo.stargazers_count as f64 * analysis.source_ratio
+            }
+        }
+    }
+}
+
+fn report_found(repo: &Repo, analysis: &CodeAnalysis) {
+    outln!(
+        "    ✓ Found best source code repository: {} ({} stars)",
+        repo.slug(),
+        repo.stargazers_count
+    );
+    outln!(
+        "    ✓ Source files: {}, Source ratio: {:.1}%",
+        analysis.source_files,
+        analysis.source_ratio * 100.0
+    );
 }
 
 pub async fn find_best_code_repo(
     repos: &[Repo],
     language: &str,
     clone_base_dir: &Path,
-    min_source_ratio: f64,
+    config: &CloneConfig,
 ) -> Result<Option<(Repo, CodeAnalysis)>, AppError> {
-    let rules = CodeDetectionRules::new(min_source_ratio, 10);
+    Ok(select_code_repo(repos, language, clone_base_dir, config)
+        .await
+        .ok())
+}
 
-    println!(
+/// Picks and clones the best source code repository using `config.selection_metric`,
+/// or says why none qualified.
+pub async fn select_code_repo(
+    repos: &[Repo],
+    language: &str,
+    clone_base_dir: &Path,
+    config: &CloneConfig,
+) -> Result<(Repo, CodeAnalysis), CloneSkipReason> {
+    select_code_repo_with(
+        repos,
+        language,
+        clone_base_dir,
+        config,
+        &config.selection_metric,
+    )
+    .await
+}
+
+/// Like [`select_code_repo`], with the choice among passing candidates made by `selector`.
+pub async fn select_code_repo_with(
+    repos: &[Repo],
+    language: &str,
+    clone_base_dir: &Path,
+    config: &CloneConfig,
+    selector: &impl RepoSelector,
+) -> Result<(Repo, CodeAnalysis), CloneSkipReason> {
+    let processes = Semaphore::new(config.process_limit.max(1));
+    let mut run = CloneRun::new(&processes);
+    select_code_repo_in(repos, language, clone_base_dir, config, selector, &mut run).await
+}
+
+/// [`select_code_repo_with`] as part of `run`, which limits the `git` processes and
+/// collects the candidates' clone failures.
+async fn select_code_repo_in(
+    repos: &[Repo],
+    language: &str,
+    clone_base_dir: &Path,
+    config: &CloneConfig,
+    selector: &impl RepoSelector,
+    run: &mut CloneRun<'_>,
+) -> Result<(Repo, CodeAnalysis), CloneSkipReason> {
+    let rules = CodeDetectionRules {
+        exclude_tests_from_ratio: config.exclude_test_files,
+        ..CodeDetectionRules::new(config.min_source_ratio, 10)
+    };
+
+    outln!(
         "  Analyzing top {} repositories for source code content...",
         repos.len()
     );
 
+    if config.parallelism > 1 || !selector.stops_at_first_pass() {
+        return find_best_code_repo_batched(
+            repos,
+            language,
+            clone_base_dir,
+            config,
+            &rules,
+            selector,
+            run,
+        )
+        .await;
+    }
+
+    let mut reason = CloneSkipReason::NoRepos;
     for (i, repo) in repos.iter().enumerate() {
-        println!(
+        outln!(
             "    [{}/{}] Checking {} ({} stars)...",
             i + 1,
             repos.len(),
@@ -227,87 +672,210 @@ pub async fn find_best_code_repo(
             repo.stargazers_count
         );
 
-        let clone_dir = clone_base_dir.join(format!("{}-{}", language.to_lowercase(), repo.name));
+        let clone_dir = clone_dir_for(clone_base_dir, language, repo, config);
 
-        if let Ok(Some((repo_clone, analysis))) =
-            clone_and_check_repo(repo, &clone_dir, &rules).await
-        {
-            println!(
-                "    ✓ Found most popular source code repository: {} ({} stars)",
-                repo.slug(),
-                repo.stargazers_count
-            );
-            println!(
-                "    ✓ Source files: {}, Source ratio: {:.1}%",
-                analysis.source_files,
-                analysis.source_ratio * 100.0
-            );
-            return Ok(Some((repo_clone, analysis)));
-        } else {
-            println!(
-                "    ✗ {} appears to be documentation/tutorial only",
-                repo.slug()
-            );
+        let check = clone_and_check_repo(repo, language, &clone_dir, &rules, config, run.processes);
+        match check.await {
+            Ok((repo_clone, analysis)) => {
+                report_found(repo, &analysis);
+                return Ok((repo_clone, analysis));
+            }
+            Err(skipped) => {
+                outln!("    ✗ {} {}", repo.slug(), skipped.candidate_message());
+                run.record(language, repo, &skipped);
+                reason = reason.most_telling(skipped);
+            }
         }
     }
 
-    println!(
+    outln!(
         "    ✗ No suitable source code repository found for {}",
         language
     );
-    Ok(None)
+    Err(reason)
+}
+
+/// Clones candidates in batches of `config.parallelism` and keeps the passing repo the
+/// selector picks. Selectors that stop at the first pass choose from the first batch with
+/// a passing repo; others see every candidate. Clones that aren't selected are removed.
+async fn find_best_code_repo_batched(
+    repos: &[Repo],
+    language: &str,
+    clone_base_dir: &Path,
+    config: &CloneConfig,
+    rules: &CodeDetectionRules,
+    selector: &impl RepoSelector,
+    run: &mut CloneRun<'_>,
+) -> Result<(Repo, CodeAnalysis), CloneSkipReason> {
+    let processes = run.processes;
+    let mut passing: Vec<(Repo, CodeAnalysis)> = Vec::new();
+    let mut reason = CloneSkipReason::NoRepos;
+
+    for (batch_idx, batch) in repos.chunks(config.parallelism).enumerate() {
+        let first = batch_idx * config.parallelism;
+        outln!(
+            "    [{}-{}/{}] Checking {} candidates concurrently...",
+            first + 1,
+            first + batch.len(),
+            repos.len(),
+            batch.len()
+        );
+
+        let checks = batch.iter().map(|repo| {
+            let clone_dir = clone_dir_for(clone_base_dir, language, repo, config);
+            async move {
+                clone_and_check_repo(repo, language, &clone_dir, rules, config, processes).await
+            }
+        });
+
+        for (repo, result) in batch.iter().zip(futures::future::join_all(checks).await) {
+            match result {
+                Ok(found) => passing.push(found),
+                Err(skipped) => {
+                    run.record(language, repo, &skipped);
+                    reason = reason.most_telling(skipped);
+                }
+            }
+        }
+
+        if selector.stops_at_first_pass() && !passing.is_empty() {
+            break;
+        }
+    }
+
+    if passing.is_empty() {
+        outln!(
+            "    ✗ No suitable source code repository found for {}",
+            language
+        );
+        return Err(reason);
+    }
+
+    let (candidates, analyses): (Vec<Repo>, Vec<CodeAnalysis>) = passing.into_iter().unzip();
+    let selected = selector
+        .select(&candidates, &analyses)
+        .and_then(|chosen| candidates.iter().position(|repo| repo.id == chosen.id));
+
+    let mut best = None;
+    for (idx, (repo, analysis)) in candidates.into_iter().zip(analyses).enumerate() {
+        if Some(idx) == selected {
+            best = Some((repo, analysis));
+            continue;
+        }
+        let clone_dir = clone_dir_for(clone_base_dir, language, &repo, config);
+        if let Err(e) = std::fs::remove_dir_all(&clone_dir) {
+            errln!("    ⚠ Failed to clean up {}: {}", clone_dir.display(), e);
+        }
+    }
+
+    let Some((best, analysis)) = best else {
+        outln!("    ✗ No candidate was selected for {}", language);
+        return Err(CloneSkipReason::NoneSelected);
+    };
+
+    report_found(&best, &analysis);
+    Ok((best, analysis))
 }
 
-/// Clones the best repo for each language and returns the list of cloned repos
+/// Dependencies listed per manifest before the rest are summarized as a count
+const TOP_DEPENDENCIES: usize = 10;
+
+fn print_dependencies(manifests: &[ManifestDependencies]) {
+    if manifests.is_empty() {
+        outln!("  - Dependencies: no Cargo.toml, package.json or pom.xml found");
+        return;
+    }
+    outln!("  - Dependencies:");
+    for deps in manifests {
+        let mut line = format!(
+            "      {} ({}): {}",
+            deps.manifest,
+            deps.names.len(),
+            deps.names
+                .iter()
+                .take(TOP_DEPENDENCIES)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        if deps.names.len() > TOP_DEPENDENCIES {
+            line.push_str(&format!(" (+{} more)", deps.names.len() - TOP_DEPENDENCIES));
+        }
+        outln!("{}", line);
+    }
+}
+
+/// Clones the best repo for each language and reports, per language, either the
+/// cloned repo or why none was cloned
 pub async fn clone_best_repos(
     language_reports: &[crate::app::LanguageReport],
     clone_base_dir: &Path,
-    min_source_ratio: f64,
-) -> Result<Vec<Repo>, AppError> {
-    println!("\n=== Part C: Clone and Inspect Repositories ===\n");
+    config: &CloneConfig,
+) -> Result<Vec<CloneOutcome>, AppError> {
+    outln!("\n=== Part C: Clone and Inspect Repositories ===\n");
 
-    let mut cloned_repos = Vec::new();
+    let mut outcomes = Vec::new();
+    // One limit for every language, however many candidates each clones at once.
+    let processes = Semaphore::new(config.process_limit.max(1));
 
     for report in language_reports {
-        println!("Processing {} repositories...", report.language);
-        println!("{}", "=".repeat(50));
+        outln!("Processing {} repositories...", report.language);
+        outln!("{}", "=".repeat(50));
 
-        match find_best_code_repo(
+        let mut run = CloneRun::new(&processes);
+        let result = select_code_repo_in(
             &report.repos,
             &report.language,
             clone_base_dir,
-            min_source_ratio,
+            config,
+            &config.selection_metric,
+            &mut run,
         )
         .await
-        {
-            Ok(Some((repo, analysis))) => {
-                println!(
+        .map(|(repo, analysis)| ClonedRepo { repo, analysis });
+        match &result {
+            Ok(ClonedRepo { repo, analysis }) => {
+                outln!(
                     "✓ Successfully cloned best source code repository for {}: {}",
                     report.language,
                     repo.slug()
                 );
-                println!("  - Stars: {}", repo.stargazers_count);
-                println!("  - Source files: {}", analysis.source_files);
-                println!("  - Source ratio: {:.1}%", analysis.source_ratio * 100.0);
-                println!("  - File extensions: {:?}", analysis.file_extensions);
-                cloned_repos.push(repo);
-            }
-            Ok(None) => {
-                println!(
-                    "✗ No suitable source code repository found for {}",
-                    report.language
+                outln!("  - Stars: {}", repo.stargazers_count);
+                outln!(
+                    "  - Source files: {} ({} production, {} test)",
+                    analysis.source_files,
+                    analysis.production_files,
+                    analysis.test_files
                 );
+                outln!("  - Source ratio: {:.1}%", analysis.source_ratio * 100.0);
+                let extensions: Vec<String> = analysis
+                    .extensions_by_count()
+                    .iter()
+                    .map(|(ext, count)| format!("{ext}: {count}"))
+                    .collect();
+                outln!("  - File extensions: {}", extensions.join(", "));
+                outln!("  - Monorepo: {}", analysis.is_monorepo);
+                if config.list_dependencies {
+                    let dir = clone_dir_for(clone_base_dir, &report.language, repo, config);
+                    print_dependencies(&declared_dependencies(&dir));
+                }
             }
-            Err(e) => {
-                eprintln!(
-                    "✗ Failed to process {} repositories: {}",
-                    report.language, e
+            Err(reason) => {
+                outln!(
+                    "✗ No suitable source code repository found for {}: {}",
+                    report.language,
+                    reason
                 );
             }
         }
+        outcomes.push(CloneOutcome {
+            language: report.language.clone(),
+            result,
+            errors: run.errors,
+        });
 
-        println!();
+        outln!();
     }
 
-    Ok(cloned_repos)
+    Ok(outcomes)
 }
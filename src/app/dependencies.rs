@@ -0,0 +1,118 @@
+//! Dependencies declared in a cloned repository's manifests.
+use std::path::Path;
+
+use serde_json::Value;
+
+/// Extracts dependency names from a manifest's text.
+type ManifestParser = fn(&str) -> Vec<String>;
+
+/// Root manifests that are read, with the parser for each.
+const MANIFESTS: &[(&str, ManifestParser)] = &[
+    ("Cargo.toml", cargo_dependencies),
+    ("package.json", npm_dependencies),
+    ("pom.xml", maven_dependencies),
+];
+
+/// The dependency names one manifest declares: in the order it lists them, except for
+/// `package.json`, whose names come sorted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestDependencies {
+    pub manifest: &'static str,
+    pub names: Vec<String>,
+}
+
+/// Reads the manifests at the root of `repo_dir`. Missing or unreadable manifests are
+/// skipped, and a malformed one yields whatever could be parsed before the damage.
+pub fn declared_dependencies(repo_dir: &Path) -> Vec<ManifestDependencies> {
+    MANIFESTS
+        .iter()
+        .filter_map(|&(manifest, parse)| {
+            let text = std::fs::read_to_string(repo_dir.join(manifest)).ok()?;
+            Some(ManifestDependencies {
+                manifest,
+                names: parse(&text),
+            })
+        })
+        .collect()
+}
+
+/// Keys of `[dependencies]`-style tables (including `[target.*.dependencies]` and
+/// `[workspace.dependencies]`) and the names of `[dependencies.<name>]` tables. Lines
+/// that continue an inline table spanning several lines are not read as keys.
+fn cargo_dependencies(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut in_dependencies = false;
+    // `{` not yet closed by an earlier line, e.g. `tokio = { version = "1", features = [`.
+    let mut open_braces = 0usize;
+
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let continues_table = open_braces > 0;
+        open_braces =
+            (open_braces + line.matches('{').count()).saturating_sub(line.matches('}').count());
+        if continues_table {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.trim_end_matches(']').trim();
+            in_dependencies = header == "dependencies" || header.ends_with(".dependencies");
+            if let Some(name) = header.strip_prefix("dependencies.") {
+                push_unique(&mut names, name.trim_matches('"'));
+            }
+            continue;
+        }
+        if !in_dependencies {
+            continue;
+        }
+        if let Some((key, _)) = line.split_once('=') {
+            // `serde.workspace = true` declares `serde`.
+            let key = key.trim().trim_matches('"');
+            let name = key.split('.').next().unwrap_or(key).trim_matches('"');
+            push_unique(&mut names, name);
+        }
+    }
+    names
+}
+
+/// Keys of the `dependencies` object, sorted by name: `serde_json` doesn't keep the
+/// order they are listed in.
+fn npm_dependencies(text: &str) -> Vec<String> {
+    let Ok(root) = serde_json::from_str::<Value>(text) else {
+        return Vec::new();
+    };
+    root.get("dependencies")
+        .and_then(Value::as_object)
+        .map(|deps| deps.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// The `artifactId` of each `<dependency>` element.
+fn maven_dependencies(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("<dependency>") {
+        rest = &rest[start + "<dependency>".len()..];
+        let Some(end) = rest.find("</dependency>") else {
+            break;
+        };
+        let block = &rest[..end];
+        if let Some(artifact) = element_text(block, "artifactId") {
+            push_unique(&mut names, artifact);
+        }
+        rest = &rest[end..];
+    }
+    names
+}
+
+fn element_text<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{tag}>");
+    let start = xml.find(&open)? + open.len();
+    let end = xml[start..].find(&format!("</{tag}>"))? + start;
+    Some(xml[start..end].trim())
+}
+
+fn push_unique(names: &mut Vec<String>, name: &str) {
+    if !name.is_empty() && !names.iter().any(|n| n == name) {
+        names.push(name.to_string());
+    }
+}
@@ -1,17 +1,35 @@
 //! Main application.
 
-use crate::config::AppConfig;
+use std::collections::HashSet;
+use std::future::Future;
+use std::io::IsTerminal;
+use std::pin::pin;
+use std::sync::Arc;
+
+use futures::stream::{FuturesUnordered, Stream, StreamExt};
+use tokio::sync::Semaphore;
+
+use crate::config::{AppConfig, OutputFormat, Part, StatsConfig, VerboseCommits};
 use crate::error::AppError;
 use crate::model::Repo;
-use crate::service::{GitService, RedisService};
+use crate::service::{
+    BoundedGitService, DataStorageService, GitRepositoryService, GitService, RedisService,
+};
+use crate::util::progress::Progress;
+use crate::{errln, out, outln};
 
+pub mod cache;
 pub mod clone;
+pub mod dependencies;
 pub mod output;
+pub mod phase_error;
 pub mod repo_fetcher;
 pub mod stats;
 
+use clone::{CloneOutcome, ClonedRepo};
 use output::OutputFormatter;
-use repo_fetcher::RepoFetcher;
+use phase_error::{Phase, PhaseError};
+use repo_fetcher::{RepoFetcher, PROGRESS_UNITS_PER_LANGUAGE};
 use stats::StatsCalculator;
 
 const TARGET_LANGUAGES: &[&str] = &["C"];
@@ -26,94 +44,491 @@ pub struct LanguageReport {
     pub total_repo_commits: usize,
     pub new_fork_commits: usize,
     pub repo_metrics: Vec<RepoMetrics>,
+    /// Settings the metrics were computed with, reused when reports are merged.
+    pub stats_config: StatsConfig,
+    /// Sub-fetches that failed while collecting this report.
+    pub errors: Vec<PhaseError>,
+}
+
+impl LanguageReport {
+    /// Combines two partial reports for the same language, deduplicating repos by id
+    /// and recomputing all totals from the merged repository list.
+    pub fn merge(self, other: LanguageReport) -> LanguageReport {
+        assert_eq!(
+            self.language, other.language,
+            "cannot merge reports for different languages"
+        );
+
+        let mut seen = HashSet::new();
+        let repos: Vec<Repo> = self
+            .repos
+            .into_iter()
+            .chain(other.repos)
+            .filter(|repo| seen.insert(repo.id))
+            .collect();
+
+        let mut merged = StatsCalculator::build_language_report_with_config(
+            &self.language,
+            repos,
+            &self.stats_config,
+        );
+        merged.errors = self.errors;
+        merged.errors.extend(other.errors);
+        merged
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RepoMetrics {
     pub slug: String,
+    /// The owner's avatar image, when GitHub sent one
+    pub owner_avatar_url: Option<String>,
     pub top_files: Vec<String>,
+    /// Files touched by the most commits, regardless of how many lines changed
+    pub top_files_by_frequency: Vec<String>,
+    pub top_reporters: Vec<String>,
+    pub unavailable: bool,
+    pub commit_times: CommitTimeDistribution,
+    pub issue_ages: IssueAgeBuckets,
+    /// Mean comment count over the fetched open issues; 0 when there are none
+    pub avg_issue_comments: f64,
+    /// Forks with new commits as `(slug, new commits)`, most active first
+    pub active_forks: Vec<(String, usize)>,
+    /// Collaborator count; `None` when the token couldn't list collaborators
+    pub collaborators: Option<u64>,
+    /// Tag and branch counts; `None` when they couldn't be fetched
+    pub tags: Option<u64>,
+    pub branches: Option<u64>,
+    /// Distinct authors of the fetched commits after identity mapping
+    pub contributors: usize,
+    /// Fraction of open issues not updated within `stale_issue_days`; 0 when there are none
+    pub stale_issue_ratio: f64,
+    /// Fraction of the fetched commits that are merges; 0 when there are none
+    pub merge_commit_ratio: f64,
+}
+
+/// Open issues grouped by how long ago they were created
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IssueAgeBuckets {
+    /// Opened within the last 7 days
+    pub under_week: usize,
+    /// Opened 7 to 30 days ago
+    pub under_month: usize,
+    /// Opened 30 to 365 days ago
+    pub under_year: usize,
+    /// Opened more than a year ago
+    pub older: usize,
+    /// Issues whose creation date couldn't be parsed
+    pub undated: usize,
+}
+
+/// Histogram of when a repository's fetched commits were authored, in the author's own
+/// timezone. Weekday buckets start at Monday.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitTimeDistribution {
+    pub by_weekday: [usize; 7],
+    pub by_hour: [usize; 24],
+    /// Commits whose author date was missing or unparseable
+    pub undated: usize,
+}
+
+impl CommitTimeDistribution {
+    /// Weekday with the most commits, earliest in the week on ties
+    pub fn busiest_weekday(&self) -> Option<chrono::Weekday> {
+        let (idx, &count) = self
+            .by_weekday
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(&a.0)))?;
+        (count > 0).then(|| chrono::Weekday::try_from(idx as u8).expect("index is below 7"))
+    }
 }
 
 pub async fn run() -> Result<(), AppError> {
     let config = AppConfig::load()?;
+    config.github.check_token_requirement()?;
     let service = GitService::new(config.github.clone())?;
-    let mut redis = RedisService::new(config.redis.clone()).await?;
 
-    println!("=== Part A: Fetching GitHub Repository Data ===\n");
+    // ETA lines would only clutter piped or machine-readable output.
+    let interactive = std::io::stdout().is_terminal()
+        && config.output.format == OutputFormat::Text
+        && config.output.verbose_commits != VerboseCommits::Json;
+    let progress = interactive.then(|| {
+        Arc::new(Progress::new(
+            TARGET_LANGUAGES.len() * PROGRESS_UNITS_PER_LANGUAGE,
+        ))
+    });
 
-    let mut language_reports = Vec::new();
+    let errors = run_parts_tracked(
+        &service,
+        &config,
+        std::path::Path::new("./cloned_repos"),
+        || RedisService::new(config.redis.clone()),
+        progress,
+    )
+    .await?;
+
+    let metrics = service.metrics();
+    outln!(
+        "\n{} requests served from cache ({} sent to GitHub)",
+        metrics.cache_hits,
+        metrics.requests
+    );
 
-    for &language in TARGET_LANGUAGES {
-        println!("Processing language: {}", language);
-        println!("{}", "=".repeat(50));
+    report_errors(&errors);
 
-        match collect_language_report(&service, language).await {
+    Ok(())
+}
+
+/// Prints the failures [`run_parts`] skipped over, grouped by language; prints nothing
+/// when there were none.
+pub fn report_errors(errors: &[PhaseError]) {
+    if !errors.is_empty() {
+        outln!("\n=== Errors encountered ===\n");
+        out!("{}", OutputFormatter::format_phase_errors(errors));
+    }
+}
+
+/// Runs the pipeline parts selected by `config.parts` and returns the failures that were
+/// skipped over. Clones go under `clone_base_dir`; `connect_storage` is only called when
+/// Part D runs.
+pub async fn run_parts<S, D, F, Fut>(
+    service: &S,
+    config: &AppConfig,
+    clone_base_dir: &std::path::Path,
+    connect_storage: F,
+) -> Result<Vec<PhaseError>, AppError>
+where
+    S: GitRepositoryService,
+    D: DataStorageService,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<D, AppError>>,
+{
+    run_parts_tracked(service, config, clone_base_dir, connect_storage, None).await
+}
+
+/// [`run_parts`], reporting Part A's progress to `progress` when given.
+async fn run_parts_tracked<S, D, F, Fut>(
+    service: &S,
+    config: &AppConfig,
+    clone_base_dir: &std::path::Path,
+    connect_storage: F,
+    progress: Option<Arc<Progress>>,
+) -> Result<Vec<PhaseError>, AppError>
+where
+    S: GitRepositoryService,
+    D: DataStorageService,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<D, AppError>>,
+{
+    crate::util::decor::set_plain(config.output.plain);
+    // Prometheus scrapers read stdout, so progress and summaries go to stderr instead.
+    crate::util::decor::set_stdout_reserved(config.output.format == OutputFormat::Prometheus);
+    outln!("=== Part A: Fetching GitHub Repository Data ===\n");
+
+    let mut language_reports = Vec::new();
+    let mut errors = Vec::new();
+    let mut outcomes = pin!(orchestrate_tracked(
+        service,
+        TARGET_LANGUAGES,
+        config,
+        progress.as_ref()
+    ));
+
+    while let Some(outcome) = outcomes.next().await {
+        match outcome.result {
             Ok(report) => {
-                println!(
+                outln!(
                     "✓ Successfully fetched {} repositories for {}",
                     report.repos.len(),
-                    language
+                    outcome.language
                 );
-                OutputFormatter::print_summary(&report);
+                errors.extend(report.errors.iter().cloned());
                 language_reports.push(report);
             }
             Err(err) => {
-                eprintln!("✗ Failed to process {}: {}", language, err);
+                errln!("✗ Failed to process {}: {}", outcome.language, err);
+                if let Some(progress) = &progress {
+                    progress.complete(PROGRESS_UNITS_PER_LANGUAGE);
+                }
+                errors
+                    .push(PhaseError::new(Phase::Search, None, err).in_language(&outcome.language));
             }
         }
 
-        println!();
+        outln!();
     }
 
-    // Part C: Clone and inspect repositories
-    let clone_base_dir = std::path::Path::new("./cloned_repos");
-    let cloned_repos = clone::clone_best_repos(
-        &language_reports,
-        clone_base_dir,
-        config.clone.min_source_ratio,
-    )
-    .await?;
+    // Reports arrive in completion order; restore config order before any requested sort.
+    language_reports.sort_by_key(|r| TARGET_LANGUAGES.iter().position(|&l| l == r.language));
+    OutputFormatter::sort_reports(&mut language_reports, config.output.sort);
 
-    // Part D: Store results in Redis (only store the cloned repos, not all 10)
-    println!("\n=== Part D: Storing Results in Redis ===\n");
-    store_cloned_repos_in_redis(&mut redis, &cloned_repos).await?;
+    // Winners are the repos Part C picks, so it runs first when they are highlighted.
+    let mut clone_outcomes = None;
+    if config.output.highlight_winner && config.parts.contains(Part::Clone) {
+        clone_outcomes =
+            Some(clone_part(&language_reports, clone_base_dir, config, &mut errors).await?);
+    }
+    if config.parts.contains(Part::Stats) {
+        let cloned = clone_outcomes.as_deref().unwrap_or_default();
+        report_stats(&language_reports, cloned, config, &mut errors);
+    }
+    if !config.parts.contains(Part::Clone) {
+        return Ok(errors);
+    }
 
-    Ok(())
+    let clone_outcomes = match clone_outcomes {
+        Some(outcomes) => outcomes,
+        None => clone_part(&language_reports, clone_base_dir, config, &mut errors).await?,
+    };
+    let cloned_repos = CloneOutcome::cloned(&clone_outcomes);
+
+    if config.parts.contains(Part::Store) {
+        // Part D: Store results in Redis (only store the cloned repos, not all 10)
+        // Connect only now so an unreachable Redis doesn't cost the results of Parts A–C.
+        outln!("\n=== Part D: Storing Results in Redis ===\n");
+        store_if_connected(connect_storage().await, &cloned_repos).await?;
+    }
+
+    Ok(errors)
+}
+
+/// Part C: clones and inspects the best repository per language.
+async fn clone_part(
+    language_reports: &[LanguageReport],
+    clone_base_dir: &std::path::Path,
+    config: &AppConfig,
+    errors: &mut Vec<PhaseError>,
+) -> Result<Vec<CloneOutcome>, AppError> {
+    let clone_outcomes =
+        clone::clone_best_repos(language_reports, clone_base_dir, &config.clone).await?;
+    outln!("Clone summary:");
+    out!(
+        "{}",
+        OutputFormatter::format_clone_outcomes(&clone_outcomes)
+    );
+    write_clone_summary(clone_base_dir, &clone_outcomes);
+    for outcome in &clone_outcomes {
+        errors.extend(outcome.errors.iter().cloned());
+    }
+    Ok(clone_outcomes)
+}
+
+/// Part B: prints each language's statistics and writes the report files, if configured.
+/// `clone_outcomes` supplies the winners when `highlight_winner` is on and Part C ran.
+fn report_stats(
+    language_reports: &[LanguageReport],
+    clone_outcomes: &[CloneOutcome],
+    config: &AppConfig,
+    errors: &mut Vec<PhaseError>,
+) {
+    match config.output.format {
+        OutputFormat::Text => {
+            for report in language_reports {
+                if config.output.highlight_winner {
+                    let cloned = clone_outcomes
+                        .iter()
+                        .find(|outcome| outcome.language == report.language)
+                        .and_then(|outcome| outcome.result.as_ref().ok());
+                    if let Some(winner) = OutputFormatter::format_winner(report, cloned) {
+                        out!("{winner}");
+                    }
+                }
+                OutputFormatter::print_summary(report);
+                if let Some(details) =
+                    OutputFormatter::format_commit_details(report, config.output.verbose_commits)
+                {
+                    outln!("{details}");
+                }
+                outln!();
+            }
+        }
+        OutputFormat::Prometheus => {
+            crate::util::decor::write_data(&OutputFormatter::format_prometheus(language_reports));
+        }
+    }
+    if let Some(dir) = &config.output.report_dir {
+        match OutputFormatter::write_report_files(language_reports, dir) {
+            Ok(paths) => outln!(
+                "✓ Wrote {} report files to {}\n",
+                paths.len(),
+                dir.display()
+            ),
+            Err(e) => {
+                errln!("⚠ Failed to write reports to {}: {}\n", dir.display(), e);
+                errors.push(PhaseError::new(
+                    Phase::Report,
+                    None,
+                    format_args!("{}: {}", dir.display(), e),
+                ));
+            }
+        }
+    }
+}
+
+/// Saves the clone outcomes as `clone_summary.json` next to the clones; failing to
+/// write it only warns.
+fn write_clone_summary(clone_base_dir: &std::path::Path, outcomes: &[CloneOutcome]) {
+    let path = clone_base_dir.join("clone_summary.json");
+    let json = OutputFormatter::clone_outcomes_json(outcomes);
+    let written = std::fs::create_dir_all(clone_base_dir).and_then(|()| {
+        std::fs::write(
+            &path,
+            serde_json::to_string_pretty(&json).unwrap_or_default(),
+        )
+    });
+    if let Err(e) = written {
+        errln!("⚠ Failed to write {}: {}", path.display(), e);
+    }
+}
+
+/// Result of processing a single language, yielded by [`orchestrate_stream`].
+#[derive(Debug)]
+pub struct LanguageOutcome {
+    pub language: String,
+    pub result: Result<LanguageReport, AppError>,
+}
+
+/// Collects every language concurrently and yields each outcome as soon as it finishes.
+pub fn orchestrate_stream<'a, S: GitRepositoryService>(
+    service: &'a S,
+    languages: &'a [&'a str],
+    config: &'a AppConfig,
+) -> impl Stream<Item = LanguageOutcome> + 'a {
+    orchestrate_tracked(service, languages, config, None)
+}
+
+/// [`orchestrate_stream`], with every language reporting its progress to `progress`.
+fn orchestrate_tracked<'a, S: GitRepositoryService>(
+    service: &'a S,
+    languages: &'a [&'a str],
+    config: &'a AppConfig,
+    progress: Option<&'a Arc<Progress>>,
+) -> impl Stream<Item = LanguageOutcome> + 'a {
+    languages
+        .iter()
+        .map(|&language| async move {
+            outln!("Processing language: {}", language);
+            outln!("{}", "=".repeat(50));
+
+            LanguageOutcome {
+                language: language.to_string(),
+                result: collect_language_report_tracked(service, language, config, progress).await,
+            }
+        })
+        .collect::<FuturesUnordered<_>>()
 }
 
 pub async fn collect_language_report(
     service: &GitService,
     language: &str,
 ) -> Result<LanguageReport, AppError> {
-    let fetcher = RepoFetcher::new(service);
-    let repos = fetcher.fetch_language_data(language).await?;
+    collect_language_report_with_config(service, language, &AppConfig::default()).await
+}
+
+pub async fn collect_language_report_with_config<S: GitRepositoryService>(
+    service: &S,
+    language: &str,
+    config: &AppConfig,
+) -> Result<LanguageReport, AppError> {
+    collect_language_report_tracked(service, language, config, None).await
+}
+
+/// [`collect_language_report_with_config`], reporting finished work to `progress`.
+async fn collect_language_report_tracked<S: GitRepositoryService>(
+    service: &S,
+    language: &str,
+    config: &AppConfig,
+    progress: Option<&Arc<Progress>>,
+) -> Result<LanguageReport, AppError> {
+    let language = config.github.language_aliases.resolve(language);
+    let mut fetcher = RepoFetcher::with_config(service, config.fetch.clone())
+        .with_max_concurrency(config.github.max_concurrency);
+    if let Some(progress) = progress {
+        fetcher = fetcher.with_progress(progress.clone());
+    }
+    let repos = fetcher.fetch_language_data(&language).await?;
+
+    let mut report =
+        StatsCalculator::build_language_report_with_config(&language, repos, &config.stats);
+    report.errors = fetcher.take_errors(&language);
+    Ok(report)
+}
 
-    Ok(StatsCalculator::build_language_report(language, repos))
+/// Like [`collect_language_report_with_config`], but every request takes a permit from
+/// `permits` first. Callers collecting several languages at once pass the same semaphore
+/// to each so the total number of requests in flight stays bounded.
+pub async fn collect_language_report_bounded<S: GitRepositoryService>(
+    service: &S,
+    language: &str,
+    config: &AppConfig,
+    permits: Arc<Semaphore>,
+) -> Result<LanguageReport, AppError> {
+    let bounded = BoundedGitService::new(service, permits);
+    collect_language_report_with_config(&bounded, language, config).await
+}
+
+/// Builds a report for all repositories of a user or organization, keyed by its login.
+pub async fn collect_owner_report<S: GitRepositoryService>(
+    service: &S,
+    login: &str,
+    config: &AppConfig,
+) -> Result<LanguageReport, AppError> {
+    let fetcher = RepoFetcher::with_config(service, config.fetch.clone())
+        .with_max_concurrency(config.github.max_concurrency);
+    let repos = fetcher.fetch_owner_data(login).await?;
+
+    let mut report =
+        StatsCalculator::build_language_report_with_config(login, repos, &config.stats);
+    report.errors = fetcher.take_errors(login);
+    Ok(report)
+}
+
+/// Stores the cloned repositories if the storage connection succeeded; otherwise warns
+/// and returns `Ok(false)` so the results already printed aren't turned into a failed run.
+pub async fn store_if_connected<D: DataStorageService>(
+    storage: Result<D, AppError>,
+    cloned_repos: &[ClonedRepo],
+) -> Result<bool, AppError> {
+    match storage {
+        Ok(mut storage) => {
+            store_cloned_repos(&mut storage, cloned_repos).await?;
+            Ok(true)
+        }
+        Err(e) => {
+            errln!("⚠ Redis is unavailable, skipping storage: {e}");
+            Ok(false)
+        }
+    }
 }
 
-async fn store_cloned_repos_in_redis(
-    redis: &mut RedisService,
-    cloned_repos: &[Repo],
+/// Stores the cloned repositories and their source analysis in the given storage
+pub async fn store_cloned_repos<D: DataStorageService>(
+    storage: &mut D,
+    cloned_repos: &[ClonedRepo],
 ) -> Result<(), AppError> {
     if cloned_repos.is_empty() {
-        println!("⚠ No repositories were cloned, skipping Redis storage");
+        outln!("⚠ No repositories were cloned, skipping Redis storage");
         return Ok(());
     }
 
-    println!(
+    outln!(
         "  Storing {} most popular source code repositories...",
         cloned_repos.len()
     );
 
-    for repo in cloned_repos {
-        redis.store_repository(repo).await?;
-        println!(
+    for ClonedRepo { repo, analysis } in cloned_repos {
+        storage.store_repository(repo).await?;
+        storage.store_code_analysis(repo, analysis).await?;
+        outln!(
             "    ✓ Stored {}/{} ({} stars)",
-            repo.owner.login, repo.name, repo.stargazers_count
+            repo.owner.login,
+            repo.name,
+            repo.stargazers_count
         );
     }
 
-    println!(
+    outln!(
         "\n✓ Successfully stored {} repositories in Redis",
         cloned_repos.len()
     );
@@ -1,26 +1,411 @@
 //! Output formatting.
 
+use std::cmp::Reverse;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+use crate::app::clone::{CloneOutcome, ClonedRepo};
+use crate::app::phase_error::PhaseError;
 use crate::app::LanguageReport;
+use crate::config::{OutputSort, VerboseCommits};
+use crate::error::AppError;
+use crate::model::Repo;
+use crate::out;
 
 pub struct OutputFormatter;
 
 impl OutputFormatter {
+    /// Reorders collected reports for printing. Sorting is stable, so ties keep config order.
+    pub fn sort_reports(reports: &mut [LanguageReport], sort: OutputSort) {
+        match sort {
+            OutputSort::Config => {}
+            OutputSort::Alphabetical => reports.sort_by(|a, b| a.language.cmp(&b.language)),
+            OutputSort::Stars => reports.sort_by_key(|r| Reverse(r.total_stars)),
+        }
+    }
+
     pub fn print_summary(report: &LanguageReport) {
-        println!("Language: {}", report.language);
-        println!("Total stars: {}", report.total_stars);
-        println!("Total forks: {}", report.total_forks);
-        println!("Top-3 Most modified file per repo:");
+        out!("{}", Self::format_summary(report));
+    }
+
+    pub fn format_summary(report: &LanguageReport) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Language: {}", report.language);
+        let _ = writeln!(out, "Total stars: {}", report.total_stars);
+        let _ = writeln!(out, "Total forks: {}", report.total_forks);
+        let _ = writeln!(
+            out,
+            "Top-{} Most modified file per repo:",
+            report.stats_config.top_files_count
+        );
         for metrics in &report.repo_metrics {
-            println!("  Repo name: {}", metrics.slug);
-            if metrics.top_files.is_empty() {
-                println!("    No files modified in recent commits");
+            let _ = writeln!(out, "  Repo name: {}", metrics.slug);
+            if metrics.unavailable {
+                let _ = writeln!(out, "    Unavailable (blocked by GitHub)");
+            } else if metrics.top_files.is_empty() {
+                let _ = writeln!(out, "    No files modified in recent commits");
             } else {
                 for (idx, file) in metrics.top_files.iter().enumerate() {
-                    println!("    File name{}: {}", idx + 1, file);
+                    let _ = writeln!(out, "    File name{}: {}", idx + 1, file);
+                }
+            }
+        }
+        let _ = writeln!(
+            out,
+            "New commits in forked repos: {}",
+            report.new_fork_commits
+        );
+        let _ = writeln!(
+            out,
+            "Open issues in top-10 repos: {}",
+            report.total_open_issues
+        );
+        out
+    }
+
+    /// A banner naming the language's best repository with its key metrics. That is the
+    /// repository Part C cloned when given, otherwise the most starred available one.
+    /// `None` when the report has no available repository.
+    pub fn format_winner(report: &LanguageReport, cloned: Option<&ClonedRepo>) -> Option<String> {
+        let winner = match cloned {
+            Some(cloned) => &cloned.repo,
+            None => report
+                .repos
+                .iter()
+                .filter(|repo| !repo.unavailable)
+                .max_by_key(|repo| (repo.stargazers_count, Reverse(repo.id)))?,
+        };
+        let slug = winner.slug();
+        let metrics = report.repo_metrics.iter().find(|m| m.slug == slug);
+
+        let mut out = String::new();
+        let _ = writeln!(out, "{}", "=".repeat(50));
+        let _ = writeln!(
+            out,
+            "Winner for {}: {} ({})",
+            report.language,
+            slug,
+            if cloned.is_some() {
+                "best source code repository"
+            } else {
+                "most starred"
+            }
+        );
+        let _ = writeln!(
+            out,
+            "  Stars: {}, forks: {}, open issues: {}",
+            winner.stargazers_count, winner.forks_count, winner.open_issues_count
+        );
+        if let Some(cloned) = cloned {
+            let _ = writeln!(
+                out,
+                "  Source files: {} ({:.1}% of files)",
+                cloned.analysis.source_files,
+                cloned.analysis.source_ratio * 100.0
+            );
+        }
+        if let Some(metrics) = metrics {
+            if !metrics.top_files.is_empty() {
+                let _ = writeln!(out, "  Most modified: {}", metrics.top_files.join(", "));
+            }
+            let _ = writeln!(out, "  Contributors: {}", metrics.contributors);
+        }
+        let _ = writeln!(out, "{}", "=".repeat(50));
+        Some(out)
+    }
+
+    /// Lists every fetched commit per repo with the files it touched, as text or JSON.
+    /// Returns `None` when verbose commit output is off.
+    pub fn format_commit_details(report: &LanguageReport, mode: VerboseCommits) -> Option<String> {
+        match mode {
+            VerboseCommits::Off => None,
+            VerboseCommits::Text => {
+                let mut out = String::new();
+                for repo in &report.repos {
+                    let _ = writeln!(out, "Commits for {}:", repo.slug());
+                    for commit in &repo.recent_commits {
+                        let _ = writeln!(out, "  {}", commit.sha);
+                        for file in &commit.files {
+                            let _ = writeln!(
+                                out,
+                                "    {} (+{} -{})",
+                                file.filename, file.additions, file.deletions
+                            );
+                        }
+                    }
+                }
+                Some(out)
+            }
+            VerboseCommits::Json => {
+                let repos: Vec<_> = report
+                    .repos
+                    .iter()
+                    .map(|repo| {
+                        json!({
+                            "repo": repo.slug(),
+                            "commits": repo.recent_commits.iter().map(|commit| json!({
+                                "sha": commit.sha,
+                                "files": commit.files.iter().map(|file| json!({
+                                    "filename": file.filename,
+                                    "additions": file.additions,
+                                    "deletions": file.deletions,
+                                })).collect::<Vec<_>>(),
+                            })).collect::<Vec<_>>(),
+                        })
+                    })
+                    .collect();
+                serde_json::to_string_pretty(&repos).ok()
+            }
+        }
+    }
+
+    /// One line per language: the cloned repo, or why nothing was cloned.
+    pub fn format_clone_outcomes(outcomes: &[CloneOutcome]) -> String {
+        let mut out = String::new();
+        for outcome in outcomes {
+            match &outcome.result {
+                Ok(cloned) => {
+                    let _ = writeln!(
+                        out,
+                        "  {}: cloned {} ({} source files)",
+                        outcome.language,
+                        cloned.repo.slug(),
+                        cloned.analysis.source_files
+                    );
+                }
+                Err(reason) => {
+                    let _ = writeln!(out, "  {}: skipped, {}", outcome.language, reason);
+                }
+            }
+        }
+        out
+    }
+
+    /// JSON form of the clone outcomes, one object per language.
+    pub fn clone_outcomes_json(outcomes: &[CloneOutcome]) -> Value {
+        outcomes
+            .iter()
+            .map(|outcome| match &outcome.result {
+                Ok(cloned) => json!({
+                    "language": outcome.language,
+                    "status": "cloned",
+                    "repo": cloned.repo.slug(),
+                    "stars": cloned.repo.stargazers_count,
+                    "source_files": cloned.analysis.source_files,
+                    "total_files": cloned.analysis.total_files,
+                    "source_ratio": cloned.analysis.source_ratio,
+                    "is_monorepo": cloned.analysis.is_monorepo,
+                    "file_extensions": cloned.analysis.file_extensions,
+                }),
+                Err(reason) => json!({
+                    "language": outcome.language,
+                    "status": "skipped",
+                    "reason": reason.code(),
+                    "detail": reason.to_string(),
+                }),
+            })
+            .collect()
+    }
+
+    /// All reports as gauges in the Prometheus text exposition format: per-language
+    /// totals labelled by `language`, and per-repo values labelled by `language` and `repo`.
+    pub fn format_prometheus(reports: &[LanguageReport]) -> String {
+        type LanguageGauge = (&'static str, &'static str, fn(&LanguageReport) -> u64);
+        type RepoGauge = (&'static str, &'static str, fn(&Repo) -> u64);
+
+        const LANGUAGE_GAUGES: &[LanguageGauge] = &[
+            (
+                "github_language_stars",
+                "Total stars of the top repositories for a language.",
+                |r| r.total_stars,
+            ),
+            (
+                "github_language_forks",
+                "Total forks of the top repositories for a language.",
+                |r| r.total_forks,
+            ),
+            (
+                "github_language_open_issues",
+                "Open issues fetched for the top repositories for a language.",
+                |r| r.total_open_issues as u64,
+            ),
+            (
+                "github_language_commits",
+                "Recent commits fetched for the top repositories for a language.",
+                |r| r.total_repo_commits as u64,
+            ),
+            (
+                "github_language_new_fork_commits",
+                "Commits made in forks after they were created.",
+                |r| r.new_fork_commits as u64,
+            ),
+        ];
+        const REPO_GAUGES: &[RepoGauge] = &[
+            ("github_repo_stars", "Stars of a repository.", |r| {
+                r.stargazers_count
+            }),
+            ("github_repo_forks", "Forks of a repository.", |r| {
+                r.forks_count
+            }),
+            (
+                "github_repo_open_issues",
+                "Open issues of a repository as reported by GitHub.",
+                |r| r.open_issues_count,
+            ),
+            (
+                "github_repo_commits",
+                "Recent commits fetched for a repository.",
+                |r| r.commit_count,
+            ),
+        ];
+
+        let mut out = String::new();
+        for (name, help, value) in LANGUAGE_GAUGES {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} gauge");
+            for report in reports {
+                let language = escape_label(&report.language);
+                let _ = writeln!(out, "{name}{{language=\"{language}\"}} {}", value(report));
+            }
+        }
+        for (name, help, value) in REPO_GAUGES {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} gauge");
+            for report in reports {
+                let language = escape_label(&report.language);
+                for repo in &report.repos {
+                    let _ = writeln!(
+                        out,
+                        "{name}{{language=\"{language}\",repo=\"{}\"}} {}",
+                        escape_label(&repo.slug()),
+                        value(repo)
+                    );
+                }
+            }
+        }
+        out
+    }
+
+    /// One line per failure, grouped under the language it happened in. Languages keep
+    /// the order of their first failure and get a single header each.
+    pub fn format_phase_errors(errors: &[PhaseError]) -> String {
+        let mut languages: Vec<&str> = Vec::new();
+        for error in errors {
+            if !languages.contains(&error.language.as_str()) {
+                languages.push(&error.language);
+            }
+        }
+
+        let mut out = String::new();
+        for language in languages {
+            if !language.is_empty() {
+                let _ = writeln!(out, "{language}:");
+            }
+            for error in errors.iter().filter(|error| error.language == language) {
+                match &error.repo {
+                    Some(repo) => {
+                        let _ = writeln!(out, "  ✗ [{}] {}: {}", error.phase, repo, error.message);
+                    }
+                    None => {
+                        let _ = writeln!(out, "  ✗ [{}] {}", error.phase, error.message);
+                    }
                 }
             }
         }
-        println!("New commits in forked repos: {}", report.new_fork_commits);
-        println!("Open issues in top-10 repos: {}", report.total_open_issues);
+        out
+    }
+
+    pub fn phase_errors_json(errors: &[PhaseError]) -> Value {
+        Value::Array(
+            errors
+                .iter()
+                .map(|error| {
+                    json!({
+                        "language": error.language,
+                        "repo": error.repo,
+                        "phase": error.phase.as_str(),
+                        "message": error.message,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// JSON form of a language report: totals plus the per-repo metrics.
+    pub fn report_json(report: &LanguageReport) -> Value {
+        json!({
+            "language": report.language,
+            "total_stars": report.total_stars,
+            "total_forks": report.total_forks,
+            "total_open_issues": report.total_open_issues,
+            "total_repo_commits": report.total_repo_commits,
+            "new_fork_commits": report.new_fork_commits,
+            "repos": report.repo_metrics.iter().map(|metrics| json!({
+                "repo": metrics.slug,
+                "owner_avatar_url": metrics.owner_avatar_url,
+                "unavailable": metrics.unavailable,
+                "top_files": metrics.top_files,
+                "top_files_by_frequency": metrics.top_files_by_frequency,
+                "top_reporters": metrics.top_reporters,
+                "avg_issue_comments": metrics.avg_issue_comments,
+                "collaborators": metrics.collaborators,
+                "tags": metrics.tags,
+                "branches": metrics.branches,
+                "contributors": metrics.contributors,
+                "stale_issue_ratio": metrics.stale_issue_ratio,
+                "merge_commit_ratio": metrics.merge_commit_ratio,
+                "active_forks": metrics.active_forks.iter().map(|(slug, commits)| json!({
+                    "fork": slug,
+                    "new_commits": commits,
+                })).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+            "errors": Self::phase_errors_json(&report.errors),
+        })
+    }
+
+    /// Writes each report to `<dir>/<language>.json`, creating `dir` if needed, and
+    /// returns the written paths in report order.
+    pub fn write_report_files(
+        reports: &[LanguageReport],
+        dir: &Path,
+    ) -> Result<Vec<PathBuf>, AppError> {
+        std::fs::create_dir_all(dir)?;
+        reports
+            .iter()
+            .map(|report| {
+                let path = dir.join(format!("{}.json", report_file_stem(&report.language)));
+                let body = serde_json::to_string_pretty(&Self::report_json(report))?;
+                std::fs::write(&path, body)?;
+                Ok(path)
+            })
+            .collect()
+    }
+}
+
+/// Escapes a Prometheus label value: backslash, double quote and newline.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// File-system-safe name for a language: `+` becomes `p` and `#` becomes `Sharp`
+/// (`C++` -> `Cpp`, `C#` -> `CSharp`), anything else outside `[A-Za-z0-9_-]` becomes `_`.
+pub fn report_file_stem(language: &str) -> String {
+    let mut stem = String::with_capacity(language.len());
+    for c in language.trim().chars() {
+        match c {
+            '+' => stem.push('p'),
+            '#' => stem.push_str("Sharp"),
+            c if c.is_ascii_alphanumeric() || c == '-' || c == '_' => stem.push(c),
+            _ => stem.push('_'),
+        }
+    }
+    if stem.is_empty() {
+        stem.push_str("unknown");
     }
+    stem
 }
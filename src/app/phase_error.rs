@@ -0,0 +1,72 @@
+//! Failures collected over a run.
+
+use std::fmt;
+
+/// Step of the run a failure happened in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Search,
+    Details,
+    Commits,
+    CommitDetails,
+    Issues,
+    Forks,
+    ForkCommits,
+    Collaborators,
+    RefCounts,
+    Cache,
+    Clone,
+    Report,
+}
+
+impl Phase {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Search => "search",
+            Self::Details => "details",
+            Self::Commits => "commits",
+            Self::CommitDetails => "commit details",
+            Self::Issues => "issues",
+            Self::Forks => "forks",
+            Self::ForkCommits => "fork commits",
+            Self::Collaborators => "collaborators",
+            Self::RefCounts => "tags and branches",
+            Self::Cache => "cache",
+            Self::Clone => "clone",
+            Self::Report => "report",
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A failure that was logged and skipped over, kept for the summary at the end of the run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhaseError {
+    /// Language (or owner login) being processed; empty for failures outside any language.
+    pub language: String,
+    /// `owner/name` of the repository involved, if any.
+    pub repo: Option<String>,
+    pub phase: Phase,
+    pub message: String,
+}
+
+impl PhaseError {
+    pub fn new(phase: Phase, repo: Option<String>, message: impl fmt::Display) -> Self {
+        Self {
+            language: String::new(),
+            repo,
+            phase,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn in_language(mut self, language: &str) -> Self {
+        self.language = language.to_string();
+        self
+    }
+}
@@ -1,31 +1,109 @@
 //! Repository fetching.
 
+use std::cmp::Reverse;
+use std::sync::{Arc, Mutex};
+
+use futures::stream::{self, StreamExt};
+
+use crate::app::cache::RepoCache;
+use crate::app::phase_error::{Phase, PhaseError};
+use crate::config::{CommitDetailOrder, FetchConfig, ForkDivergence, GitHubConfig};
 use crate::error::AppError;
-use crate::model::Repo;
+use crate::model::{Commit, Repo};
 use crate::service::traits::GitRepositoryService;
+use crate::util::progress::Progress;
+use crate::{errln, outln};
 
 /// # top repositories to fetch per language
 const TOP_REPOSITORIES_COUNT: u8 = 10;
 
-/// max # of commits to fetch detailed file information for
-const MAX_COMMITS_WITH_FILES: usize = 50;
-
 /// max # of forks to process commits for
 const MAX_FORKS_TO_PROCESS: usize = 20;
 
+/// Progress units per repository: its commits and issues, then its forks
+const PROGRESS_UNITS_PER_REPO: usize = 2;
+
+/// Progress units a language accounts for when all top repositories are enriched
+pub const PROGRESS_UNITS_PER_LANGUAGE: usize =
+    TOP_REPOSITORIES_COUNT as usize * PROGRESS_UNITS_PER_REPO;
+
 pub struct RepoFetcher<'a, S: GitRepositoryService> {
     service: &'a S,
+    config: FetchConfig,
+    /// Maximum fork requests in flight per repository
+    max_concurrency: usize,
+    /// Failures logged while enriching, kept for the end-of-run summary
+    errors: Mutex<Vec<PhaseError>>,
+    /// Tracker for the ETA line, shared with the other languages
+    progress: Option<Arc<Progress>>,
 }
 
 impl<'a, S: GitRepositoryService> RepoFetcher<'a, S> {
     /// Creates a new repo fetcher with any Git service
     pub fn new(service: &'a S) -> Self {
-        Self { service }
+        Self::with_config(service, FetchConfig::default())
+    }
+
+    /// Creates a repo fetcher with explicit fetch settings
+    pub fn with_config(service: &'a S, config: FetchConfig) -> Self {
+        Self {
+            service,
+            config,
+            max_concurrency: GitHubConfig::DEFAULT_MAX_CONCURRENCY,
+            errors: Mutex::new(Vec::new()),
+            progress: None,
+        }
+    }
+
+    /// Caps fork requests in flight per repository; `run` passes `GitHubConfig::max_concurrency`
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// Reports finished work to `progress`; `run` passes one on interactive text output
+    pub fn with_progress(mut self, progress: Arc<Progress>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Hands over the failures recorded so far, tagged with `language`
+    pub fn take_errors(&self, language: &str) -> Vec<PhaseError> {
+        let mut errors = self.errors.lock().unwrap_or_else(|e| e.into_inner());
+        std::mem::take(&mut *errors)
+            .into_iter()
+            .map(|error| error.in_language(language))
+            .collect()
+    }
+
+    fn record(&self, phase: Phase, repo: &Repo, error: impl std::fmt::Display) {
+        self.errors
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(PhaseError::new(phase, Some(repo.slug()), error));
+    }
+
+    /// Whether a failure was recorded for `repo` or one of its forks in this pass
+    fn has_errors_for(&self, repo: &Repo) -> bool {
+        let slug = repo.slug();
+        let fork_slugs: Vec<String> = repo.forks.iter().map(Repo::slug).collect();
+        self.errors
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter_map(|error| error.repo.as_deref())
+            .any(|failed| failed == slug || fork_slugs.iter().any(|fork| fork == failed))
+    }
+
+    fn advance(&self, units: usize) {
+        if let Some(progress) = &self.progress {
+            progress.complete(units);
+        }
     }
 
     /// Fetches comprehensive data for repositories of a specific language
     pub async fn fetch_language_data(&self, language: &str) -> Result<Vec<Repo>, AppError> {
-        println!(
+        outln!(
             "  [1/4] Fetching top {} repositories...",
             TOP_REPOSITORIES_COUNT
         );
@@ -33,144 +111,476 @@ impl<'a, S: GitRepositoryService> RepoFetcher<'a, S> {
             .service
             .fetch_top_repositories(language, TOP_REPOSITORIES_COUNT)
             .await?;
-        
+
+        if !self.config.include_archived {
+            let before = repos.len();
+            repos.retain(|r| !r.archived);
+            if repos.len() < before {
+                outln!("      ✓ Skipped {} archived repositories", before - repos.len());
+            }
+        }
+
+        self.fill_repo_details(&mut repos).await;
+
+        if self.config.strict_language_match {
+            let before = repos.len();
+            repos.retain(|r| {
+                r.language
+                    .as_deref()
+                    .is_some_and(|l| l.eq_ignore_ascii_case(language))
+            });
+            if repos.len() < before {
+                outln!(
+                    "      ✓ Skipped {} repositories not primarily written in {}",
+                    before - repos.len(),
+                    language
+                );
+            }
+        }
+
         // Filter for C language: find first repo with issues enabled
         if language == "C" {
             if let Some(repo_with_issues) = repos.iter().find(|r| r.has_issues && r.open_issues_count > 0) {
-                println!("      ✓ Found C repository with issues: {}", repo_with_issues.slug());
+                outln!("      ✓ Found C repository with issues: {}", repo_with_issues.slug());
                 let target_repo = repo_with_issues.clone();
                 repos = vec![target_repo];
             } else {
-                println!("      ⚠ No C repository with issues found in top results");
+                outln!("      ⚠ No C repository with issues found in top results");
                 repos.clear();
             }
         }
 
-        println!("      ✓ Found {} repositories", repos.len());
+        outln!("      ✓ Found {} repositories", repos.len());
+        // Repositories filtered out above won't be enriched; count them as done.
+        let skipped = (TOP_REPOSITORIES_COUNT as usize).saturating_sub(repos.len());
+        self.advance(skipped * PROGRESS_UNITS_PER_REPO);
 
-        println!("  [2/4] Fetching commits and issues for each repository...");
-        self.enrich_with_commits_and_issues(&mut repos).await;
-
-        println!("  [3/4] Fetching forks for each repository...");
-        self.enrich_with_forks(&mut repos).await;
-
-        println!("  [4/4] Fetching commits for forked repositories...");
-        self.enrich_forks_with_commits(&mut repos).await;
+        self.enrich(&mut repos).await;
 
         Ok(repos)
     }
 
-    /// Enriches repositories with commit and issue data (concurrent per repo)
-    async fn enrich_with_commits_and_issues(&self, repos: &mut [Repo]) {
+    /// Replaces search-result fields with the full repository details, keeping the
+    /// search data for any repo whose details can't be fetched
+    async fn fill_repo_details(&self, repos: &mut [Repo]) {
         for repo in repos.iter_mut() {
-            // Fetch commits and issues concurrently
-            let commits_future = self
-                .service
-                .fetch_recent_commits(&repo.owner.login, &repo.name);
-            let issues_future = self
+            match self
                 .service
-                .fetch_open_issues(&repo.owner.login, &repo.name);
+                .fetch_repo_details(&repo.owner.login, &repo.name)
+                .await
+            {
+                Ok(details) => repo.fill_details(&details),
+                Err(e) => {
+                    errln!("      ⚠ Failed to fetch details for {}: {}", repo.slug(), e);
+                    self.record(Phase::Details, repo, e);
+                }
+            }
+        }
+    }
 
-            match tokio::join!(commits_future, issues_future) {
-                (Ok(commits), Ok(issues)) => {
-                    println!("      ✓ {}: {} commits", repo.slug(), commits.len());
-                    repo.commit_count = commits.len() as u64;
+    /// Fetches and enriches every repository owned by a user or organization
+    pub async fn fetch_owner_data(&self, login: &str) -> Result<Vec<Repo>, AppError> {
+        outln!("  [1/4] Fetching repositories owned by {}...", login);
+        let mut repos = self.service.fetch_user_repositories(login).await?;
 
-                    let mut detailed_commits = Vec::new();
-                    for commit in commits.iter().take(MAX_COMMITS_WITH_FILES) {
-                        match self
-                            .service
-                            .fetch_commit_with_files(&repo.owner.login, &repo.name, &commit.sha)
-                            .await
-                        {
-                            Ok(detailed) => detailed_commits.push(detailed),
-                            Err(e) => {
-                                eprintln!(
-                                    "        ⚠ Failed to fetch details for commit {}: {}",
-                                    &commit.sha[..7],
-                                    e
-                                );
-                            }
-                        }
-                    }
-                    repo.recent_commits = detailed_commits;
-                    repo.issues = issues;
-                    println!("      ✓ {}: {} open issues", repo.slug(), repo.issues.len());
+        if !self.config.include_archived {
+            repos.retain(|r| !r.archived);
+        }
+
+        outln!("      ✓ Found {} repositories", repos.len());
+
+        self.enrich(&mut repos).await;
+
+        Ok(repos)
+    }
+
+    /// Runs the commit, issue and fork enrichment steps, reusing cached data for
+    /// repositories whose `pushed_at` hasn't changed since they were last enriched
+    async fn enrich(&self, repos: &mut [Repo]) {
+        let cache = self.config.cache_dir.as_ref().map(RepoCache::new);
+
+        let mut stale_indices = Vec::new();
+        for (idx, repo) in repos.iter_mut().enumerate() {
+            let cached = match &cache {
+                Some(cache) if !self.config.force_refresh => cache.load_unchanged(repo),
+                _ => None,
+            };
+            match cached {
+                Some(cached) => {
+                    outln!(
+                        "      ✓ {}: unchanged since last run, using cache",
+                        repo.slug()
+                    );
+                    repo.forks = cached.forks;
+                    repo.recent_commits = cached.recent_commits;
+                    repo.issues = cached.issues;
+                    repo.commit_count = cached.commit_count;
+                    repo.collaborators_count = cached.collaborators_count;
+                    repo.tags_count = cached.tags_count;
+                    repo.branches_count = cached.branches_count;
+                    self.advance(PROGRESS_UNITS_PER_REPO);
+                }
+                None => stale_indices.push(idx),
+            }
+        }
+
+        let mut stale: Vec<Repo> = stale_indices.iter().map(|&i| repos[i].clone()).collect();
+
+        outln!("  [2/4] Fetching commits and issues for each repository...");
+        self.enrich_with_commits_and_issues(&mut stale).await;
+
+        outln!("  [3/4] Fetching forks for each repository...");
+        self.enrich_with_forks(&mut stale).await;
+
+        if self.config.skip_fork_commits {
+            outln!("  [4/4] Skipping commits for forked repositories");
+            self.advance(stale.len());
+        } else {
+            match self.config.fork_divergence {
+                ForkDivergence::Commits => {
+                    outln!("  [4/4] Fetching commits for forked repositories...");
+                    self.enrich_forks_with_commits(&mut stale).await;
                 }
-                (Err(e), _) => {
-                    eprintln!("      ✗ Failed to fetch commits for {}: {}", repo.slug(), e);
+                ForkDivergence::Compare => {
+                    outln!("  [4/4] Comparing forked repositories with their parents...");
+                    self.enrich_forks_with_ahead_by(&mut stale).await;
                 }
-                (_, Err(e)) => {
-                    eprintln!("      ✗ Failed to fetch issues for {}: {}", repo.slug(), e);
+            }
+        }
+
+        if self.config.fetch_collaborators {
+            self.enrich_with_collaborators(&mut stale).await;
+        }
+        if self.config.fetch_ref_counts {
+            self.enrich_with_ref_counts(&mut stale).await;
+        }
+
+        for (idx, repo) in stale_indices.into_iter().zip(stale) {
+            // Partly enriched repos would be served as fresh until their next push.
+            let complete = !repo.unavailable && !self.has_errors_for(&repo);
+            if let Some(cache) = cache.as_ref().filter(|_| complete) {
+                if let Err(e) = cache.store(&repo) {
+                    errln!("      ⚠ Failed to cache {}: {}", repo.slug(), e);
+                    self.record(Phase::Cache, &repo, e);
                 }
             }
+            repos[idx] = repo;
         }
     }
 
-    /// Enriches repositories with fork data (in parallel)
+    /// Enriches repositories with commit and issue data, `repo_concurrency` repos at a time
+    async fn enrich_with_commits_and_issues(&self, repos: &mut [Repo]) {
+        stream::iter(repos.iter_mut())
+            .map(|repo| self.enrich_repo_with_commits_and_issues(repo))
+            .buffer_unordered(self.config.repo_concurrency.max(1))
+            .collect::<()>()
+            .await;
+    }
+
+    /// Fetches one repository's commits and issues concurrently; a failure is logged and
+    /// recorded, leaving the repository as it was
+    async fn enrich_repo_with_commits_and_issues(&self, repo: &mut Repo) {
+        let commits_future =
+            self.service
+                .fetch_recent_commits(&repo.owner.login, &repo.name, None, None);
+        let issues_future = self
+            .service
+            .fetch_open_issues(&repo.owner.login, &repo.name);
+
+        match tokio::join!(commits_future, issues_future) {
+            (Ok(commits), Ok(issues)) => {
+                outln!("      ✓ {}: {} commits", repo.slug(), commits.len());
+                repo.commit_count = commits.len() as u64;
+                repo.recent_commits = if self.config.skip_commit_details {
+                    commits
+                } else {
+                    self.fetch_commit_details(repo, &commits).await
+                };
+                repo.issues = issues;
+                outln!("      ✓ {}: {} open issues", repo.slug(), repo.issues.len());
+            }
+            (Err(AppError::RepositoryUnavailable { status, .. }), _)
+            | (_, Err(AppError::RepositoryUnavailable { status, .. })) => {
+                errln!(
+                    "      ⚠ {} is unavailable (HTTP {}), skipping it",
+                    repo.slug(),
+                    status
+                );
+                repo.unavailable = true;
+            }
+            (Err(e), _) => {
+                errln!("      ✗ Failed to fetch commits for {}: {}", repo.slug(), e);
+                self.record(Phase::Commits, repo, e);
+            }
+            (_, Err(e)) => {
+                errln!("      ✗ Failed to fetch issues for {}: {}", repo.slug(), e);
+                self.record(Phase::Issues, repo, e);
+            }
+        }
+        self.advance(1);
+    }
+
+    /// Fetches file details for up to `max_commit_details` commits,
+    /// `commit_detail_concurrency` at a time, keeping the commits' order. Commits whose
+    /// details can't be fetched are left out.
+    async fn fetch_commit_details(&self, repo: &Repo, commits: &[Commit]) -> Vec<Commit> {
+        let results: Vec<_> = stream::iter(self.commits_for_details(commits))
+            .map(|commit| async move {
+                let result = self
+                    .service
+                    .fetch_commit_with_files(&repo.owner.login, &repo.name, &commit.sha)
+                    .await;
+                (commit, result)
+            })
+            .buffered(self.config.commit_detail_concurrency.max(1))
+            .collect()
+            .await;
+
+        let mut detailed_commits = Vec::new();
+        for (commit, result) in results {
+            match result {
+                Ok(detailed) => detailed_commits.push(detailed),
+                Err(e) => {
+                    let short_sha = commit.sha.get(..7).unwrap_or(&commit.sha);
+                    errln!(
+                        "        ⚠ Failed to fetch details for commit {} of {}: {}",
+                        short_sha,
+                        repo.slug(),
+                        e
+                    );
+                    self.record(
+                        Phase::CommitDetails,
+                        repo,
+                        format_args!("commit {short_sha}: {e}"),
+                    );
+                }
+            }
+        }
+        detailed_commits
+    }
+
+    /// The commits whose details are fetched, in the order they were listed. With
+    /// `CommitDetailOrder::Newest` those are the newest by date; undated commits go last.
+    fn commits_for_details<'c>(&self, commits: &'c [Commit]) -> Vec<&'c Commit> {
+        let limit = self.config.max_commit_details;
+        match self.config.commit_detail_order {
+            CommitDetailOrder::Listed => commits.iter().take(limit).collect(),
+            CommitDetailOrder::Newest => {
+                let mut indices: Vec<usize> = (0..commits.len()).collect();
+                indices.sort_by_key(|&idx| Reverse(commits[idx].date()));
+                indices.truncate(limit);
+                indices.sort_unstable();
+                indices.into_iter().map(|idx| &commits[idx]).collect()
+            }
+        }
+    }
+
+    /// Fills `collaborators_count` where the token has access, `repo_concurrency` repos at
+    /// a time; without access it just stays unset
+    async fn enrich_with_collaborators(&self, repos: &mut [Repo]) {
+        stream::iter(repos.iter_mut().filter(|r| !r.unavailable))
+            .map(|repo| async move {
+                match self
+                    .service
+                    .fetch_collaborators_count(&repo.owner.login, &repo.name)
+                    .await
+                {
+                    Ok(count) => repo.collaborators_count = count,
+                    Err(e) => {
+                        errln!(
+                            "      ⚠ Failed to count collaborators for {}: {}",
+                            repo.slug(),
+                            e
+                        );
+                        self.record(Phase::Collaborators, repo, e);
+                    }
+                }
+            })
+            .buffer_unordered(self.config.repo_concurrency.max(1))
+            .collect::<()>()
+            .await;
+    }
+
+    /// Fills `tags_count` and `branches_count`, one request each per repository and
+    /// `repo_concurrency` repos at a time; a failed count leaves only that one unset
+    async fn enrich_with_ref_counts(&self, repos: &mut [Repo]) {
+        stream::iter(repos.iter_mut().filter(|r| !r.unavailable))
+            .map(|repo| async move {
+                let (owner, name) = (&repo.owner.login, &repo.name);
+                let (tags, branches) = tokio::join!(
+                    self.service.fetch_tags_count(owner, name),
+                    self.service.fetch_branches_count(owner, name)
+                );
+                repo.tags_count = self.ref_count(repo, "tags", tags);
+                repo.branches_count = self.ref_count(repo, "branches", branches);
+            })
+            .buffer_unordered(self.config.repo_concurrency.max(1))
+            .collect::<()>()
+            .await;
+    }
+
+    /// The count from one ref listing; a failure is logged and recorded
+    fn ref_count(&self, repo: &Repo, refs: &str, result: Result<u64, AppError>) -> Option<u64> {
+        result
+            .inspect_err(|e| {
+                errln!(
+                    "      ⚠ Failed to count {} for {}: {}",
+                    refs,
+                    repo.slug(),
+                    e
+                );
+                self.record(Phase::RefCounts, repo, format_args!("{refs}: {e}"));
+            })
+            .ok()
+    }
+
+    /// Enriches repositories with fork data, skipping those with fewer than
+    /// `min_forks_to_fetch` forks
     async fn enrich_with_forks(&self, repos: &mut [Repo]) {
-        for repo in repos.iter_mut() {
+        let min_forks = self.config.min_forks_to_fetch.max(1);
+
+        for repo in repos.iter_mut().filter(|r| !r.unavailable) {
+            if repo.forks_count < min_forks {
+                outln!(
+                    "      ✓ {}: {} forks, not fetching them",
+                    repo.slug(),
+                    repo.forks_count
+                );
+                continue;
+            }
             match self
                 .service
                 .fetch_repo_forks(&repo.owner.login, &repo.name)
                 .await
             {
                 Ok(forks) => {
-                    println!("      ✓ {}: {} forks", repo.slug(), forks.len());
+                    outln!("      ✓ {}: {} forks", repo.slug(), forks.len());
                     repo.forks = forks;
                 }
                 Err(e) => {
-                    eprintln!("      ✗ Failed to fetch forks for {}: {}", repo.slug(), e);
+                    errln!("      ✗ Failed to fetch forks for {}: {}", repo.slug(), e);
+                    self.record(Phase::Forks, repo, e);
                 }
             }
         }
     }
 
-    /// Enriches forks with commit data (concurrent per repository)
+    /// Enriches forks with commit data, at most `max_concurrency` requests in flight per
+    /// repository; a new one starts as soon as any finishes
     async fn enrich_forks_with_commits(&self, repos: &mut [Repo]) {
+        let limit = self.max_concurrency;
+
         for repo in repos.iter_mut() {
             let forks_to_process = repo.forks.len().min(MAX_FORKS_TO_PROCESS);
+            let forks = &repo.forks[..forks_to_process];
 
-            let mut futures = Vec::new();
-            for fork in repo.forks.iter().take(MAX_FORKS_TO_PROCESS) {
-                futures.push(
-                    self.service
-                        .fetch_recent_commits(&fork.owner.login, &fork.name),
-                );
-            }
-
-            let results = futures::future::join_all(futures).await;
+            // Only commits made after the fork was created count as fork activity.
+            let mut results: Vec<_> = stream::iter(forks.iter().enumerate())
+                .map(|(idx, fork)| async move {
+                    let result = self
+                        .service
+                        .fetch_recent_commits(
+                            &fork.owner.login,
+                            &fork.name,
+                            fork.created_at.as_deref(),
+                            None,
+                        )
+                        .await;
+                    (idx, result)
+                })
+                .buffer_unordered(limit)
+                .collect()
+                .await;
+            // Log failures in fork order, not completion order.
+            results.sort_by_key(|(idx, _)| *idx);
 
-            for (fork, result) in repo
-                .forks
-                .iter_mut()
-                .take(MAX_FORKS_TO_PROCESS)
-                .zip(results)
-            {
+            for (idx, result) in results {
+                let fork = &mut repo.forks[idx];
                 match result {
                     Ok(commits) => {
                         fork.commit_count = commits.len() as u64;
                         fork.recent_commits = commits;
                     }
                     Err(e) => {
-                        eprintln!(
+                        errln!(
                             "      ⚠ Failed to fetch commits for fork {}: {}",
                             fork.slug(),
                             e
                         );
+                        self.record(Phase::ForkCommits, fork, e);
                     }
                 }
             }
 
             let forks_with_commits = repo.forks.iter().filter(|f| f.commit_count > 0).count();
             if forks_with_commits > 0 {
-                println!(
+                outln!(
                     "      ✓ {}: fetched commits for {}/{} forks",
                     repo.slug(),
                     forks_with_commits,
                     forks_to_process
                 );
             }
+            self.advance(1);
+        }
+    }
+
+    /// Fills each fork's `ahead_by` from the compare API, at most `max_concurrency` requests
+    /// in flight per repository. Forks are compared on their default branch, or the parent's
+    /// if unknown.
+    async fn enrich_forks_with_ahead_by(&self, repos: &mut [Repo]) {
+        let limit = self.max_concurrency;
+
+        for repo in repos.iter_mut() {
+            let forks_to_process = repo.forks.len().min(MAX_FORKS_TO_PROCESS);
+            let parent = &*repo;
+
+            let mut results: Vec<_> =
+                stream::iter(parent.forks[..forks_to_process].iter().enumerate())
+                    .map(|(idx, fork)| async move {
+                        let branch = fork
+                            .default_branch
+                            .as_deref()
+                            .or(parent.default_branch.as_deref())
+                            .unwrap_or("HEAD");
+                        let result = self
+                            .service
+                            .fetch_fork_ahead_by(
+                                &parent.owner.login,
+                                &parent.name,
+                                &fork.owner.login,
+                                branch,
+                            )
+                            .await;
+                        (idx, result)
+                    })
+                    .buffer_unordered(limit)
+                    .collect()
+                    .await;
+            // Log failures in fork order, not completion order.
+            results.sort_by_key(|(idx, _)| *idx);
+
+            for (idx, result) in results {
+                let fork = &mut repo.forks[idx];
+                match result {
+                    Ok(ahead_by) => fork.ahead_by = Some(ahead_by),
+                    Err(e) => {
+                        errln!("      ⚠ Failed to compare fork {}: {}", fork.slug(), e);
+                        self.record(Phase::ForkCommits, fork, e);
+                    }
+                }
+            }
+
+            let compared = repo.forks.iter().filter(|f| f.ahead_by.is_some()).count();
+            if compared > 0 {
+                outln!(
+                    "      ✓ {}: compared {}/{} forks",
+                    repo.slug(),
+                    compared,
+                    forks_to_process
+                );
+            }
+            self.advance(1);
         }
     }
 }
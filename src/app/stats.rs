@@ -1,8 +1,15 @@
 //! Statistics calculation.
 
-use crate::app::{LanguageReport, RepoMetrics};
-use crate::model::Repo;
-use std::collections::HashMap;
+use chrono::{Datelike, Timelike};
+
+use crate::app::{CommitTimeDistribution, IssueAgeBuckets, LanguageReport, RepoMetrics};
+use crate::config::{AuthorIdentities, BotAuthors, CommitDateSource, StatsConfig};
+use crate::model::{Commit, Issue, Repo};
+use crate::util::clock::{Clock, SystemClock};
+use crate::util::languages::source_extensions;
+use crate::util::time::{days_since, parse_github_datetime, parse_github_datetime_local};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 
 /// Maximum number of forks to process commits for
 const MAX_FORKS_TO_PROCESS: usize = 20;
@@ -12,76 +19,366 @@ pub struct StatsCalculator;
 
 impl StatsCalculator {
     pub fn calculate_repo_stats(repos: &[Repo]) -> (Vec<RepoMetrics>, usize) {
+        Self::calculate_repo_stats_with_config(repos, &StatsConfig::default())
+    }
+
+    pub fn calculate_repo_stats_with_config(
+        repos: &[Repo],
+        config: &StatsConfig,
+    ) -> (Vec<RepoMetrics>, usize) {
+        Self::calculate_repo_stats_with_clock(repos, config, &SystemClock)
+    }
+
+    /// Like [`Self::calculate_repo_stats_with_config`], measuring issue ages against `clock`
+    pub fn calculate_repo_stats_with_clock(
+        repos: &[Repo],
+        config: &StatsConfig,
+        clock: &impl Clock,
+    ) -> (Vec<RepoMetrics>, usize) {
         let mut metrics = Vec::with_capacity(repos.len());
         let mut fork_commit_total = 0usize;
 
         for repo in repos {
-            let top_files = Self::get_top_files(repo);
+            let language_extensions = config
+                .language_files_only
+                .then(|| repo.language.as_deref().and_then(source_extensions))
+                .flatten();
+            let top_files = Self::get_top_files(repo, config, language_extensions);
+            let top_reporters = Self::get_top_reporters(repo);
 
-            let new_fork_commits: usize = repo
+            let mut active_forks: Vec<(String, usize)> = repo
                 .forks
                 .iter()
                 .take(MAX_FORKS_TO_PROCESS)
-                .map(|fork| Self::count_new_commits(fork))
-                .sum();
+                .map(|fork| {
+                    (
+                        fork.slug(),
+                        Self::count_new_commits(fork, config.fork_commit_date),
+                    )
+                })
+                .filter(|&(_, count)| count > 0)
+                .collect();
+            // Stable sort keeps fork order for equal counts.
+            active_forks.sort_by_key(|&(_, count)| Reverse(count));
+            let new_fork_commits: usize = active_forks.iter().map(|(_, count)| count).sum();
 
             fork_commit_total += new_fork_commits;
 
             metrics.push(RepoMetrics {
                 slug: repo.slug(),
+                owner_avatar_url: repo.owner.avatar_url.clone(),
                 top_files,
+                top_files_by_frequency: Self::top_files_by_frequency(repo, config.top_files_count),
+                top_reporters,
+                unavailable: repo.unavailable,
+                commit_times: Self::commit_time_distribution(repo),
+                issue_ages: Self::issue_age_buckets(repo, clock),
+                avg_issue_comments: Self::average_issue_comments(repo),
+                active_forks,
+                collaborators: repo.collaborators_count,
+                tags: repo.tags_count,
+                branches: repo.branches_count,
+                contributors: Self::unique_contributors(
+                    repo,
+                    &config.author_identities,
+                    &config.bot_authors,
+                ),
+                stale_issue_ratio: Self::stale_issue_ratio(
+                    &repo.issues,
+                    config.stale_issue_days,
+                    clock,
+                ),
+                merge_commit_ratio: Self::merge_commit_ratio(repo),
             });
         }
 
         (metrics, fork_commit_total)
     }
 
-    fn count_new_commits(fork: &Repo) -> usize {
-        let Some(fork_created_at) = &fork.created_at else {
+    fn count_new_commits(fork: &Repo, date_source: CommitDateSource) -> usize {
+        if let Some(ahead_by) = fork.ahead_by {
+            return usize::try_from(ahead_by).unwrap_or(usize::MAX);
+        }
+        let Some(fork_created_at) = fork.created_at.as_deref().and_then(parse_github_datetime)
+        else {
             return 0;
         };
 
         fork.recent_commits
             .iter()
             .filter(|commit| {
-                commit
-                    .commit
-                    .author
-                    .as_ref()
-                    .and_then(|author| author.date.as_ref())
-                    .map(|commit_date| commit_date > fork_created_at)
-                    .unwrap_or(false)
+                let summary = &commit.commit;
+                let author_date = summary.author.as_ref().and_then(|a| a.date.as_ref());
+                let date = match date_source {
+                    CommitDateSource::Author => author_date,
+                    CommitDateSource::Committer => summary
+                        .committer
+                        .as_ref()
+                        .and_then(|c| c.date.as_ref())
+                        .or(author_date),
+                };
+                date.and_then(|date| parse_github_datetime(date))
+                    .is_some_and(|commit_date| commit_date > fork_created_at)
             })
             .count()
     }
 
-    fn get_top_files(repo: &Repo) -> Vec<String> {
-        let mut by_file: HashMap<String, i64> = HashMap::new();
+    /// Buckets the repo's fetched commits by the weekday and hour they were authored,
+    /// using the UTC offset recorded in each author date
+    pub fn commit_time_distribution(repo: &Repo) -> CommitTimeDistribution {
+        let mut distribution = CommitTimeDistribution::default();
 
         for commit in &repo.recent_commits {
+            let authored = commit
+                .commit
+                .author
+                .as_ref()
+                .and_then(|author| author.date.as_deref())
+                .and_then(parse_github_datetime_local);
+
+            match authored {
+                Some(at) => {
+                    distribution.by_weekday[at.weekday().num_days_from_monday() as usize] += 1;
+                    distribution.by_hour[at.hour() as usize] += 1;
+                }
+                None => distribution.undated += 1,
+            }
+        }
+
+        distribution
+    }
+
+    /// Average number of comments per fetched issue
+    pub fn average_issue_comments(repo: &Repo) -> f64 {
+        if repo.issues.is_empty() {
+            return 0.0;
+        }
+        let total: u64 = repo.issues.iter().map(|i| i.comments).sum();
+        total as f64 / repo.issues.len() as f64
+    }
+
+    /// Buckets the repo's open issues by age relative to `clock.now()`
+    pub fn issue_age_buckets(repo: &Repo, clock: &impl Clock) -> IssueAgeBuckets {
+        let mut buckets = IssueAgeBuckets::default();
+
+        for issue in &repo.issues {
+            let Some(age_days) = days_since(&issue.created_at, clock) else {
+                buckets.undated += 1;
+                continue;
+            };
+
+            match age_days {
+                ..=6 => buckets.under_week += 1,
+                7..=29 => buckets.under_month += 1,
+                30..=364 => buckets.under_year += 1,
+                _ => buckets.older += 1,
+            }
+        }
+
+        buckets
+    }
+
+    /// Fraction of the repo's fetched commits with more than one parent
+    pub fn merge_commit_ratio(repo: &Repo) -> f64 {
+        if repo.recent_commits.is_empty() {
+            return 0.0;
+        }
+        let merges = repo.recent_commits.iter().filter(|c| c.is_merge()).count();
+        merges as f64 / repo.recent_commits.len() as f64
+    }
+
+    /// Fraction of open issues whose last update is more than `threshold_days` before
+    /// `clock.now()`. Issues with an unparseable `updated_at` are left out; 0 when no
+    /// open issue has a usable date.
+    pub fn stale_issue_ratio(issues: &[Issue], threshold_days: u32, clock: &impl Clock) -> f64 {
+        let ages: Vec<i64> = issues
+            .iter()
+            .filter(|issue| issue.state.eq_ignore_ascii_case("open"))
+            .filter_map(|issue| days_since(&issue.updated_at, clock))
+            .collect();
+        if ages.is_empty() {
+            return 0.0;
+        }
+
+        let stale = ages
+            .iter()
+            .filter(|&&days| days > i64::from(threshold_days))
+            .count();
+        stale as f64 / ages.len() as f64
+    }
+
+    /// Ranks files by total line churn; per-file totals saturate at `i64::MAX`. With
+    /// `extensions`, only files ending in one of them are ranked.
+    fn get_top_files(
+        repo: &Repo,
+        config: &StatsConfig,
+        extensions: Option<&[&str]>,
+    ) -> Vec<String> {
+        let renames = if config.merge_renames {
+            Self::rename_map(repo)
+        } else {
+            HashMap::new()
+        };
+        let mut by_file: HashMap<String, i64> = HashMap::new();
+
+        let commits = repo
+            .recent_commits
+            .iter()
+            .filter(|commit| !(config.exclude_merge_commits && commit.is_merge()))
+            .filter(|commit| !Self::is_bot_commit(commit, &config.bot_authors));
+        for commit in commits {
             for file in &commit.files {
+                let filename = Self::current_name(&renames, &file.filename);
+                if extensions.is_some_and(|exts| !has_extension(filename, exts)) {
+                    continue;
+                }
                 let mut score = file.changes;
                 if score == 0 {
-                    score = file.additions + file.deletions;
+                    score = file.additions.saturating_add(file.deletions);
                 }
                 by_file
-                    .entry(file.filename.clone())
-                    .and_modify(|total| *total += score)
+                    .entry(filename.to_string())
+                    .and_modify(|total| *total = total.saturating_add(score))
                     .or_insert(score);
             }
         }
 
         let mut items: Vec<(String, i64)> = by_file.into_iter().collect();
         items.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
-        items.into_iter().map(|(name, _)| name).take(3).collect()
+        items
+            .into_iter()
+            .map(|(name, _)| name)
+            .take(config.top_files_count)
+            .collect()
+    }
+
+    /// Old name -> new name for every rename among the fetched commits
+    fn rename_map(repo: &Repo) -> HashMap<&str, &str> {
+        repo.recent_commits
+            .iter()
+            .flat_map(|commit| &commit.files)
+            .filter_map(|file| {
+                let previous = file.previous_filename.as_deref()?;
+                (previous != file.filename).then_some((previous, file.filename.as_str()))
+            })
+            .collect()
+    }
+
+    /// Follows `name` through chained renames to its latest name. Stops after one step
+    /// per known rename so a file renamed back and forth can't loop forever.
+    fn current_name<'a>(renames: &HashMap<&str, &'a str>, name: &'a str) -> &'a str {
+        let mut current = name;
+        for _ in 0..renames.len() {
+            match renames.get(current) {
+                Some(&next) if next != name => current = next,
+                _ => break,
+            }
+        }
+        current
+    }
+
+    /// Files ranked by how many distinct commits touched them (change frequency)
+    pub fn top_files_by_frequency(repo: &Repo, count: usize) -> Vec<String> {
+        let mut by_file: HashMap<&str, HashSet<&str>> = HashMap::new();
+
+        for commit in &repo.recent_commits {
+            for file in &commit.files {
+                by_file
+                    .entry(file.filename.as_str())
+                    .or_default()
+                    .insert(commit.sha.as_str());
+            }
+        }
+
+        let mut items: Vec<(&str, usize)> = by_file
+            .into_iter()
+            .map(|(name, commits)| (name, commits.len()))
+            .collect();
+        items.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        items
+            .into_iter()
+            .map(|(name, _)| name.to_string())
+            .take(count)
+            .collect()
+    }
+
+    /// Number of distinct people among the fetched commits' authors. Authors are told
+    /// apart by email (mapped through `identities`), or by name when there is no email.
+    /// Commits by `bots` aren't counted.
+    pub fn unique_contributors(
+        repo: &Repo,
+        identities: &AuthorIdentities,
+        bots: &BotAuthors,
+    ) -> usize {
+        repo.recent_commits
+            .iter()
+            .filter(|commit| !Self::is_bot_commit(commit, bots))
+            .filter_map(|commit| commit.commit.author.as_ref())
+            .filter_map(|author| match (&author.email, &author.name) {
+                (Some(email), _) if !email.trim().is_empty() => Some(identities.canonical(email)),
+                (_, Some(name)) => Some(name.clone()),
+                _ => None,
+            })
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Whether the commit's author name or email matches one of the `bots` patterns
+    fn is_bot_commit(commit: &Commit, bots: &BotAuthors) -> bool {
+        commit.commit.author.as_ref().is_some_and(|author| {
+            [&author.name, &author.email]
+                .into_iter()
+                .flatten()
+                .any(|value| bots.matches(value))
+        })
+    }
+
+    /// Issue authors ranked by how many of the fetched open issues they opened
+    fn get_top_reporters(repo: &Repo) -> Vec<String> {
+        let mut by_login: HashMap<&str, usize> = HashMap::new();
+
+        for login in repo.issues.iter().filter_map(|i| i.user_login.as_deref()) {
+            *by_login.entry(login).or_default() += 1;
+        }
+
+        let mut items: Vec<(&str, usize)> = by_login.into_iter().collect();
+        items.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        items
+            .into_iter()
+            .map(|(login, _)| login.to_string())
+            .take(3)
+            .collect()
     }
 
     pub fn build_language_report(language: &str, repos: Vec<Repo>) -> LanguageReport {
-        let total_stars: u64 = repos.iter().map(|r| r.stargazers_count).sum();
-        let total_forks: u64 = repos.iter().map(|r| r.forks_count).sum();
-        let total_open_issues: usize = repos.iter().map(|r| r.issues.len()).sum();
-        let (repo_metrics, new_fork_commits) = Self::calculate_repo_stats(&repos);
-        let total_repo_commits: usize = repos.iter().map(|r| r.commit_count as usize).sum();
+        Self::build_language_report_with_config(language, repos, &StatsConfig::default())
+    }
+
+    pub fn build_language_report_with_config(
+        language: &str,
+        repos: Vec<Repo>,
+        config: &StatsConfig,
+    ) -> LanguageReport {
+        // Repos below `min_commits` stay in `repos` but don't count towards any statistic.
+        let counted: Vec<Repo> = repos
+            .iter()
+            .filter(|r| r.commit_count >= config.min_commits)
+            .cloned()
+            .collect();
+
+        // Totals saturate at the type's maximum instead of overflowing on pathological input.
+        let total_stars = counted
+            .iter()
+            .fold(0u64, |acc, r| acc.saturating_add(r.stargazers_count));
+        let total_forks = counted
+            .iter()
+            .fold(0u64, |acc, r| acc.saturating_add(r.forks_count));
+        let total_open_issues: usize = counted.iter().map(|r| r.issues.len()).sum();
+        let (repo_metrics, new_fork_commits) =
+            Self::calculate_repo_stats_with_config(&counted, config);
+        let total_repo_commits = counted.iter().fold(0usize, |acc, r| {
+            acc.saturating_add(usize::try_from(r.commit_count).unwrap_or(usize::MAX))
+        });
 
         LanguageReport {
             language: language.to_string(),
@@ -92,6 +389,15 @@ impl StatsCalculator {
             total_repo_commits,
             new_fork_commits,
             repo_metrics,
+            stats_config: config.clone(),
+            errors: Vec::new(),
         }
     }
 }
+
+fn has_extension(filename: &str, extensions: &[&str]) -> bool {
+    std::path::Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+}
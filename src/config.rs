@@ -1,7 +1,12 @@
 //! Configuration loading.
+use std::collections::HashMap;
 use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
 
 use crate::error::AppError;
+use crate::util::backoff::{backoff_delay, JitterStrategy};
+use crate::util::time::is_search_date;
 
 pub trait ConfigSource {
     fn get(&self, key: &str) -> Option<String>;
@@ -23,11 +28,15 @@ impl ConfigSource for EnvSource {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct AppConfig {
     pub github: GitHubConfig,
     pub redis: RedisConfig,
     pub clone: CloneConfig,
+    pub fetch: FetchConfig,
+    pub output: OutputConfig,
+    pub stats: StatsConfig,
+    pub parts: Parts,
 }
 
 impl AppConfig {
@@ -42,20 +51,190 @@ impl AppConfig {
             github: GitHubConfig::from_source(source)?,
             redis: RedisConfig::from_source(source)?,
             clone: CloneConfig::from_source(source)?,
+            fetch: FetchConfig::from_source(source)?,
+            output: OutputConfig::from_source(source)?,
+            stats: StatsConfig::from_source(source)?,
+            parts: match source.get("PARTS") {
+                Some(value) => value.parse()?,
+                None => Parts::default(),
+            },
         })
     }
 }
 
+/// A stage of the pipeline, named by the letter it goes by in `PARTS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    /// A: search and enrich the top repositories.
+    Fetch,
+    /// B: print and write the per-language statistics.
+    Stats,
+    /// C: clone and inspect the best repository per language.
+    Clone,
+    /// D: store the cloned repositories in Redis.
+    Store,
+}
+
+impl Part {
+    const ALL: [Part; 4] = [Part::Fetch, Part::Stats, Part::Clone, Part::Store];
+
+    pub fn letter(self) -> char {
+        match self {
+            Part::Fetch => 'A',
+            Part::Stats => 'B',
+            Part::Clone => 'C',
+            Part::Store => 'D',
+        }
+    }
+
+    /// The part whose output this one works on.
+    fn requires(self) -> Option<Part> {
+        match self {
+            Part::Fetch => None,
+            Part::Stats | Part::Clone => Some(Part::Fetch),
+            Part::Store => Some(Part::Clone),
+        }
+    }
+}
+
+/// Parts of the pipeline `run` executes, e.g. `PARTS=A,B` to fetch and report without
+/// cloning or storing anything. Every part runs by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parts([bool; 4]);
+
+impl Parts {
+    pub fn contains(self, part: Part) -> bool {
+        self.0[part as usize]
+    }
+}
+
+impl Default for Parts {
+    fn default() -> Self {
+        Self([true; 4])
+    }
+}
+
+impl std::str::FromStr for Parts {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut parts = Self([false; 4]);
+        for letter in value.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let part = Part::ALL
+                .into_iter()
+                .find(|part| letter.eq_ignore_ascii_case(&part.letter().to_string()))
+                .ok_or_else(|| AppError::Config(format!("unknown PARTS value: {letter}")))?;
+            parts.0[part as usize] = true;
+        }
+
+        if !parts.0.contains(&true) {
+            return Err(AppError::Config(
+                "PARTS must name at least one part".to_string(),
+            ));
+        }
+        for part in Part::ALL.into_iter().filter(|&part| parts.contains(part)) {
+            if let Some(required) = part.requires().filter(|&r| !parts.contains(r)) {
+                return Err(AppError::Config(format!(
+                    "PARTS: part {} needs part {}",
+                    part.letter(),
+                    required.letter()
+                )));
+            }
+        }
+        Ok(parts)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GitHubConfig {
     pub token: Option<String>,
     pub api_base: String,
     pub user_agent: String,
+    pub retry: RetryConfig,
+    /// Maximum characters of a 4xx error body included in `GitHubApi` errors.
+    pub error_body_preview: usize,
+    /// Extra GitHub search syntax appended verbatim to the `q` parameter.
+    pub search_qualifiers: Option<String>,
+    /// Refuse to start without `GITHUB_TOKEN` instead of running unauthenticated.
+    pub token_required: bool,
+    /// Order GitHub returns forks in; only the first page of forks is analyzed.
+    pub fork_sort: ForkSort,
+    /// Largest response body read from GitHub before the request is failed.
+    pub max_response_bytes: usize,
+    /// Whether the newest or the oldest commits of each repository are fetched.
+    pub commit_order: CommitOrder,
+    /// Reuse commit details already fetched this run instead of requesting a SHA again.
+    pub cache_commit_details: bool,
+    /// Maps user-typed language names (`cpp`, `golang`) to GitHub's (`C++`, `Go`).
+    pub language_aliases: LanguageAliases,
+    /// Whether an exhausted rate limit is waited out or fails requests right away.
+    pub rate_limit_behavior: RateLimitBehavior,
+    /// Longest single wait for the rate limit to reset.
+    pub rate_limit_max_wait: Duration,
+    /// Only search repositories created on or after this date (or RFC 3339 time).
+    pub created_after: Option<String>,
+    /// Only search repositories created on or before this date (or RFC 3339 time).
+    pub created_before: Option<String>,
+    /// Speak HTTP/2 from the first byte instead of negotiating it; the API base must
+    /// accept that (api.github.com does).
+    pub http2_prior_knowledge: bool,
+    /// Let HTTP/2 size its flow-control windows from measured bandwidth and latency.
+    pub http2_adaptive_window: bool,
+    /// Commits fetched per repository (one page); clamped to GitHub's 1..=100.
+    pub commits_per_page: usize,
+    /// Open issues fetched per repository (one page); clamped to GitHub's 1..=100.
+    pub issues_per_page: usize,
+    /// Forks fetched per repository (one page); clamped to GitHub's 1..=100.
+    pub forks_per_page: usize,
+    /// Write every response (status, `Link`/`ETag` headers and body) here, one file per URL.
+    pub record_dir: Option<PathBuf>,
+    /// Answer requests from responses recorded into this directory instead of the network.
+    pub replay_dir: Option<PathBuf>,
+    /// Maximum fork requests in flight per repository.
+    pub max_concurrency: usize,
+}
+
+impl Default for GitHubConfig {
+    fn default() -> Self {
+        Self {
+            token: None,
+            api_base: Self::DEFAULT_API_BASE.to_string(),
+            user_agent: Self::DEFAULT_USER_AGENT.to_string(),
+            retry: RetryConfig::default(),
+            error_body_preview: Self::DEFAULT_ERROR_BODY_PREVIEW,
+            search_qualifiers: None,
+            token_required: false,
+            fork_sort: ForkSort::default(),
+            max_response_bytes: Self::DEFAULT_MAX_RESPONSE_BYTES,
+            commit_order: CommitOrder::default(),
+            cache_commit_details: true,
+            language_aliases: LanguageAliases::default(),
+            rate_limit_behavior: RateLimitBehavior::default(),
+            rate_limit_max_wait: Duration::from_secs(Self::DEFAULT_RATE_LIMIT_MAX_WAIT_SECS),
+            created_after: None,
+            created_before: None,
+            http2_prior_knowledge: false,
+            http2_adaptive_window: false,
+            commits_per_page: Self::DEFAULT_COMMITS_PER_PAGE,
+            issues_per_page: Self::DEFAULT_ISSUES_PER_PAGE,
+            forks_per_page: Self::DEFAULT_FORKS_PER_PAGE,
+            record_dir: None,
+            replay_dir: None,
+            max_concurrency: Self::DEFAULT_MAX_CONCURRENCY,
+        }
+    }
 }
 
 impl GitHubConfig {
     const DEFAULT_API_BASE: &'static str = "https://api.github.com";
     const DEFAULT_USER_AGENT: &'static str = "ecs160-hw1-github-client/0.1";
+    const DEFAULT_ERROR_BODY_PREVIEW: usize = 300;
+    const DEFAULT_MAX_RESPONSE_BYTES: usize = 50 * 1024 * 1024;
+    const DEFAULT_RATE_LIMIT_MAX_WAIT_SECS: u64 = 3600;
+    const DEFAULT_COMMITS_PER_PAGE: usize = 50;
+    const DEFAULT_ISSUES_PER_PAGE: usize = 100;
+    const DEFAULT_FORKS_PER_PAGE: usize = 100;
+    pub(crate) const DEFAULT_MAX_CONCURRENCY: usize = 8;
 
     fn from_source(source: &impl ConfigSource) -> Result<Self, AppError> {
         let token = source.get("GITHUB_TOKEN");
@@ -65,11 +244,92 @@ impl GitHubConfig {
         let user_agent = source
             .get("GITHUB_USER_AGENT")
             .unwrap_or_else(|| Self::DEFAULT_USER_AGENT.to_string());
+        let error_body_preview = source
+            .get("GITHUB_ERROR_BODY_PREVIEW")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(Self::DEFAULT_ERROR_BODY_PREVIEW);
+        let search_qualifiers = source
+            .get("SEARCH_QUALIFIERS")
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+        if search_qualifiers
+            .as_deref()
+            .is_some_and(|q| q.contains(['\n', '\r']))
+        {
+            return Err(AppError::Config(
+                "SEARCH_QUALIFIERS must not contain newlines".to_string(),
+            ));
+        }
 
         Ok(Self {
             token,
             api_base,
             user_agent,
+            retry: RetryConfig::from_source(source)?,
+            error_body_preview,
+            search_qualifiers,
+            token_required: source
+                .get("REQUIRE_TOKEN")
+                .and_then(|s| parse_flag(&s))
+                .unwrap_or(false),
+            fork_sort: match source.get("FORK_SORT") {
+                Some(value) => value.parse()?,
+                None => ForkSort::default(),
+            },
+            max_response_bytes: source
+                .get("MAX_RESPONSE_BYTES")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(Self::DEFAULT_MAX_RESPONSE_BYTES),
+            commit_order: match source.get("COMMIT_ORDER") {
+                Some(value) => value.parse()?,
+                None => CommitOrder::default(),
+            },
+            cache_commit_details: source
+                .get("CACHE_COMMIT_DETAILS")
+                .and_then(|s| parse_flag(&s))
+                .unwrap_or(true),
+            language_aliases: LanguageAliases::from_source(source)?,
+            rate_limit_behavior: match source.get("RATE_LIMIT_BEHAVIOR") {
+                Some(value) => value.parse()?,
+                None => RateLimitBehavior::default(),
+            },
+            rate_limit_max_wait: Duration::from_secs(
+                source
+                    .get("RATE_LIMIT_MAX_WAIT_SECS")
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(Self::DEFAULT_RATE_LIMIT_MAX_WAIT_SECS),
+            ),
+            created_after: search_date(source, "CREATED_AFTER")?,
+            created_before: search_date(source, "CREATED_BEFORE")?,
+            http2_prior_knowledge: source
+                .get("HTTP2_PRIOR_KNOWLEDGE")
+                .and_then(|s| parse_flag(&s))
+                .unwrap_or(false),
+            http2_adaptive_window: source
+                .get("HTTP2_ADAPTIVE_WINDOW")
+                .and_then(|s| parse_flag(&s))
+                .unwrap_or(false),
+            commits_per_page: source
+                .get("GITHUB_COMMITS_PER_PAGE")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(Self::DEFAULT_COMMITS_PER_PAGE),
+            issues_per_page: source
+                .get("GITHUB_ISSUES_PER_PAGE")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(Self::DEFAULT_ISSUES_PER_PAGE),
+            forks_per_page: source
+                .get("GITHUB_FORKS_PER_PAGE")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(Self::DEFAULT_FORKS_PER_PAGE),
+            record_dir: source.get("RECORD_DIR").map(PathBuf::from),
+            replay_dir: source.get("REPLAY_DIR").map(PathBuf::from),
+            // FORK_COMMIT_BATCH_SIZE is the deprecated name of the same limit.
+            max_concurrency: source
+                .get("GITHUB_MAX_CONCURRENCY")
+                .or_else(|| source.get("FORK_COMMIT_BATCH_SIZE"))
+                .and_then(|s| s.parse().ok())
+                .filter(|&n: &usize| n > 0)
+                .unwrap_or(Self::DEFAULT_MAX_CONCURRENCY),
         })
     }
 
@@ -77,11 +337,191 @@ impl GitHubConfig {
     pub fn require_token(&self) -> Result<&str, AppError> {
         self.token.as_deref().ok_or(AppError::MissingGitHubToken)
     }
+
+    /// Fails with `MissingGitHubToken` when `REQUIRE_TOKEN` is set but no token is configured.
+    pub fn check_token_requirement(&self) -> Result<(), AppError> {
+        if self.token_required {
+            self.require_token()?;
+        }
+        Ok(())
+    }
+}
+
+/// What a request does when GitHub reports no remaining rate-limit quota.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RateLimitBehavior {
+    /// Sleep until the window resets, then send the request.
+    #[default]
+    Wait,
+    /// Fail with `AppError::RateLimited` without sending the request.
+    Abort,
+}
+
+impl std::str::FromStr for RateLimitBehavior {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "wait" => Ok(Self::Wait),
+            "abort" => Ok(Self::Abort),
+            other => Err(AppError::Config(format!(
+                "unknown RATE_LIMIT_BEHAVIOR value: {other}"
+            ))),
+        }
+    }
+}
+
+/// Language names as users type them, mapped to the names GitHub search expects.
+#[derive(Debug, Clone)]
+pub struct LanguageAliases(HashMap<String, String>);
+
+impl Default for LanguageAliases {
+    fn default() -> Self {
+        Self(
+            Self::BUILT_IN
+                .iter()
+                .map(|&(alias, name)| (alias.to_string(), name.to_string()))
+                .collect(),
+        )
+    }
+}
+
+impl LanguageAliases {
+    const BUILT_IN: &'static [(&'static str, &'static str)] = &[
+        ("cpp", "C++"),
+        ("c++", "C++"),
+        ("cplusplus", "C++"),
+        ("c#", "C#"),
+        ("csharp", "C#"),
+        ("golang", "Go"),
+        ("js", "JavaScript"),
+        ("javascript", "JavaScript"),
+        ("ts", "TypeScript"),
+        ("typescript", "TypeScript"),
+        ("py", "Python"),
+        ("python", "Python"),
+        ("rs", "Rust"),
+        ("rust", "Rust"),
+        ("java", "Java"),
+    ];
+
+    /// Loads `LANGUAGE_ALIASES` (`alias=Name` pairs separated by commas) on top of the
+    /// built-in aliases; entries there win over the built-in ones.
+    fn from_source(source: &impl ConfigSource) -> Result<Self, AppError> {
+        let mut aliases = Self::default();
+        let Some(value) = source.get("LANGUAGE_ALIASES") else {
+            return Ok(aliases);
+        };
+
+        for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (alias, name) = entry
+                .split_once('=')
+                .map(|(a, n)| (a.trim(), n.trim()))
+                .filter(|(a, n)| !a.is_empty() && !n.is_empty())
+                .ok_or_else(|| {
+                    AppError::Config(format!("invalid LANGUAGE_ALIASES entry: {entry}"))
+                })?;
+            aliases.insert(alias, name);
+        }
+        Ok(aliases)
+    }
+
+    /// Adds or replaces an alias; aliases match case-insensitively.
+    pub fn insert(&mut self, alias: &str, name: &str) {
+        self.0.insert(alias.to_lowercase(), name.to_string());
+    }
+
+    /// GitHub's name for `language`, or `language` itself when it has no alias.
+    pub fn resolve(&self, language: &str) -> String {
+        self.0
+            .get(&language.trim().to_lowercase())
+            .cloned()
+            .unwrap_or_else(|| language.to_string())
+    }
+}
+
+/// Backoff settings for retried GitHub requests.
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    pub jitter: JitterStrategy,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Total tries for a request that keeps failing transiently, including the first.
+    pub max_attempts: u32,
+}
+
+impl RetryConfig {
+    const DEFAULT_BASE_DELAY_MS: u64 = 500;
+    const DEFAULT_MAX_DELAY_MS: u64 = 30_000;
+    const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+    fn from_source(source: &impl ConfigSource) -> Result<Self, AppError> {
+        let jitter = match source.get("RETRY_JITTER") {
+            Some(value) => value.parse()?,
+            None => JitterStrategy::default(),
+        };
+        let base_delay = source
+            .get("RETRY_BASE_DELAY_MS")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(Self::DEFAULT_BASE_DELAY_MS);
+        let max_delay = source
+            .get("RETRY_MAX_DELAY_MS")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(Self::DEFAULT_MAX_DELAY_MS);
+        let max_attempts = source
+            .get("RETRY_MAX_ATTEMPTS")
+            .and_then(|s| s.parse().ok())
+            .filter(|&n: &u32| n > 0)
+            .unwrap_or(Self::DEFAULT_MAX_ATTEMPTS);
+
+        Ok(Self {
+            jitter,
+            base_delay: Duration::from_millis(base_delay),
+            max_delay: Duration::from_millis(max_delay),
+            max_attempts,
+        })
+    }
+
+    /// Delay to wait before the zero-based retry `attempt`.
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        backoff_delay(self.base_delay, self.max_delay, attempt, self.jitter)
+    }
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            jitter: JitterStrategy::default(),
+            base_delay: Duration::from_millis(Self::DEFAULT_BASE_DELAY_MS),
+            max_delay: Duration::from_millis(Self::DEFAULT_MAX_DELAY_MS),
+            max_attempts: Self::DEFAULT_MAX_ATTEMPTS,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct RedisConfig {
     pub url: String,
+    /// Whether issue hashes include the issue body; metadata is always stored.
+    pub store_issue_bodies: bool,
+    /// Database index, overriding the one in `url`.
+    pub db: Option<i64>,
+    /// ACL username, overriding the one in `url`.
+    pub username: Option<String>,
+    /// Password, overriding the one in `url`.
+    pub password: Option<String>,
+}
+
+impl Default for RedisConfig {
+    fn default() -> Self {
+        Self {
+            url: Self::DEFAULT_REDIS_URL.to_string(),
+            store_issue_bodies: true,
+            db: None,
+            username: None,
+            password: None,
+        }
+    }
 }
 
 impl RedisConfig {
@@ -91,25 +531,771 @@ impl RedisConfig {
         let url = source
             .get("REDIS_URL")
             .unwrap_or_else(|| Self::DEFAULT_REDIS_URL.to_string());
+        let store_issue_bodies = source
+            .get("STORE_ISSUE_BODIES")
+            .and_then(|s| parse_flag(&s))
+            .unwrap_or(true);
 
-        Ok(Self { url })
+        let db = match source.get("REDIS_DB") {
+            Some(value) => Some(value.trim().parse().map_err(|_| {
+                AppError::Config(format!("REDIS_DB must be a database index, got: {value}"))
+            })?),
+            None => None,
+        };
+
+        Ok(Self {
+            url,
+            store_issue_bodies,
+            db,
+            username: source.get("REDIS_USERNAME"),
+            password: source.get("REDIS_PASSWORD"),
+        })
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct CloneConfig {
     pub min_source_ratio: f64,
+    /// Number of candidates cloned and inspected at the same time per language.
+    pub parallelism: usize,
+    pub git_base_url: String,
+    pub selection_metric: SelectionMetric,
+    /// Candidates whose reported size exceeds this many KB are skipped without cloning.
+    pub max_size_kb: Option<u64>,
+    /// Extra attempts for a clone that failed with a network error.
+    pub retries: u32,
+    /// Pause between clone attempts.
+    pub retry_delay: Duration,
+    /// The `git` executable used for cloning.
+    pub git_program: String,
+    /// Leave files under test directories out of the source ratio.
+    pub exclude_test_files: bool,
+    /// Name clone directories and pick source extensions by each repo's own primary
+    /// language instead of the searched one.
+    pub use_repo_language: bool,
+    /// Most `git` processes running at once during the clone phase.
+    pub process_limit: usize,
+    /// List the dependencies declared in each kept repository's root manifests.
+    pub list_dependencies: bool,
 }
 
 impl CloneConfig {
     const DEFAULT_MIN_SOURCE_RATIO: f64 = 0.05;
+    const DEFAULT_PARALLELISM: usize = 1;
+    const DEFAULT_GIT_BASE_URL: &'static str = "https://github.com";
+    const DEFAULT_RETRIES: u32 = 2;
+    const DEFAULT_RETRY_DELAY_MS: u64 = 2_000;
+    const DEFAULT_GIT_PROGRAM: &'static str = "git";
+    const DEFAULT_PROCESS_LIMIT: usize = 4;
 
     fn from_source(source: &impl ConfigSource) -> Result<Self, AppError> {
         let min_source_ratio = source
             .get("CLONE_MIN_SOURCE_RATIO")
             .and_then(|s| s.parse().ok())
             .unwrap_or(Self::DEFAULT_MIN_SOURCE_RATIO);
+        let parallelism = source
+            .get("CLONE_PARALLELISM")
+            .and_then(|s| s.parse().ok())
+            .filter(|&n: &usize| n > 0)
+            .unwrap_or(Self::DEFAULT_PARALLELISM);
+        let git_base_url = source
+            .get("CLONE_GIT_BASE_URL")
+            .unwrap_or_else(|| Self::DEFAULT_GIT_BASE_URL.to_string());
+        let selection_metric = match source.get("SELECTION_METRIC") {
+            Some(value) => value.parse()?,
+            None => SelectionMetric::default(),
+        };
+        let max_size_kb = source
+            .get("MAX_CLONE_SIZE_KB")
+            .and_then(|s| s.parse().ok())
+            .filter(|&n: &u64| n > 0);
+        let retries = source
+            .get("CLONE_RETRIES")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(Self::DEFAULT_RETRIES);
+        let retry_delay_ms = source
+            .get("CLONE_RETRY_DELAY_MS")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(Self::DEFAULT_RETRY_DELAY_MS);
+        let git_program = source
+            .get("GIT_PROGRAM")
+            .unwrap_or_else(|| Self::DEFAULT_GIT_PROGRAM.to_string());
+        let exclude_test_files = source
+            .get("EXCLUDE_TEST_FILES")
+            .and_then(|s| parse_flag(&s))
+            .unwrap_or(false);
+        let use_repo_language = source
+            .get("CLONE_USE_REPO_LANGUAGE")
+            .and_then(|s| parse_flag(&s))
+            .unwrap_or(false);
+        let process_limit = source
+            .get("CLONE_PROCESS_LIMIT")
+            .and_then(|s| s.parse().ok())
+            .filter(|&n: &usize| n > 0)
+            .unwrap_or(Self::DEFAULT_PROCESS_LIMIT);
+        let list_dependencies = source
+            .get("LIST_DEPENDENCIES")
+            .and_then(|s| parse_flag(&s))
+            .unwrap_or(false);
+
+        Ok(Self {
+            min_source_ratio,
+            parallelism,
+            git_base_url,
+            selection_metric,
+            max_size_kb,
+            retries,
+            retry_delay: Duration::from_millis(retry_delay_ms),
+            git_program,
+            exclude_test_files,
+            use_repo_language,
+            process_limit,
+            list_dependencies,
+        })
+    }
+}
+
+impl Default for CloneConfig {
+    fn default() -> Self {
+        Self {
+            min_source_ratio: Self::DEFAULT_MIN_SOURCE_RATIO,
+            parallelism: Self::DEFAULT_PARALLELISM,
+            git_base_url: Self::DEFAULT_GIT_BASE_URL.to_string(),
+            selection_metric: SelectionMetric::default(),
+            max_size_kb: None,
+            retries: Self::DEFAULT_RETRIES,
+            retry_delay: Duration::from_millis(Self::DEFAULT_RETRY_DELAY_MS),
+            git_program: Self::DEFAULT_GIT_PROGRAM.to_string(),
+            exclude_test_files: false,
+            use_repo_language: false,
+            process_limit: Self::DEFAULT_PROCESS_LIMIT,
+            list_dependencies: false,
+        }
+    }
+}
+
+/// How the clone phase ranks candidates that pass source detection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionMetric {
+    /// Most starred passing repository.
+    #[default]
+    Stars,
+    /// Stars scaled by the source file ratio, favouring code-heavy repositories.
+    StarsTimesRatio,
+}
+
+impl std::str::FromStr for SelectionMetric {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "stars" => Ok(Self::Stars),
+            "stars_ratio" | "stars*ratio" | "weighted" => Ok(Self::StarsTimesRatio),
+            other => Err(AppError::Config(format!(
+                "unknown SELECTION_METRIC value: {other}"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FetchConfig {
+    pub include_archived: bool,
+    pub skip_commit_details: bool,
+    /// Directory for the enrichment cache; caching is disabled when unset.
+    pub cache_dir: Option<PathBuf>,
+    /// Re-enrich every repository even if the cache says it is unchanged.
+    pub force_refresh: bool,
+    /// How many repositories of a language have their commits and issues fetched at once.
+    pub repo_concurrency: usize,
+    /// Maximum commit-detail requests in flight per repository.
+    pub commit_detail_concurrency: usize,
+    /// Skip fetching fork commits; `new_fork_commits` is then always 0.
+    pub skip_fork_commits: bool,
+    /// Count each repository's collaborators; needs a token with push access.
+    pub fetch_collaborators: bool,
+    /// Count each repository's tags and branches, two requests per repository.
+    pub fetch_ref_counts: bool,
+    /// Drop search results whose primary language isn't exactly the requested one.
+    pub strict_language_match: bool,
+    /// Forks aren't fetched for repositories whose `forks_count` is below this; repositories
+    /// without forks are always skipped.
+    pub min_forks_to_fetch: u64,
+    /// How new fork commits are found: by fetching fork commits or via the compare API.
+    pub fork_divergence: ForkDivergence,
+    /// Most commits per repository whose file details are fetched.
+    pub max_commit_details: usize,
+    /// Which of a repository's fetched commits get their file details fetched.
+    pub commit_detail_order: CommitDetailOrder,
+}
+
+impl FetchConfig {
+    const DEFAULT_INCLUDE_ARCHIVED: bool = true;
+    const DEFAULT_SKIP_COMMIT_DETAILS: bool = false;
+    const DEFAULT_REPO_CONCURRENCY: usize = 4;
+    const DEFAULT_COMMIT_DETAIL_CONCURRENCY: usize = 8;
+    const DEFAULT_MIN_FORKS_TO_FETCH: u64 = 1;
+    const DEFAULT_MAX_COMMIT_DETAILS: usize = 50;
+
+    fn from_source(source: &impl ConfigSource) -> Result<Self, AppError> {
+        let include_archived = source
+            .get("INCLUDE_ARCHIVED")
+            .and_then(|s| parse_flag(&s))
+            .unwrap_or(Self::DEFAULT_INCLUDE_ARCHIVED);
+        let skip_commit_details = source
+            .get("SKIP_COMMIT_DETAILS")
+            .and_then(|s| parse_flag(&s))
+            .unwrap_or(Self::DEFAULT_SKIP_COMMIT_DETAILS);
+
+        let cache_dir = source.get("REPO_CACHE_DIR").map(PathBuf::from);
+        let force_refresh = source
+            .get("FORCE_REFRESH")
+            .and_then(|s| parse_flag(&s))
+            .unwrap_or(false);
+        let repo_concurrency = source
+            .get("REPO_CONCURRENCY")
+            .and_then(|s| s.parse().ok())
+            .filter(|&n: &usize| n > 0)
+            .unwrap_or(Self::DEFAULT_REPO_CONCURRENCY);
+        let commit_detail_concurrency = source
+            .get("COMMIT_DETAIL_CONCURRENCY")
+            .and_then(|s| s.parse().ok())
+            .filter(|&n: &usize| n > 0)
+            .unwrap_or(Self::DEFAULT_COMMIT_DETAIL_CONCURRENCY);
+        let skip_fork_commits = source
+            .get("SKIP_FORK_COMMITS")
+            .and_then(|s| parse_flag(&s))
+            .unwrap_or(false);
+        let fetch_collaborators = source
+            .get("FETCH_COLLABORATORS")
+            .and_then(|s| parse_flag(&s))
+            .unwrap_or(false);
+        let fetch_ref_counts = source
+            .get("FETCH_REF_COUNTS")
+            .and_then(|s| parse_flag(&s))
+            .unwrap_or(false);
+        let strict_language_match = source
+            .get("STRICT_LANGUAGE_MATCH")
+            .and_then(|s| parse_flag(&s))
+            .unwrap_or(false);
+        let min_forks_to_fetch = source
+            .get("MIN_FORKS_TO_FETCH")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(Self::DEFAULT_MIN_FORKS_TO_FETCH);
+        let fork_divergence = match source.get("FORK_DIVERGENCE") {
+            Some(value) => value.parse()?,
+            None => ForkDivergence::default(),
+        };
+        let max_commit_details = source
+            .get("MAX_COMMIT_DETAILS")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(Self::DEFAULT_MAX_COMMIT_DETAILS);
+        let commit_detail_order = match source.get("COMMIT_DETAIL_ORDER") {
+            Some(value) => value.parse()?,
+            None => CommitDetailOrder::default(),
+        };
+
+        Ok(Self {
+            include_archived,
+            skip_commit_details,
+            cache_dir,
+            force_refresh,
+            repo_concurrency,
+            commit_detail_concurrency,
+            skip_fork_commits,
+            fetch_collaborators,
+            fetch_ref_counts,
+            strict_language_match,
+            min_forks_to_fetch,
+            fork_divergence,
+            max_commit_details,
+            commit_detail_order,
+        })
+    }
+}
+
+impl Default for FetchConfig {
+    fn default() -> Self {
+        Self {
+            include_archived: Self::DEFAULT_INCLUDE_ARCHIVED,
+            skip_commit_details: Self::DEFAULT_SKIP_COMMIT_DETAILS,
+            cache_dir: None,
+            force_refresh: false,
+            repo_concurrency: Self::DEFAULT_REPO_CONCURRENCY,
+            commit_detail_concurrency: Self::DEFAULT_COMMIT_DETAIL_CONCURRENCY,
+            skip_fork_commits: false,
+            fetch_collaborators: false,
+            fetch_ref_counts: false,
+            strict_language_match: false,
+            min_forks_to_fetch: Self::DEFAULT_MIN_FORKS_TO_FETCH,
+            fork_divergence: ForkDivergence::default(),
+            max_commit_details: Self::DEFAULT_MAX_COMMIT_DETAILS,
+            commit_detail_order: CommitDetailOrder::default(),
+        }
+    }
+}
+
+/// Strategy for counting the commits a fork has that its parent doesn't.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ForkDivergence {
+    /// Fetch each fork's recent commits and count those made after the fork was created.
+    #[default]
+    Commits,
+    /// Ask the compare API for `ahead_by`; one request per fork, no commit details.
+    Compare,
+}
+
+impl std::str::FromStr for ForkDivergence {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "commits" => Ok(Self::Commits),
+            "compare" | "ahead_by" => Ok(Self::Compare),
+            other => Err(AppError::Config(format!(
+                "unknown FORK_DIVERGENCE value: {other}"
+            ))),
+        }
+    }
+}
+
+/// Which commits get their file details fetched when there are more than
+/// `max_commit_details`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CommitDetailOrder {
+    /// The newest commits by author date (committer date when there is none).
+    #[default]
+    Newest,
+    /// The first commits in the order GitHub listed them.
+    Listed,
+}
+
+impl std::str::FromStr for CommitDetailOrder {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "newest" => Ok(Self::Newest),
+            "listed" => Ok(Self::Listed),
+            other => Err(AppError::Config(format!(
+                "unknown COMMIT_DETAIL_ORDER value: {other}"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StatsConfig {
+    /// Number of most modified files reported per repository.
+    pub top_files_count: usize,
+    /// Which commit date is compared with a fork's creation to count new fork commits.
+    pub fork_commit_date: CommitDateSource,
+    /// Rank top files only among files whose extension matches the repo's language.
+    pub language_files_only: bool,
+    /// Repositories with fewer fetched commits are listed but left out of the statistics.
+    pub min_commits: u64,
+    /// Credit churn on a renamed file's old name to its new name when ranking top files.
+    pub merge_renames: bool,
+    /// Commit author emails mapped to the person they belong to.
+    pub author_identities: AuthorIdentities,
+    /// Open issues not updated for this many days count as stale.
+    pub stale_issue_days: u32,
+    /// Leave merge commits out of the churn used to rank top files.
+    pub exclude_merge_commits: bool,
+    /// Commit authors that are bots, left out of contributor counts and top-file churn.
+    pub bot_authors: BotAuthors,
+}
+
+/// Date used to decide whether a fork commit was made after the fork was created.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CommitDateSource {
+    /// When the change was originally written; kept by rebases and cherry-picks.
+    #[default]
+    Author,
+    /// When the commit was (re)applied, falling back to the author date if absent.
+    Committer,
+}
+
+impl std::str::FromStr for CommitDateSource {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "author" => Ok(Self::Author),
+            "committer" => Ok(Self::Committer),
+            other => Err(AppError::Config(format!(
+                "unknown FORK_COMMIT_DATE value: {other}"
+            ))),
+        }
+    }
+}
+
+impl StatsConfig {
+    const DEFAULT_TOP_FILES_COUNT: usize = 3;
+    const DEFAULT_STALE_ISSUE_DAYS: u32 = 90;
+
+    fn from_source(source: &impl ConfigSource) -> Result<Self, AppError> {
+        let top_files_count = source
+            .get("TOP_FILES_COUNT")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(Self::DEFAULT_TOP_FILES_COUNT);
+        let fork_commit_date = match source.get("FORK_COMMIT_DATE") {
+            Some(value) => value.parse()?,
+            None => CommitDateSource::default(),
+        };
+
+        let language_files_only = source
+            .get("TOP_FILES_LANGUAGE_ONLY")
+            .and_then(|s| parse_flag(&s))
+            .unwrap_or(false);
+
+        let min_commits = source
+            .get("MIN_COMMITS")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0);
+
+        let merge_renames = source
+            .get("MERGE_RENAMES")
+            .and_then(|s| parse_flag(&s))
+            .unwrap_or(true);
+
+        Ok(Self {
+            top_files_count,
+            fork_commit_date,
+            language_files_only,
+            min_commits,
+            merge_renames,
+            author_identities: AuthorIdentities::from_source(source)?,
+            stale_issue_days: source
+                .get("STALE_ISSUE_DAYS")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(Self::DEFAULT_STALE_ISSUE_DAYS),
+            exclude_merge_commits: source
+                .get("EXCLUDE_MERGE_COMMITS")
+                .and_then(|s| parse_flag(&s))
+                .unwrap_or(false),
+            bot_authors: source
+                .get("BOT_AUTHORS")
+                .map(|list| BotAuthors::parse(&list))
+                .unwrap_or_default(),
+        })
+    }
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self {
+            top_files_count: Self::DEFAULT_TOP_FILES_COUNT,
+            fork_commit_date: CommitDateSource::default(),
+            language_files_only: false,
+            min_commits: 0,
+            merge_renames: true,
+            author_identities: AuthorIdentities::default(),
+            stale_issue_days: Self::DEFAULT_STALE_ISSUE_DAYS,
+            exclude_merge_commits: false,
+            bot_authors: BotAuthors::default(),
+        }
+    }
+}
+
+/// Commit author emails mapped to a canonical identity, so one person committing from
+/// several addresses counts once. Empty unless `AUTHOR_IDENTITIES_FILE` is set.
+#[derive(Debug, Clone, Default)]
+pub struct AuthorIdentities(HashMap<String, String>);
+
+impl AuthorIdentities {
+    /// Loads the JSON object at `AUTHOR_IDENTITIES_FILE`, e.g.
+    /// `{"jane@personal.com": "jane@work.com"}`.
+    fn from_source(source: &impl ConfigSource) -> Result<Self, AppError> {
+        let Some(path) = source.get("AUTHOR_IDENTITIES_FILE") else {
+            return Ok(Self::default());
+        };
+
+        let text = std::fs::read_to_string(&path).map_err(|err| {
+            AppError::Config(format!(
+                "failed to read AUTHOR_IDENTITIES_FILE {path}: {err}"
+            ))
+        })?;
+        let map: HashMap<String, String> = serde_json::from_str(&text).map_err(|err| {
+            AppError::Config(format!("invalid AUTHOR_IDENTITIES_FILE {path}: {err}"))
+        })?;
+
+        let mut identities = Self::default();
+        for (email, identity) in &map {
+            identities.insert(email, identity);
+        }
+        Ok(identities)
+    }
+
+    /// Maps `email` to `identity`; emails match case-insensitively.
+    pub fn insert(&mut self, email: &str, identity: &str) {
+        self.0
+            .insert(email.trim().to_lowercase(), identity.to_string());
+    }
+
+    /// The identity `email` belongs to, or the normalized email itself when unmapped.
+    pub fn canonical(&self, email: &str) -> String {
+        let email = email.trim().to_lowercase();
+        self.0.get(&email).cloned().unwrap_or(email)
+    }
+}
+
+/// Commit author names or emails that belong to bots. A pattern matches
+/// case-insensitively; a leading or trailing `*` matches any prefix or suffix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BotAuthors(Vec<String>);
+
+impl BotAuthors {
+    /// `dependabot[bot]`, `renovate[bot]`, `github-actions[bot]` and the like, by name
+    /// or by their `...[bot]@users.noreply.github.com` address.
+    const DEFAULT_PATTERNS: &'static [&'static str] = &[
+        "*[bot]",
+        "*[bot]@users.noreply.github.com",
+        "dependabot",
+        "renovate-bot",
+        "github-actions",
+    ];
+
+    /// Reads a comma-separated pattern list; an empty list matches no one.
+    pub fn parse(list: &str) -> Self {
+        Self(
+            list.split(',')
+                .map(|pattern| pattern.trim().to_lowercase())
+                .filter(|pattern| !pattern.is_empty())
+                .collect(),
+        )
+    }
+
+    /// Whether `value` (an author name or email) matches any pattern.
+    pub fn matches(&self, value: &str) -> bool {
+        let value = value.trim().to_lowercase();
+        self.0.iter().any(
+            |pattern| match (pattern.strip_prefix('*'), pattern.strip_suffix('*')) {
+                (Some(suffix), _) if suffix.ends_with('*') => {
+                    value.contains(suffix.trim_end_matches('*'))
+                }
+                (Some(suffix), _) => value.ends_with(suffix),
+                (None, Some(prefix)) => value.starts_with(prefix),
+                (None, None) => value == *pattern,
+            },
+        )
+    }
+}
+
+impl Default for BotAuthors {
+    fn default() -> Self {
+        Self::parse(&Self::DEFAULT_PATTERNS.join(","))
+    }
+}
+
+/// Order in which language reports are printed once all of them are collected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputSort {
+    /// Keep the order the languages were configured in.
+    #[default]
+    Config,
+    /// Sort by language name.
+    Alphabetical,
+    /// Sort by total stars, most starred language first.
+    Stars,
+}
+
+impl std::str::FromStr for OutputSort {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "config" => Ok(Self::Config),
+            "alphabetical" | "alpha" | "name" => Ok(Self::Alphabetical),
+            "stars" => Ok(Self::Stars),
+            other => Err(AppError::Config(format!(
+                "unknown OUTPUT_SORT value: {other}"
+            ))),
+        }
+    }
+}
+
+/// Which end of a repository's history is fetched.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CommitOrder {
+    /// Most recent commits, newest first (GitHub's default).
+    #[default]
+    Newest,
+    /// Earliest commits, oldest first.
+    Oldest,
+}
+
+impl std::str::FromStr for CommitOrder {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "newest" | "recent" => Ok(Self::Newest),
+            "oldest" | "first" => Ok(Self::Oldest),
+            other => Err(AppError::Config(format!(
+                "unknown COMMIT_ORDER value: {other}"
+            ))),
+        }
+    }
+}
+
+/// Sort order for the forks endpoint.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ForkSort {
+    #[default]
+    Newest,
+    Oldest,
+    StargazerCount,
+    Watchers,
+}
+
+impl ForkSort {
+    /// Value of GitHub's `sort` query parameter for this order.
+    pub fn as_query_value(&self) -> &'static str {
+        match self {
+            ForkSort::Newest => "newest",
+            ForkSort::Oldest => "oldest",
+            ForkSort::StargazerCount => "stargazers",
+            ForkSort::Watchers => "watchers",
+        }
+    }
+}
+
+impl std::str::FromStr for ForkSort {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "newest" => Ok(Self::Newest),
+            "oldest" => Ok(Self::Oldest),
+            "stargazers" | "stars" => Ok(Self::StargazerCount),
+            "watchers" => Ok(Self::Watchers),
+            other => Err(AppError::Config(format!(
+                "unknown FORK_SORT value: {other}"
+            ))),
+        }
+    }
+}
+
+/// Whether each repo's recent commits and their file changes are printed after the summary.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VerboseCommits {
+    #[default]
+    Off,
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for VerboseCommits {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match parse_flag(value) {
+            Some(true) => return Ok(Self::Text),
+            Some(false) => return Ok(Self::Off),
+            None => {}
+        }
+        match value.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            other => Err(AppError::Config(format!(
+                "unknown VERBOSE_COMMITS value: {other}"
+            ))),
+        }
+    }
+}
+
+/// How the per-language summaries are printed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable summaries.
+    #[default]
+    Text,
+    /// Gauges in the Prometheus text exposition format.
+    Prometheus,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "prometheus" => Ok(Self::Prometheus),
+            other => Err(AppError::Config(format!(
+                "unknown OUTPUT_FORMAT value: {other}"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OutputConfig {
+    pub format: OutputFormat,
+    pub sort: OutputSort,
+    pub verbose_commits: VerboseCommits,
+    /// Directory each language report is written to as `<language>.json`; unset skips it.
+    pub report_dir: Option<PathBuf>,
+    /// Print ASCII markers (`[OK]`, `[FAIL]`, `[WARN]`) and no `=====` banners.
+    pub plain: bool,
+    /// Print a "Winner" block for each language's best repository above its summary.
+    pub highlight_winner: bool,
+}
+
+impl OutputConfig {
+    fn from_source(source: &impl ConfigSource) -> Result<Self, AppError> {
+        let format = match source.get("OUTPUT_FORMAT") {
+            Some(value) => value.parse()?,
+            None => OutputFormat::default(),
+        };
+        let sort = match source.get("OUTPUT_SORT") {
+            Some(value) => value.parse()?,
+            None => OutputSort::default(),
+        };
+        let verbose_commits = match source.get("VERBOSE_COMMITS") {
+            Some(value) => value.parse()?,
+            None => VerboseCommits::default(),
+        };
+        let report_dir = source.get("REPORT_DIR").map(PathBuf::from);
+        let plain = source
+            .get("PLAIN_OUTPUT")
+            .and_then(|s| parse_flag(&s))
+            .unwrap_or(false);
+        let highlight_winner = source
+            .get("HIGHLIGHT_WINNER")
+            .and_then(|s| parse_flag(&s))
+            .unwrap_or(false);
+
+        Ok(Self {
+            format,
+            sort,
+            verbose_commits,
+            report_dir,
+            plain,
+            highlight_winner,
+        })
+    }
+}
+
+/// Reads `key` as a `YYYY-MM-DD` date or an RFC 3339 timestamp, the forms GitHub's
+/// `created:` search qualifier accepts.
+fn search_date(source: &impl ConfigSource, key: &str) -> Result<Option<String>, AppError> {
+    let Some(value) = source.get(key).map(|s| s.trim().to_string()) else {
+        return Ok(None);
+    };
+    if !is_search_date(&value) {
+        return Err(AppError::Config(format!(
+            "{key} must be a YYYY-MM-DD date or RFC 3339 time, got: {value}"
+        )));
+    }
+    Ok(Some(value))
+}
 
-        Ok(Self { min_source_ratio })
+/// Parses boolean-ish environment values such as `true`, `1`, `yes` or `off`.
+fn parse_flag(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
     }
 }
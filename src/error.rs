@@ -7,7 +7,7 @@ use thiserror::Error;
 pub enum AppError {
     #[error("configuration error: {0}")]
     Config(String),
-    #[error("missing GitHub token in configuration")]
+    #[error("missing GitHub token in configuration; set GITHUB_TOKEN")]
     MissingGitHubToken,
     #[error("http client error: {0}")]
     Http(#[from] reqwest::Error),
@@ -15,8 +15,14 @@ pub enum AppError {
     Serialization(#[from] serde_json::Error),
     #[error("io error: {0}")]
     Io(#[from] io::Error),
-    #[error("github api error: {0}")]
-    GitHubApi(String),
+    #[error("github api error: {message}")]
+    GitHubApi { status: u16, message: String },
+    #[error("GitHub rate limit exhausted; it resets in {reset_in_secs}s")]
+    RateLimited { reset_in_secs: u64 },
+    #[error("malformed Link header: {0}")]
+    MalformedLinkHeader(String),
+    #[error("repository {repo} is unavailable (HTTP {status})")]
+    RepositoryUnavailable { repo: String, status: u16 },
     #[error("git operation error: {0}")]
     Git(String),
     #[error("redis error: {0}")]
@@ -24,3 +30,34 @@ pub enum AppError {
     #[error("feature not implemented yet")]
     NotImplemented,
 }
+
+impl AppError {
+    /// Process exit code for this error, so scripts and CI can tell failure kinds apart:
+    ///
+    /// | code | category | variants |
+    /// |------|----------|----------|
+    /// | 1 | other | `Serialization`, `Io`, `Git`, `NotImplemented` |
+    /// | 2 | configuration | `Config` |
+    /// | 3 | auth / rate limit | `MissingGitHubToken`, `RateLimited`, `GitHubApi` with HTTP 401, 403 or 429 |
+    /// | 4 | network / GitHub | `Http`, `GitHubApi` (other statuses), `MalformedLinkHeader`, `RepositoryUnavailable` |
+    /// | 5 | storage | `Redis` |
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AppError::Config(_) => 2,
+            AppError::MissingGitHubToken | AppError::RateLimited { .. } => 3,
+            AppError::GitHubApi {
+                status: 401 | 403 | 429,
+                ..
+            } => 3,
+            AppError::Http(_)
+            | AppError::GitHubApi { .. }
+            | AppError::MalformedLinkHeader(_)
+            | AppError::RepositoryUnavailable { .. } => 4,
+            AppError::Redis(_) => 5,
+            AppError::Serialization(_)
+            | AppError::Io(_)
+            | AppError::Git(_)
+            | AppError::NotImplemented => 1,
+        }
+    }
+}
@@ -2,7 +2,7 @@
 #[tokio::main]
 async fn main() {
     if let Err(err) = ecs160_hw1::app::run().await {
-        eprintln!("application error: {err}");
-        std::process::exit(1);
+        ecs160_hw1::errln!("application error: {err}");
+        std::process::exit(err.exit_code());
     }
 }
@@ -0,0 +1,33 @@
+//! Source code analysis model.
+use std::collections::HashMap;
+
+/// Result of scanning a cloned repository for source files
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeAnalysis {
+    pub source_files: usize,
+    pub total_files: usize,
+    pub source_ratio: f64,
+    pub is_source_code_repo: bool,
+    /// Number of scanned files per lowercase extension (e.g. `rs` -> 120).
+    pub file_extensions: HashMap<String, usize>,
+    /// Several package manifests were found in subdirectories, suggesting the repository
+    /// holds multiple independent projects.
+    pub is_monorepo: bool,
+    /// Source files under a test directory (`tests/`, `test/`, `spec/`, `__tests__/`).
+    pub test_files: usize,
+    /// Source files outside test directories.
+    pub production_files: usize,
+}
+
+impl CodeAnalysis {
+    /// Extensions with their file counts, most common first, then alphabetically.
+    pub fn extensions_by_count(&self) -> Vec<(&str, usize)> {
+        let mut items: Vec<(&str, usize)> = self
+            .file_extensions
+            .iter()
+            .map(|(ext, &count)| (ext.as_str(), count))
+            .collect();
+        items.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        items
+    }
+}
@@ -1,18 +1,24 @@
 //! Commit model.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::error::AppError;
 use crate::util::json::{
     as_object, optional_i64, optional_string, parse_optional, required_field, required_string,
 };
+use crate::util::time::parse_github_datetime;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Commit {
     pub sha: String,
     pub url: String,
     pub html_url: Option<String>,
     pub commit: CommitSummary,
     pub files: Vec<CommitFile>,
+    /// SHAs of the parent commits; more than one for merges.
+    #[serde(default)]
+    pub parents: Vec<String>,
 }
 
 impl Commit {
@@ -31,11 +37,35 @@ impl Commit {
                     .collect::<Result<Vec<_>, _>>()?,
                 _ => Vec::new(),
             },
+            parents: match map.get("parents") {
+                Some(Value::Array(items)) => items
+                    .iter()
+                    .filter_map(|parent| parent.get("sha").and_then(Value::as_str))
+                    .map(str::to_string)
+                    .collect(),
+                _ => Vec::new(),
+            },
         })
     }
+
+    /// When the commit was authored, or committed if it has no author date.
+    pub fn date(&self) -> Option<DateTime<Utc>> {
+        let summary = &self.commit;
+        summary
+            .author
+            .as_ref()
+            .and_then(|a| a.date.as_deref())
+            .or_else(|| summary.committer.as_ref().and_then(|c| c.date.as_deref()))
+            .and_then(parse_github_datetime)
+    }
+
+    /// Whether this commit merges two or more lines of history.
+    pub fn is_merge(&self) -> bool {
+        self.parents.len() > 1
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommitSummary {
     pub message: String,
     pub author: Option<CommitAuthor>,
@@ -54,7 +84,7 @@ impl CommitSummary {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommitAuthor {
     pub name: Option<String>,
     pub email: Option<String>,
@@ -73,13 +103,16 @@ impl CommitAuthor {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommitFile {
     pub filename: String,
     pub additions: i64,
     pub deletions: i64,
     pub changes: i64,
     pub status: String,
+    /// Name before the change, set when `status` is `renamed`.
+    #[serde(default)]
+    pub previous_filename: Option<String>,
 }
 
 impl CommitFile {
@@ -92,6 +125,7 @@ impl CommitFile {
             deletions: optional_i64(map, "deletions"),
             changes: optional_i64(map, "changes"),
             status: optional_string(map, "status").unwrap_or_default(),
+            previous_filename: optional_string(map, "previous_filename"),
         })
     }
 }
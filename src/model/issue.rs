@@ -1,10 +1,11 @@
 //! Issue model.
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::error::AppError;
-use crate::util::json::{as_object, optional_string, required_string, required_i64};
+use crate::util::json::{as_object, optional_string, optional_u64, required_i64, required_string};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Issue {
     pub id: i64,
     pub number: i64,
@@ -14,6 +15,12 @@ pub struct Issue {
     pub html_url: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    pub user_login: Option<String>,
+    /// Number of comments on the issue
+    pub comments: u64,
+    /// Set for pull requests, which GitHub's issues endpoint lists alongside issues.
+    #[serde(default)]
+    pub is_pull_request: bool,
 }
 
 impl Issue {
@@ -29,6 +36,34 @@ impl Issue {
             html_url: optional_string(map, "html_url"),
             created_at: required_string(map, "created_at")?,
             updated_at: required_string(map, "updated_at")?,
+            user_login: match map.get("user") {
+                Some(user @ Value::Object(_)) => {
+                    optional_string(as_object(user, "issue user")?, "login")
+                }
+                _ => None,
+            },
+            comments: optional_u64(map, "comments"),
+            is_pull_request: matches!(map.get("pull_request"), Some(Value::Object(_))),
         })
     }
 }
+
+/// Which issues to list, by state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IssueState {
+    #[default]
+    Open,
+    Closed,
+    All,
+}
+
+impl IssueState {
+    /// Value of GitHub's `state` query parameter.
+    pub fn as_query_value(self) -> &'static str {
+        match self {
+            IssueState::Open => "open",
+            IssueState::Closed => "closed",
+            IssueState::All => "all",
+        }
+    }
+}
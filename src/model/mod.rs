@@ -1,10 +1,12 @@
 //! Data models.
+pub mod analysis;
 pub mod commit;
 pub mod issue;
 pub mod owner;
 pub mod repo;
 
+pub use analysis::CodeAnalysis;
 pub use commit::{Commit, CommitAuthor, CommitFile, CommitSummary};
-pub use issue::Issue;
+pub use issue::{Issue, IssueState};
 pub use owner::Owner;
-pub use repo::Repo;
+pub use repo::{Repo, RepoBuilder};
@@ -1,15 +1,19 @@
 //! Owner model.
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::error::AppError;
-use crate::util::json::{as_object, required_bool, required_i64, required_string};
+use crate::util::json::{
+    as_object, optional_string, required_bool, required_i64, required_string,
+};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Owner {
     pub login: String,
     pub id: i64,
     pub html_url: String,
     pub site_admin: bool,
+    pub avatar_url: Option<String>,
 }
 
 impl Owner {
@@ -21,6 +25,7 @@ impl Owner {
             id: required_i64(map, "id")?,
             html_url: required_string(map, "html_url")?,
             site_admin: required_bool(map, "site_admin")?,
+            avatar_url: optional_string(map, "avatar_url"),
         })
     }
 }
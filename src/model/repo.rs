@@ -1,15 +1,17 @@
 //! Repository model.
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::error::AppError;
+use crate::service::RepoData;
 use crate::util::json::{
-    as_object, optional_string, optional_u64, required_field, required_i64, required_string,
-    optional_bool,
+    as_object, optional_bool, optional_string, optional_u64, required_field, required_i64,
+    required_string,
 };
 
 use super::{Commit, Issue, Owner};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Repo {
     pub id: i64,
     pub name: String,
@@ -18,14 +20,36 @@ pub struct Repo {
     pub forks_count: u64,
     pub stargazers_count: u64,
     pub open_issues_count: u64,
+    /// Repository size in KB as reported by the API; 0 when unknown.
+    #[serde(default)]
+    pub size: u64,
     pub has_issues: bool,
+    pub archived: bool,
     pub language: Option<String>,
     pub owner: Owner,
     pub created_at: Option<String>,
+    pub pushed_at: Option<String>,
     pub forks: Vec<Repo>,
     pub recent_commits: Vec<Commit>,
     pub issues: Vec<Issue>,
     pub commit_count: u64,
+    /// Set when GitHub refuses to serve the repository's details (HTTP 451/422).
+    pub unavailable: bool,
+    /// Only present in the full repository response, not in search results.
+    pub default_branch: Option<String>,
+    /// SPDX identifier of the license (e.g. `MIT`), falling back to its name.
+    pub license: Option<String>,
+    /// Commits this fork has that its parent doesn't, when taken from the compare API.
+    pub ahead_by: Option<u64>,
+    /// Collaborator count, when the token is allowed to list collaborators.
+    #[serde(default)]
+    pub collaborators_count: Option<u64>,
+    /// Number of tags; `None` until fetched.
+    #[serde(default)]
+    pub tags_count: Option<u64>,
+    /// Number of branches; `None` until fetched.
+    #[serde(default)]
+    pub branches_count: Option<u64>,
 }
 impl Repo {
     pub fn from_json(value: &Value) -> Result<Self, AppError> {
@@ -39,18 +63,217 @@ impl Repo {
             forks_count: optional_u64(map, "forks_count"),
             stargazers_count: optional_u64(map, "stargazers_count"),
             open_issues_count: optional_u64(map, "open_issues_count"),
+            size: optional_u64(map, "size"),
             has_issues: optional_bool(map, "has_issues").unwrap_or(true),
+            archived: optional_bool(map, "archived").unwrap_or(false),
             language: optional_string(map, "language"),
             owner: Owner::from_json(required_field(map, "owner")?)?,
             created_at: optional_string(map, "created_at"),
+            pushed_at: optional_string(map, "pushed_at"),
             forks: Vec::new(),
             recent_commits: Vec::new(),
             issues: Vec::new(),
             commit_count: 0,
+            unavailable: false,
+            ahead_by: None,
+            collaborators_count: None,
+            tags_count: None,
+            branches_count: None,
+            default_branch: optional_string(map, "default_branch"),
+            license: match map.get("license") {
+                Some(license @ Value::Object(_)) => {
+                    let license = as_object(license, "license")?;
+                    optional_string(license, "spdx_id")
+                        .filter(|id| id != "NOASSERTION")
+                        .or_else(|| optional_string(license, "name"))
+                }
+                _ => None,
+            },
         })
     }
 
+    /// Copies the fields that only the full `/repos/{owner}/{repo}` response carries
+    /// (or that search results may report stale) from `details` into this repo.
+    pub fn fill_details(&mut self, details: &Repo) {
+        self.has_issues = details.has_issues;
+        self.archived = details.archived;
+        self.open_issues_count = details.open_issues_count;
+        if details.pushed_at.is_some() {
+            self.pushed_at.clone_from(&details.pushed_at);
+        }
+        if details.default_branch.is_some() {
+            self.default_branch.clone_from(&details.default_branch);
+        }
+        if details.license.is_some() {
+            self.license.clone_from(&details.license);
+        }
+    }
+
     pub fn slug(&self) -> String {
         format!("{}/{}", self.owner.login, self.name)
     }
+
+    /// The stored summary of this repository; a missing language becomes `unknown`.
+    pub fn to_repo_data(&self) -> RepoData {
+        RepoData {
+            url: self.html_url.clone(),
+            name: self.name.clone(),
+            owner: self.owner.login.clone(),
+            language: self
+                .language
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string()),
+            stars: self.stargazers_count,
+            forks: self.forks_count,
+            open_issues: self.open_issues_count,
+        }
+    }
+
+    /// Starts a [`RepoBuilder`] with placeholder values for every field.
+    pub fn builder() -> RepoBuilder {
+        RepoBuilder::default()
+    }
+}
+
+/// Fluent construction of a [`Repo`], mainly for tests. Unset fields keep neutral
+/// defaults (`id` 0, no stars, forks or issues, not archived), and `full_name` and
+/// `html_url` are derived from the owner and name when the repo is built.
+#[derive(Debug, Clone)]
+pub struct RepoBuilder {
+    repo: Repo,
+}
+
+impl Default for RepoBuilder {
+    fn default() -> Self {
+        Self {
+            repo: Repo {
+                id: 0,
+                name: "repo".to_string(),
+                full_name: String::new(),
+                html_url: String::new(),
+                forks_count: 0,
+                stargazers_count: 0,
+                open_issues_count: 0,
+                size: 0,
+                has_issues: true,
+                archived: false,
+                language: None,
+                owner: Owner {
+                    login: "owner".to_string(),
+                    id: 0,
+                    html_url: String::new(),
+                    site_admin: false,
+                    avatar_url: None,
+                },
+                created_at: None,
+                pushed_at: None,
+                forks: Vec::new(),
+                recent_commits: Vec::new(),
+                issues: Vec::new(),
+                commit_count: 0,
+                unavailable: false,
+                default_branch: None,
+                license: None,
+                ahead_by: None,
+                collaborators_count: None,
+                tags_count: None,
+                branches_count: None,
+            },
+        }
+    }
+}
+
+impl RepoBuilder {
+    pub fn id(mut self, id: i64) -> Self {
+        self.repo.id = id;
+        self
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.repo.name = name.to_string();
+        self
+    }
+
+    /// Sets the owner login; the owner's `html_url` is derived from it.
+    pub fn owner(mut self, login: &str) -> Self {
+        self.repo.owner.login = login.to_string();
+        self
+    }
+
+    pub fn language(mut self, language: &str) -> Self {
+        self.repo.language = Some(language.to_string());
+        self
+    }
+
+    pub fn stars(mut self, stars: u64) -> Self {
+        self.repo.stargazers_count = stars;
+        self
+    }
+
+    pub fn forks_count(mut self, forks: u64) -> Self {
+        self.repo.forks_count = forks;
+        self
+    }
+
+    pub fn open_issues_count(mut self, count: u64) -> Self {
+        self.repo.open_issues_count = count;
+        self
+    }
+
+    pub fn archived(mut self, archived: bool) -> Self {
+        self.repo.archived = archived;
+        self
+    }
+
+    pub fn created_at(mut self, created_at: &str) -> Self {
+        self.repo.created_at = Some(created_at.to_string());
+        self
+    }
+
+    pub fn pushed_at(mut self, pushed_at: &str) -> Self {
+        self.repo.pushed_at = Some(pushed_at.to_string());
+        self
+    }
+
+    pub fn forks(mut self, forks: Vec<Repo>) -> Self {
+        self.repo.forks = forks;
+        self
+    }
+
+    pub fn issues(mut self, issues: Vec<Issue>) -> Self {
+        self.repo.issues = issues;
+        self
+    }
+
+    /// Sets the fetched commits and `commit_count` to match.
+    pub fn commits(mut self, commits: Vec<Commit>) -> Self {
+        self.repo.commit_count = commits.len() as u64;
+        self.repo.recent_commits = commits;
+        self
+    }
+
+    pub fn build(self) -> Repo {
+        let mut repo = self.repo;
+        repo.full_name = format!("{}/{}", repo.owner.login, repo.name);
+        repo.html_url = format!("https://github.com/{}", repo.full_name);
+        repo.owner.html_url = format!("https://github.com/{}", repo.owner.login);
+        repo
+    }
+}
+
+/// Repos compare by identity: two values are equal when they share a GitHub `id`,
+/// even if one of them has been enriched with commits, issues or forks and the other
+/// hasn't. Use this for deduplication, not to check whether the data is the same.
+impl PartialEq for Repo {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Repo {}
+
+impl std::hash::Hash for Repo {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
 }
@@ -0,0 +1,130 @@
+//! Request budget shared by several services.
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+
+use crate::error::AppError;
+use crate::model::{Commit, CommitFile, Issue, Repo};
+use crate::service::traits::GitRepositoryService;
+
+/// Wraps a service so every request first takes a permit from a shared semaphore.
+/// Giving the same semaphore to the services of several languages caps how many
+/// requests are in flight across all of them.
+pub struct BoundedGitService<'a, S> {
+    inner: &'a S,
+    permits: Arc<Semaphore>,
+}
+
+impl<'a, S: GitRepositoryService> BoundedGitService<'a, S> {
+    pub fn new(inner: &'a S, permits: Arc<Semaphore>) -> Self {
+        Self { inner, permits }
+    }
+
+    /// Runs `request` while holding a permit; fails without sending it once the
+    /// semaphore has been closed.
+    async fn bounded<T>(
+        &self,
+        request: impl std::future::Future<Output = Result<T, AppError>>,
+    ) -> Result<T, AppError> {
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|_| AppError::Config("request semaphore was closed".to_string()))?;
+        request.await
+    }
+}
+
+impl<S: GitRepositoryService> GitRepositoryService for BoundedGitService<'_, S> {
+    async fn fetch_top_repositories(
+        &self,
+        language: &str,
+        per_page: u8,
+    ) -> Result<Vec<Repo>, AppError> {
+        self.bounded(self.inner.fetch_top_repositories(language, per_page))
+            .await
+    }
+
+    async fn fetch_repo_forks(&self, owner: &str, repo: &str) -> Result<Vec<Repo>, AppError> {
+        self.bounded(self.inner.fetch_repo_forks(owner, repo)).await
+    }
+
+    async fn fetch_repo_details(&self, owner: &str, repo: &str) -> Result<Repo, AppError> {
+        self.bounded(self.inner.fetch_repo_details(owner, repo))
+            .await
+    }
+
+    async fn fetch_user_repositories(&self, login: &str) -> Result<Vec<Repo>, AppError> {
+        self.bounded(self.inner.fetch_user_repositories(login))
+            .await
+    }
+
+    async fn fetch_recent_commits(
+        &self,
+        owner: &str,
+        repo: &str,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> Result<Vec<Commit>, AppError> {
+        self.bounded(self.inner.fetch_recent_commits(owner, repo, since, until))
+            .await
+    }
+
+    async fn fetch_open_issues(&self, owner: &str, repo: &str) -> Result<Vec<Issue>, AppError> {
+        self.bounded(self.inner.fetch_open_issues(owner, repo))
+            .await
+    }
+
+    async fn fetch_commit_with_files(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+    ) -> Result<Commit, AppError> {
+        self.bounded(self.inner.fetch_commit_with_files(owner, repo, sha))
+            .await
+    }
+
+    async fn fetch_compare(
+        &self,
+        owner: &str,
+        repo: &str,
+        base: &str,
+        head: &str,
+    ) -> Result<Vec<CommitFile>, AppError> {
+        self.bounded(self.inner.fetch_compare(owner, repo, base, head))
+            .await
+    }
+
+    async fn fetch_fork_ahead_by(
+        &self,
+        parent_owner: &str,
+        parent_repo: &str,
+        fork_owner: &str,
+        branch: &str,
+    ) -> Result<u64, AppError> {
+        self.bounded(
+            self.inner
+                .fetch_fork_ahead_by(parent_owner, parent_repo, fork_owner, branch),
+        )
+        .await
+    }
+
+    async fn fetch_collaborators_count(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Option<u64>, AppError> {
+        self.bounded(self.inner.fetch_collaborators_count(owner, repo))
+            .await
+    }
+
+    async fn fetch_tags_count(&self, owner: &str, repo: &str) -> Result<u64, AppError> {
+        self.bounded(self.inner.fetch_tags_count(owner, repo)).await
+    }
+
+    async fn fetch_branches_count(&self, owner: &str, repo: &str) -> Result<u64, AppError> {
+        self.bounded(self.inner.fetch_branches_count(owner, repo))
+            .await
+    }
+}
@@ -0,0 +1,69 @@
+//! Conditional-request cache shared across service clones.
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+struct Entry {
+    etag: String,
+    value: Arc<dyn Any + Send + Sync>,
+}
+
+/// Parsed responses keyed by request URL, with the `ETag` GitHub sent for each. A later
+/// request for the same URL sends `If-None-Match`; on a 304 the parsed value is reused.
+#[derive(Default)]
+pub struct EtagCache {
+    entries: RwLock<HashMap<String, Entry>>,
+}
+
+impl std::fmt::Debug for EtagCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EtagCache")
+            .field("entries", &self.len())
+            .finish()
+    }
+}
+
+impl EtagCache {
+    /// `ETag` stored for `url`, to send as `If-None-Match`.
+    pub fn etag(&self, url: &str) -> Option<String> {
+        self.read().get(url).map(|entry| entry.etag.clone())
+    }
+
+    /// The value parsed from the response that carried the stored `ETag`, if it is a `T`.
+    pub fn get<T: Clone + 'static>(&self, url: &str) -> Option<T> {
+        self.read()
+            .get(url)
+            .and_then(|entry| entry.value.downcast_ref::<T>())
+            .cloned()
+    }
+
+    pub fn insert<T: Send + Sync + 'static>(&self, url: String, etag: String, value: T) {
+        let entry = Entry {
+            etag,
+            value: Arc::new(value),
+        };
+        self.entries
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(url, entry);
+    }
+
+    pub fn clear(&self) {
+        self.entries
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<String, Entry>> {
+        self.entries.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
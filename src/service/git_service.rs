@@ -1,29 +1,240 @@
 //! GitHub API service.
-use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, USER_AGENT};
-use reqwest::{Client, Url};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use reqwest::header::HeaderName;
+use reqwest::header::{
+    HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, ETAG, IF_NONE_MATCH, LINK, USER_AGENT,
+};
+use reqwest::{Client, RequestBuilder, Response, ResponseBuilderExt, StatusCode, Url};
 use serde_json::Value;
 
-use crate::config::GitHubConfig;
+use crate::config::{CommitOrder, GitHubConfig};
+use crate::errln;
 use crate::error::AppError;
-use crate::model::{Commit, Issue, Repo};
+use crate::model::{Commit, CommitFile, Issue, IssueState, Repo};
+use crate::service::etag_cache::EtagCache;
+use crate::service::metrics::{MetricsSnapshot, ServiceMetrics};
+use crate::service::page_info::PageInfo;
+use crate::service::rate_limit::RateLimiter;
+use crate::service::recording::{self, RecordedResponse};
+use crate::service::search_query::{RepoSearchQuery, SortField, SortOrder};
 use crate::service::traits::GitRepositoryService;
 use crate::util::json::json_error;
 
+/// Upper bound on pages followed when listing a user's repositories.
+const MAX_USER_REPO_PAGES: usize = 20;
+
+/// Page size used when listing a user's repositories.
+const USER_REPOS_PER_PAGE: usize = 100;
+
+/// Largest `per_page` GitHub accepts.
+const MAX_PER_PAGE: usize = 100;
+
 #[allow(dead_code)]
 #[derive(Clone)]
 pub struct GitService {
     http: Client,
     config: GitHubConfig,
+    rate_limit: Arc<RateLimiter>,
+    metrics: Arc<ServiceMetrics>,
+    /// Commit details already fetched this run, keyed by SHA. Forks share history with
+    /// their parent, so the same commit is often requested for several repositories.
+    commit_details: Arc<Mutex<HashMap<String, Commit>>>,
+    /// Parsed responses and their `ETag`s, reused when GitHub answers 304 Not Modified.
+    etags: Arc<EtagCache>,
 }
 
 impl GitService {
     pub fn new(config: GitHubConfig) -> Result<Self, AppError> {
-        let http = Client::builder()
+        let mut builder = Client::builder()
             .default_headers(Self::default_headers(&config)?)
-            .build()
-            .map_err(AppError::from)?;
+            .http2_adaptive_window(config.http2_adaptive_window);
+        if config.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        let http = builder.build().map_err(AppError::from)?;
+
+        Ok(Self {
+            http,
+            config,
+            rate_limit: Arc::default(),
+            metrics: Arc::default(),
+            commit_details: Arc::default(),
+            etags: Arc::default(),
+        })
+    }
+
+    /// Requests sent and cache hits/misses so far, across all clones of this service.
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// Forgets every cached response, so the next requests are sent unconditionally.
+    pub fn clear_cache(&self) {
+        self.etags.clear();
+        self.commit_details
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    /// Adds `If-None-Match` if a response for the same URL is cached. Returns the
+    /// request with the cache key (its full URL) to look the response up by.
+    fn conditional(&self, request: RequestBuilder) -> (RequestBuilder, Option<String>) {
+        let key = request
+            .try_clone()
+            .and_then(|r| r.build().ok())
+            .map(|r| r.url().to_string());
+        match key.as_deref().and_then(|key| self.etags.etag(key)) {
+            Some(etag) => (request.header(IF_NONE_MATCH, etag), key),
+            None => (request, key),
+        }
+    }
+
+    /// The value cached under `key` if GitHub answered 304 Not Modified.
+    fn not_modified<T: Clone + 'static>(
+        &self,
+        response: &Response,
+        key: Option<&str>,
+    ) -> Option<T> {
+        if response.status() != StatusCode::NOT_MODIFIED {
+            return None;
+        }
+        let value = self.etags.get(key?)?;
+        self.metrics.record_cache_hit();
+        Some(value)
+    }
+
+    /// Caches `value` under `key` if the response it was parsed from had an `ETag`.
+    fn remember<T: Clone + Send + Sync + 'static>(
+        &self,
+        key: Option<String>,
+        etag: Option<String>,
+        value: &T,
+    ) {
+        if let (Some(key), Some(etag)) = (key, etag) {
+            self.etags.insert(key, etag, value.clone());
+        }
+    }
+
+    /// Like [`Self::send`], but a connection failure or a 502/503/504 is retried with
+    /// exponential backoff until `retry.max_attempts` tries have been made. Anything else,
+    /// e.g. a 404, is returned right away.
+    async fn send_with_retry(&self, request: RequestBuilder) -> Result<Response, AppError> {
+        let max_attempts = self.config.retry.max_attempts.max(1);
+        let mut request = request;
+        let mut attempt = 0;
+
+        loop {
+            let next = request.try_clone();
+            let result = self.send(request).await;
+            attempt += 1;
+
+            let failure = match &result {
+                Ok(response) if is_transient_status(response.status()) => {
+                    response.status().to_string()
+                }
+                Err(AppError::Http(err)) if is_transient_error(err) => err.to_string(),
+                _ => return result,
+            };
+            match next {
+                Some(next) if attempt < max_attempts => {
+                    let delay = self.config.retry.delay_for(attempt - 1);
+                    errln!(
+                        "      ⚠ {failure}; retrying in {}ms (attempt {}/{})",
+                        delay.as_millis(),
+                        attempt + 1,
+                        max_attempts
+                    );
+                    tokio::time::sleep(delay).await;
+                    request = next;
+                }
+                _ => return result,
+            }
+        }
+    }
+
+    /// Sends a request once the shared rate limit allows it (or fails, per
+    /// `rate_limit_behavior`), then records the quota GitHub reports so other clones of
+    /// this service can back off too. A 403/429 that leaves the quota exhausted is sent
+    /// once more after the wait, instead of surfacing as an error.
+    async fn send(&self, request: RequestBuilder) -> Result<Response, AppError> {
+        let retry = request.try_clone();
+        let response = self.send_once(request).await?;
+
+        let rate_limited = matches!(
+            response.status(),
+            StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS
+        ) && self.rate_limit.delay().is_some();
+        match retry {
+            Some(retry) if rate_limited => self.send_once(retry).await,
+            _ => Ok(response),
+        }
+    }
+
+    async fn send_once(&self, request: RequestBuilder) -> Result<Response, AppError> {
+        if let Some(dir) = &self.config.replay_dir {
+            return Self::replay(dir, request);
+        }
+        self.rate_limit
+            .acquire(
+                self.config.rate_limit_behavior,
+                self.config.rate_limit_max_wait,
+            )
+            .await?;
+        self.metrics.record_request();
+        let response = request.send().await.map_err(AppError::from)?;
+        self.rate_limit.record(response.headers());
+        match &self.config.record_dir {
+            // A 304 only means "unchanged"; keep the full response recorded before it.
+            Some(dir) if response.status() != StatusCode::NOT_MODIFIED => {
+                self.record(dir, response).await
+            }
+            _ => Ok(response),
+        }
+    }
+
+    /// Saves `response` under `dir` with its status and `RECORDED_HEADERS`, and hands back
+    /// an equivalent response for the caller to read.
+    async fn record(
+        &self,
+        dir: &std::path::Path,
+        response: Response,
+    ) -> Result<Response, AppError> {
+        let url = response.url().clone();
+        let status = response.status().as_u16();
+        let headers = RECORDED_HEADERS
+            .iter()
+            .filter_map(|name| {
+                let value = response.headers().get(name)?.to_str().ok()?;
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect();
+        let body = self.read_limited(response).await?;
+        let recorded = RecordedResponse {
+            status,
+            headers,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        };
+
+        if let Err(e) = recording::record(dir, url.as_str(), &recorded) {
+            errln!("      ⚠ Failed to record response from {}: {e}", url.path());
+        }
+        recorded_response(url, recorded)
+    }
 
-        Ok(Self { http, config })
+    /// The response recorded for the request's URL, with its status and headers.
+    fn replay(dir: &std::path::Path, request: RequestBuilder) -> Result<Response, AppError> {
+        let request = request.build().map_err(AppError::from)?;
+        let url = request.url().clone();
+        let recorded = recording::replay(dir, url.as_str()).map_err(|err| {
+            AppError::Io(std::io::Error::new(
+                err.kind(),
+                format!("no recorded response for {url}: {err}"),
+            ))
+        })?;
+        recorded_response(url, recorded)
     }
 
     fn default_headers(config: &GitHubConfig) -> Result<HeaderMap, AppError> {
@@ -53,11 +264,168 @@ impl GitService {
         Ok(headers)
     }
 
+    /// Like `error_for_status`, but reports repositories GitHub has blocked
+    /// (e.g. DMCA takedowns answered with 451 or 422) as `RepositoryUnavailable`.
+    async fn check_repo_status(
+        &self,
+        response: Response,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Response, AppError> {
+        let status = response.status();
+        if status == StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS
+            || status == StatusCode::UNPROCESSABLE_ENTITY
+        {
+            return Err(AppError::RepositoryUnavailable {
+                repo: format!("{owner}/{repo}"),
+                status: status.as_u16(),
+            });
+        }
+
+        self.check_status(response).await
+    }
+
+    /// Like `error_for_status`, but 4xx responses become `GitHubApi` errors that carry
+    /// GitHub's `message` field (or a preview of the raw body) instead of just the status.
+    async fn check_status(&self, response: Response) -> Result<Response, AppError> {
+        let status = response.status();
+        if !status.is_client_error() {
+            return response.error_for_status().map_err(AppError::from);
+        }
+
+        let path = response.url().path().to_string();
+        let body = self.read_body(response).await.unwrap_or_default();
+
+        Err(self.api_error(status, &path, &body))
+    }
+
+    fn api_error(&self, status: StatusCode, path: &str, body: &str) -> AppError {
+        let message = match error_detail(body, self.config.error_body_preview) {
+            Some(detail) => format!("{status} from {path}: {detail}"),
+            None => format!("{status} from {path}"),
+        };
+        AppError::GitHubApi {
+            status: status.as_u16(),
+            message,
+        }
+    }
+
+    /// Reads the response body as text, failing once it grows past `max_response_bytes`
+    /// instead of buffering an arbitrarily large body.
+    async fn read_body(&self, response: Response) -> Result<String, AppError> {
+        let body = self.read_limited(response).await?;
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    /// The raw response body, or an error once it grows past `max_response_bytes`.
+    async fn read_limited(&self, mut response: Response) -> Result<Vec<u8>, AppError> {
+        let limit = self.config.max_response_bytes;
+        let path = response.url().path().to_string();
+        let status = response.status().as_u16();
+        let too_large = || AppError::GitHubApi {
+            status,
+            message: format!("response from {path} exceeds {limit} bytes"),
+        };
+
+        if response
+            .content_length()
+            .is_some_and(|len| len > limit as u64)
+        {
+            return Err(too_large());
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(AppError::from)? {
+            if body.len() + chunk.len() > limit {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
+    }
+
+    /// The search for `language` that `fetch_top_repositories` runs: its GitHub name,
+    /// plus the configured creation dates and `SEARCH_QUALIFIERS`.
+    fn language_query(&self, language: &str) -> RepoSearchQuery {
+        let mut query =
+            RepoSearchQuery::new().language(&self.config.language_aliases.resolve(language));
+        query.created_after = self.config.created_after.clone();
+        query.created_before = self.config.created_before.clone();
+        query
+            .qualifiers
+            .extend(self.config.search_qualifiers.clone());
+        query
+    }
+
     pub async fn fetch_top_repositories(
         &self,
         language: &str,
         per_page: u8,
     ) -> Result<Vec<Repo>, AppError> {
+        let (repos, _) = self
+            .fetch_top_repositories_with_total(language, per_page)
+            .await?;
+        Ok(repos)
+    }
+
+    /// The top repositories along with how many repositories match the search in total
+    /// (`total_count`), e.g. to show "10 of 48,213".
+    pub async fn fetch_top_repositories_with_total(
+        &self,
+        language: &str,
+        per_page: u8,
+    ) -> Result<(Vec<Repo>, u64), AppError> {
+        let (repos, info) = self.fetch_top_repositories_page(language, per_page).await?;
+        // GitHub always sends `total_count`; without it, all we know is this page.
+        let total = info.total.unwrap_or(repos.len() as u64);
+        Ok((repos, total))
+    }
+
+    /// Like [`Self::fetch_top_repositories`], ranked by `sort` in `order` instead of by
+    /// stars, descending.
+    pub async fn fetch_top_repositories_sorted(
+        &self,
+        language: &str,
+        per_page: u8,
+        sort: SortField,
+        order: SortOrder,
+    ) -> Result<Vec<Repo>, AppError> {
+        let query = self.language_query(language).sort_by(sort, order);
+        self.fetch_repositories(&query, per_page).await
+    }
+
+    /// Like [`Self::fetch_top_repositories`], also returning the search's `total_count`
+    /// and whether there are more pages.
+    pub async fn fetch_top_repositories_page(
+        &self,
+        language: &str,
+        per_page: u8,
+    ) -> Result<(Vec<Repo>, PageInfo), AppError> {
+        let query = self
+            .language_query(language)
+            .sort_by(SortField::Stars, SortOrder::Desc);
+        self.fetch_repositories_page(&query, per_page).await
+    }
+
+    /// The repositories matching `query`, ranked by its sort, which is sent as given: language
+    /// aliases, `CREATED_*` and `SEARCH_QUALIFIERS` only apply to
+    /// [`Self::fetch_top_repositories`].
+    pub async fn fetch_repositories(
+        &self,
+        query: &RepoSearchQuery,
+        per_page: u8,
+    ) -> Result<Vec<Repo>, AppError> {
+        let (repos, _) = self.fetch_repositories_page(query, per_page).await?;
+        Ok(repos)
+    }
+
+    /// Like [`Self::fetch_repositories`], also returning the search's `total_count` and
+    /// whether there are more pages.
+    pub async fn fetch_repositories_page(
+        &self,
+        query: &RepoSearchQuery,
+        per_page: u8,
+    ) -> Result<(Vec<Repo>, PageInfo), AppError> {
         let per_page = per_page.clamp(1, 100);
 
         let base_url = Url::parse(&self.config.api_base)
@@ -67,22 +435,22 @@ impl GitService {
             AppError::Config(format!("failed to construct search endpoint URL: {err}"))
         })?;
 
-        let response = self
-            .http
-            .get(url)
-            .query(&[
-                ("q", format!("language:{language}")),
-                ("sort", "stars".to_string()),
-                ("order", "desc".to_string()),
-                ("per_page", per_page.to_string()),
-                ("page", "1".to_string()),
-            ])
-            .send()
-            .await
-            .map_err(AppError::from)?;
-
-        let response = response.error_for_status().map_err(AppError::from)?;
-        let body = response.text().await.map_err(AppError::from)?;
+        let (request, key) = self.conditional(self.http.get(url).query(&[
+            ("q", query.to_query_string()),
+            ("sort", query.sort.as_query_value().to_string()),
+            ("order", query.order.as_query_value().to_string()),
+            ("per_page", per_page.to_string()),
+            ("page", "1".to_string()),
+        ]));
+        let response = self.send_with_retry(request).await?;
+        if let Some(page) = self.not_modified(&response, key.as_deref()) {
+            return Ok(page);
+        }
+
+        let response = self.check_status(response).await?;
+        let etag = etag_of(&response);
+        let has_next = has_next_page(&response);
+        let body = self.read_body(response).await?;
         let root: Value = serde_json::from_str(&body).map_err(AppError::from)?;
 
         let items = root
@@ -90,13 +458,31 @@ impl GitService {
             .and_then(Value::as_array)
             .ok_or_else(|| json_error("GitHub search response missing `items` array"))?;
 
-        items
+        let repos = items
             .iter()
             .map(Repo::from_json)
-            .collect::<Result<Vec<_>, _>>()
+            .collect::<Result<Vec<_>, _>>()?;
+        let info = PageInfo {
+            total: root.get("total_count").and_then(Value::as_u64),
+            has_next,
+        };
+        let page = (repos, info);
+        self.remember(key, etag, &page);
+        Ok(page)
     }
 
     pub async fn fetch_repo_forks(&self, owner: &str, repo: &str) -> Result<Vec<Repo>, AppError> {
+        let (forks, _) = self.fetch_repo_forks_page(owner, repo).await?;
+        Ok(forks)
+    }
+
+    /// Like [`Self::fetch_repo_forks`], also returning whether there are more pages. The
+    /// forks listing reports no total; `Repo::forks_count` has it.
+    pub async fn fetch_repo_forks_page(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<(Vec<Repo>, PageInfo), AppError> {
         let base_url = Url::parse(&self.config.api_base)
             .map_err(|err| AppError::Config(format!("invalid GitHub API base url: {err}")))?;
 
@@ -106,69 +492,273 @@ impl GitService {
                 AppError::Config(format!("failed to construct forks endpoint URL: {err}"))
             })?;
 
-        let response = self
-            .http
-            .get(url)
-            .query(&[
-                ("per_page", "100".to_string()),
-                ("page", "1".to_string()),
-                ("sort", "newest".to_string()),
-            ])
-            .send()
-            .await
-            .map_err(AppError::from)?;
-
-        let response = response.error_for_status().map_err(AppError::from)?;
-        let body = response.text().await.map_err(AppError::from)?;
+        let per_page = self.config.forks_per_page.clamp(1, MAX_PER_PAGE);
+        let (request, key) = self.conditional(self.http.get(url).query(&[
+            ("per_page", per_page.to_string()),
+            ("page", "1".to_string()),
+            ("sort", self.config.fork_sort.as_query_value().to_string()),
+        ]));
+        let response = self.send_with_retry(request).await?;
+        if let Some(page) = self.not_modified(&response, key.as_deref()) {
+            return Ok(page);
+        }
+
+        let response = self.check_repo_status(response, owner, repo).await?;
+        let etag = etag_of(&response);
+        let has_next = has_next_page(&response);
+        let body = self.read_body(response).await?;
         let root: Value = serde_json::from_str(&body).map_err(AppError::from)?;
 
         let items = root
             .as_array()
             .ok_or_else(|| json_error("GitHub forks response was not an array"))?;
 
-        items
+        let forks = items
             .iter()
             .map(Repo::from_json)
-            .collect::<Result<Vec<_>, _>>()
+            .collect::<Result<Vec<_>, _>>()?;
+        let page = (
+            forks,
+            PageInfo {
+                total: None,
+                has_next,
+            },
+        );
+        self.remember(key, etag, &page);
+        Ok(page)
+    }
+
+    /// Fetches the full repository object from `/repos/{owner}/{repo}`.
+    pub async fn fetch_repo_details(&self, owner: &str, repo: &str) -> Result<Repo, AppError> {
+        let base_url = Url::parse(&self.config.api_base)
+            .map_err(|err| AppError::Config(format!("invalid GitHub API base url: {err}")))?;
+
+        let url = base_url
+            .join(&format!("repos/{owner}/{repo}"))
+            .map_err(|err| {
+                AppError::Config(format!("failed to construct repo endpoint URL: {err}"))
+            })?;
+
+        let (request, key) = self.conditional(self.http.get(url));
+        let response = self.send(request).await?;
+        if let Some(details) = self.not_modified(&response, key.as_deref()) {
+            return Ok(details);
+        }
+
+        let response = self.check_repo_status(response, owner, repo).await?;
+        let etag = etag_of(&response);
+        let body = self.read_body(response).await?;
+        let root: Value = serde_json::from_str(&body).map_err(AppError::from)?;
+
+        let details = Repo::from_json(&root)?;
+        self.remember(key, etag, &details);
+        Ok(details)
+    }
+
+    /// Fills fields the search API omits (`has_issues`, `pushed_at`, `default_branch`,
+    /// `license`, ...) from the full repository endpoint.
+    pub async fn enrich_repo_details(&self, repo: &mut Repo) -> Result<(), AppError> {
+        let details = self
+            .fetch_repo_details(&repo.owner.login, &repo.name)
+            .await?;
+        repo.fill_details(&details);
+        Ok(())
+    }
+
+    /// Fetches every public repository owned by a user or organization, following
+    /// the `Link: rel="next"` pagination headers GitHub returns.
+    pub async fn fetch_user_repositories(&self, login: &str) -> Result<Vec<Repo>, AppError> {
+        let base_url = Url::parse(&self.config.api_base)
+            .map_err(|err| AppError::Config(format!("invalid GitHub API base url: {err}")))?;
+
+        let mut url = base_url
+            .join(&format!("users/{login}/repos"))
+            .map_err(|err| {
+                AppError::Config(format!(
+                    "failed to construct user repos endpoint URL: {err}"
+                ))
+            })?;
+        url.query_pairs_mut()
+            .append_pair("per_page", &USER_REPOS_PER_PAGE.to_string())
+            .append_pair("page", "1");
+
+        let mut repos = Vec::new();
+        for _ in 0..MAX_USER_REPO_PAGES {
+            let response = self.send(self.http.get(url.clone())).await?;
+            let response = self.check_status(response).await?;
+            let next = link_url(response.headers(), response.url(), "next");
+            let body = self.read_body(response).await?;
+            let root: Value = serde_json::from_str(&body).map_err(AppError::from)?;
+
+            let items = root
+                .as_array()
+                .ok_or_else(|| json_error("GitHub user repos response was not an array"))?;
+            for item in items {
+                repos.push(Repo::from_json(item)?);
+            }
+
+            let next = match next {
+                Ok(next) => next,
+                // Without a usable Link header, a full page suggests there's another one.
+                Err(e) => {
+                    errln!("      ⚠ {e}; falling back to page numbers");
+                    (items.len() == USER_REPOS_PER_PAGE)
+                        .then(|| with_page(&url, page_number(&url).unwrap_or(1) + 1))
+                }
+            };
+            match next {
+                Some(next) => url = next,
+                None => break,
+            }
+        }
+
+        Ok(repos)
     }
 
+    /// Fetches up to `commits_per_page` commits, newest first by default. With
+    /// `CommitOrder::Oldest` the oldest commits are returned, oldest first; that follows
+    /// the `rel="last"` link, so it costs one or two extra requests for repos with more
+    /// than one page of history. `since` and `until` are passed to GitHub as ISO-8601
+    /// bounds on the commit date.
     pub async fn fetch_recent_commits(
         &self,
         owner: &str,
         repo: &str,
+        since: Option<&str>,
+        until: Option<&str>,
     ) -> Result<Vec<Commit>, AppError> {
         let base_url = Url::parse(&self.config.api_base)
             .map_err(|err| AppError::Config(format!("invalid GitHub API base url: {err}")))?;
 
-        let url = base_url
+        let mut url = base_url
             .join(&format!("repos/{owner}/{repo}/commits"))
             .map_err(|err| {
                 AppError::Config(format!("failed to construct commits endpoint URL: {err}"))
             })?;
+        {
+            let mut query = url.query_pairs_mut();
+            if let Some(since) = since {
+                query.append_pair("since", since);
+            }
+            if let Some(until) = until {
+                query.append_pair("until", until);
+            }
+            query
+                .append_pair("per_page", &self.commits_per_page().to_string())
+                .append_pair("page", "1");
+        }
 
-        let response = self
-            .http
-            .get(url)
-            .query(&[("per_page", "50".to_string()), ("page", "1".to_string())])
-            .send()
-            .await
-            .map_err(AppError::from)?;
+        let (newest, last_page) = self.fetch_commit_page(url, owner, repo).await?;
+
+        match self.config.commit_order {
+            CommitOrder::Newest => Ok(newest),
+            CommitOrder::Oldest => self.oldest_commits(newest, last_page, owner, repo).await,
+        }
+    }
+
+    fn commits_per_page(&self) -> usize {
+        self.config.commits_per_page.clamp(1, MAX_PER_PAGE)
+    }
+
+    /// Fetches one page of commits along with the `rel="last"` page URL, if any.
+    async fn fetch_commit_page(
+        &self,
+        url: Url,
+        owner: &str,
+        repo: &str,
+    ) -> Result<(Vec<Commit>, Option<Url>), AppError> {
+        let (request, key) = self.conditional(self.http.get(url));
+        let response = self.send_with_retry(request).await?;
+        if let Some(page) = self.not_modified(&response, key.as_deref()) {
+            return Ok(page);
+        }
 
-        let response = response.error_for_status().map_err(AppError::from)?;
-        let body = response.text().await.map_err(AppError::from)?;
+        // GitHub answers 409 "Git Repository is empty." for repositories without commits.
+        if response.status() == StatusCode::CONFLICT {
+            let path = response.url().path().to_string();
+            let body = self.read_body(response).await.unwrap_or_default();
+            return match serde_json::from_str(&body) {
+                Ok(root) if is_empty_repository(&root) => Ok((Vec::new(), None)),
+                _ => Err(self.api_error(StatusCode::CONFLICT, &path, &body)),
+            };
+        }
+
+        let response = self.check_repo_status(response, owner, repo).await?;
+        let etag = etag_of(&response);
+        let last_page = link_url(response.headers(), response.url(), "last").unwrap_or_else(|e| {
+            errln!("      ⚠ {e}; using the first page of commits only");
+            None
+        });
+        let body = self.read_body(response).await?;
         let root: Value = serde_json::from_str(&body).map_err(AppError::from)?;
 
+        if is_empty_repository(&root) {
+            return Ok((Vec::new(), None));
+        }
         let items = root
             .as_array()
             .ok_or_else(|| json_error("GitHub commits response was not an array"))?;
 
-        items
+        let commits = items
             .iter()
             .map(Commit::from_json)
-            .collect::<Result<Vec<_>, _>>()
+            .collect::<Result<Vec<_>, _>>()?;
+        let page = (commits, last_page);
+        self.remember(key, etag, &page);
+        Ok(page)
+    }
+
+    /// Walks back from the last page to collect the oldest commits, oldest first.
+    async fn oldest_commits(
+        &self,
+        first_page: Vec<Commit>,
+        last_page: Option<Url>,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Vec<Commit>, AppError> {
+        let Some(last_url) = last_page else {
+            let mut commits = first_page;
+            commits.reverse();
+            return Ok(commits);
+        };
+
+        let (mut commits, _) = self
+            .fetch_commit_page(last_url.clone(), owner, repo)
+            .await?;
+
+        // A short last page is topped up from the page before it.
+        let per_page = self.commits_per_page();
+        if commits.len() < per_page {
+            match page_number(&last_url) {
+                Some(2) => commits = first_page.into_iter().chain(commits).collect(),
+                Some(page) if page > 2 => {
+                    let (previous, _) = self
+                        .fetch_commit_page(with_page(&last_url, page - 1), owner, repo)
+                        .await?;
+                    commits = previous.into_iter().chain(commits).collect();
+                }
+                _ => {}
+            }
+        }
+
+        let excess = commits.len().saturating_sub(per_page);
+        commits.drain(..excess);
+        commits.reverse();
+        Ok(commits)
     }
 
     pub async fn fetch_open_issues(&self, owner: &str, repo: &str) -> Result<Vec<Issue>, AppError> {
+        self.fetch_issues(owner, repo, IssueState::Open).await
+    }
+
+    /// Fetches the first page of the repository's issues in `state`, leaving out the pull
+    /// requests GitHub lists among them.
+    pub async fn fetch_issues(
+        &self,
+        owner: &str,
+        repo: &str,
+        state: IssueState,
+    ) -> Result<Vec<Issue>, AppError> {
         let base_url = Url::parse(&self.config.api_base)
             .map_err(|err| AppError::Config(format!("invalid GitHub API base url: {err}")))?;
 
@@ -178,38 +768,57 @@ impl GitService {
                 AppError::Config(format!("failed to construct issues endpoint URL: {err}"))
             })?;
 
-        let response = self
-            .http
-            .get(url)
-            .query(&[
-                ("state", "open".to_string()),
-                ("per_page", "100".to_string()),
-                ("page", "1".to_string()),
-            ])
-            .send()
-            .await
-            .map_err(AppError::from)?;
-
-        let response = response.error_for_status().map_err(AppError::from)?;
-        let body = response.text().await.map_err(AppError::from)?;
+        let per_page = self.config.issues_per_page.clamp(1, MAX_PER_PAGE);
+        let (request, key) = self.conditional(self.http.get(url).query(&[
+            ("state", state.as_query_value().to_string()),
+            ("per_page", per_page.to_string()),
+            ("page", "1".to_string()),
+        ]));
+        let response = self.send_with_retry(request).await?;
+        if let Some(issues) = self.not_modified(&response, key.as_deref()) {
+            return Ok(issues);
+        }
+
+        let response = self.check_repo_status(response, owner, repo).await?;
+        let etag = etag_of(&response);
+        let body = self.read_body(response).await?;
         let root: Value = serde_json::from_str(&body).map_err(AppError::from)?;
 
         let items = root
             .as_array()
             .ok_or_else(|| json_error("GitHub issues response was not an array"))?;
 
-        items
+        let issues = items
             .iter()
             .map(Issue::from_json)
-            .collect::<Result<Vec<_>, _>>()
+            .filter(|issue| !issue.as_ref().is_ok_and(|issue| issue.is_pull_request))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.remember(key, etag, &issues);
+        Ok(issues)
     }
 
+    /// Fetches a commit with its changed files. With `cache_commit_details` enabled, a SHA
+    /// that was already fetched (for this or any other repository) is served from memory.
     pub async fn fetch_commit_with_files(
         &self,
         owner: &str,
         repo: &str,
         sha: &str,
     ) -> Result<Commit, AppError> {
+        if self.config.cache_commit_details {
+            let cached = self
+                .commit_details
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(sha)
+                .cloned();
+            if let Some(commit) = cached {
+                self.metrics.record_cache_hit();
+                return Ok(commit);
+            }
+            self.metrics.record_cache_miss();
+        }
+
         let base_url = Url::parse(&self.config.api_base)
             .map_err(|err| AppError::Config(format!("invalid GitHub API base url: {err}")))?;
 
@@ -221,71 +830,467 @@ impl GitService {
                 ))
             })?;
 
-        let response = self.http.get(url).send().await.map_err(AppError::from)?;
+        let response = self.send(self.http.get(url)).await?;
 
-        let response = response.error_for_status().map_err(AppError::from)?;
-        let body = response.text().await.map_err(AppError::from)?;
+        let response = self.check_repo_status(response, owner, repo).await?;
+        let body = self.read_body(response).await?;
         let root: Value = serde_json::from_str(&body).map_err(AppError::from)?;
 
-        Commit::from_json(&root)
+        let commit = Commit::from_json(&root)?;
+        if self.config.cache_commit_details {
+            self.commit_details
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(sha.to_string(), commit.clone());
+        }
+        Ok(commit)
     }
-}
 
-impl GitRepositoryService for GitService {
-    async fn fetch_top_repositories(
+    /// Fetches the aggregate file changes between two commits with a single
+    /// `/compare/{base}...{head}` request, instead of one detail request per commit.
+    /// Changes made by `base` itself are not included.
+    pub async fn fetch_compare(
         &self,
-        language: &str,
-        per_page: u8,
-    ) -> Result<Vec<Repo>, AppError> {
-        self.fetch_top_repositories(language, per_page).await
-    }
+        owner: &str,
+        repo: &str,
+        base: &str,
+        head: &str,
+    ) -> Result<Vec<CommitFile>, AppError> {
+        let base_url = Url::parse(&self.config.api_base)
+            .map_err(|err| AppError::Config(format!("invalid GitHub API base url: {err}")))?;
 
-    async fn fetch_repo_forks(&self, owner: &str, repo: &str) -> Result<Vec<Repo>, AppError> {
-        self.fetch_repo_forks(owner, repo).await
-    }
+        let url = base_url
+            .join(&format!("repos/{owner}/{repo}/compare/{base}...{head}"))
+            .map_err(|err| {
+                AppError::Config(format!("failed to construct compare endpoint URL: {err}"))
+            })?;
 
-    async fn fetch_recent_commits(&self, owner: &str, repo: &str) -> Result<Vec<Commit>, AppError> {
-        self.fetch_recent_commits(owner, repo).await
+        let response = self.send(self.http.get(url)).await?;
+
+        let response = self.check_repo_status(response, owner, repo).await?;
+        let body = self.read_body(response).await?;
+        let root: Value = serde_json::from_str(&body).map_err(AppError::from)?;
+
+        match root.get("files") {
+            Some(Value::Array(items)) => items
+                .iter()
+                .map(CommitFile::from_json)
+                .collect::<Result<Vec<_>, _>>(),
+            Some(_) => Err(json_error("GitHub compare `files` was not an array")),
+            None => Ok(Vec::new()),
+        }
     }
 
-    async fn fetch_open_issues(&self, owner: &str, repo: &str) -> Result<Vec<Issue>, AppError> {
-        self.fetch_open_issues(owner, repo).await
+    /// Reads `ahead_by` from `/compare/{branch}...{fork_owner}:{branch}` on the parent,
+    /// i.e. how many commits the fork has that the parent doesn't, in a single request.
+    pub async fn fetch_fork_ahead_by(
+        &self,
+        parent_owner: &str,
+        parent_repo: &str,
+        fork_owner: &str,
+        branch: &str,
+    ) -> Result<u64, AppError> {
+        let base_url = Url::parse(&self.config.api_base)
+            .map_err(|err| AppError::Config(format!("invalid GitHub API base url: {err}")))?;
+
+        let url = base_url
+            .join(&format!(
+                "repos/{parent_owner}/{parent_repo}/compare/{branch}...{fork_owner}:{branch}"
+            ))
+            .map_err(|err| {
+                AppError::Config(format!("failed to construct compare endpoint URL: {err}"))
+            })?;
+
+        let response = self.send(self.http.get(url)).await?;
+
+        let response = self
+            .check_repo_status(response, parent_owner, parent_repo)
+            .await?;
+        let body = self.read_body(response).await?;
+        let root: Value = serde_json::from_str(&body).map_err(AppError::from)?;
+
+        root.get("ahead_by")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| json_error("GitHub compare response is missing `ahead_by`"))
     }
 
-    async fn fetch_commit_with_files(
+    /// Counts collaborators with a single `per_page=1` request: the page number of the
+    /// `rel="last"` link is the total. `None` without a token, which GitHub answers with
+    /// 401, or when the token may not list collaborators (403 without push access).
+    pub async fn fetch_collaborators_count(
         &self,
         owner: &str,
         repo: &str,
-        sha: &str,
-    ) -> Result<Commit, AppError> {
-        self.fetch_commit_with_files(owner, repo, sha).await
-    }
-}
+    ) -> Result<Option<u64>, AppError> {
+        if self.config.token.is_none() {
+            return Ok(None);
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use httpmock::prelude::*;
-    use serde_json::json;
+        let base_url = Url::parse(&self.config.api_base)
+            .map_err(|err| AppError::Config(format!("invalid GitHub API base url: {err}")))?;
 
-    fn service_with_base(base_url: &str) -> GitService {
-        let config = GitHubConfig {
-            token: None,
-            api_base: base_url.to_string(),
-            user_agent: "ecs160-test-agent/0.1".to_string(),
-        };
+        let url = base_url
+            .join(&format!("repos/{owner}/{repo}/collaborators"))
+            .map_err(|err| {
+                AppError::Config(format!(
+                    "failed to construct collaborators endpoint URL: {err}"
+                ))
+            })?;
 
-        GitService::new(config).expect("failed to construct test client")
+        let response = self
+            .send(self.http.get(url).query(&[("per_page", "1")]))
+            .await?;
+        if matches!(
+            response.status(),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+        ) {
+            return Ok(None);
+        }
+
+        self.count_listed(response, owner, repo, "collaborators")
+            .await
+            .map(Some)
     }
 
-    fn sample_response() -> serde_json::Value {
-        json!({
-            "total_count": 1,
-            "incomplete_results": false,
-            "items": [
-                {
-                    "id": 42,
-                    "name": "repo-one",
+    /// Number of tags, counted like collaborators from a `per_page=1` listing.
+    pub async fn fetch_tags_count(&self, owner: &str, repo: &str) -> Result<u64, AppError> {
+        self.fetch_listing_count(owner, repo, "tags").await
+    }
+
+    /// Number of branches, counted like collaborators from a `per_page=1` listing.
+    pub async fn fetch_branches_count(&self, owner: &str, repo: &str) -> Result<u64, AppError> {
+        self.fetch_listing_count(owner, repo, "branches").await
+    }
+
+    /// Requests `repos/{owner}/{repo}/{listing}` one item per page and counts the items.
+    async fn fetch_listing_count(
+        &self,
+        owner: &str,
+        repo: &str,
+        listing: &str,
+    ) -> Result<u64, AppError> {
+        let base_url = Url::parse(&self.config.api_base)
+            .map_err(|err| AppError::Config(format!("invalid GitHub API base url: {err}")))?;
+
+        let url = base_url
+            .join(&format!("repos/{owner}/{repo}/{listing}"))
+            .map_err(|err| {
+                AppError::Config(format!("failed to construct {listing} endpoint URL: {err}"))
+            })?;
+
+        let response = self
+            .send(self.http.get(url).query(&[("per_page", "1")]))
+            .await?;
+
+        self.count_listed(response, owner, repo, listing).await
+    }
+
+    /// Item count of a `per_page=1` listing: the `rel="last"` page number, or the length
+    /// of the returned page when there is no `Link` header (zero or one item). A `Link`
+    /// header that can't be read is an error, since the page alone can't tell the count.
+    async fn count_listed(
+        &self,
+        response: Response,
+        owner: &str,
+        repo: &str,
+        listing: &str,
+    ) -> Result<u64, AppError> {
+        let response = self.check_repo_status(response, owner, repo).await?;
+        if let Some(last) = link_url(response.headers(), response.url(), "last")? {
+            return page_number(&last).map(u64::from).ok_or_else(|| {
+                AppError::MalformedLinkHeader(format!("no page number in the last {listing} link"))
+            });
+        }
+
+        let body = self.read_body(response).await?;
+        let root: Value = serde_json::from_str(&body).map_err(AppError::from)?;
+        let items = root
+            .as_array()
+            .ok_or_else(|| json_error(format!("GitHub {listing} response was not an array")))?;
+
+        Ok(items.len() as u64)
+    }
+}
+
+/// Headers saved with recorded responses: pagination and counts read `Link`, the
+/// conditional-request cache reads `ETag`.
+const RECORDED_HEADERS: [HeaderName; 2] = [LINK, ETAG];
+
+/// Rebuilds a response for `url` from its recording.
+fn recorded_response(url: Url, recorded: RecordedResponse) -> Result<Response, AppError> {
+    let mut builder = http::Response::builder()
+        .status(recorded.status)
+        .url(url.clone());
+    for (name, value) in &recorded.headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    let response = builder.body(recorded.body).map_err(|err| {
+        AppError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("invalid recorded response for {url}: {err}"),
+        ))
+    })?;
+    Ok(Response::from(response))
+}
+
+/// The response's `ETag` header, if it has a readable one.
+fn etag_of(response: &Response) -> Option<String> {
+    response
+        .headers()
+        .get(ETAG)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Gateway errors GitHub answers while overloaded or deploying; worth another try.
+fn is_transient_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Failures to connect or to get a response in time, such as a reset connection.
+fn is_transient_error(err: &reqwest::Error) -> bool {
+    err.is_connect() || err.is_timeout() || err.is_request()
+}
+
+/// Whether a response body is GitHub's `{"message": "Git Repository is empty."}`.
+fn is_empty_repository(root: &Value) -> bool {
+    root.get("message")
+        .and_then(Value::as_str)
+        .is_some_and(|message| message.contains("Git Repository is empty"))
+}
+
+/// Picks the `message` from a GitHub JSON error body, falling back to the raw body,
+/// truncated to `max_chars`. Returns `None` when there is nothing to show.
+fn error_detail(body: &str, max_chars: usize) -> Option<String> {
+    let message = serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|root| root.get("message")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| body.trim().to_string());
+
+    let preview: String = message.chars().take(max_chars).collect();
+    if preview.is_empty() {
+        None
+    } else if preview.len() < message.len() {
+        Some(format!("{preview}…"))
+    } else {
+        Some(preview)
+    }
+}
+
+/// Whether the response's `Link` header points to a next page. A malformed header is
+/// logged and treated as the last page.
+fn has_next_page(response: &Response) -> bool {
+    link_url(response.headers(), response.url(), "next")
+        .unwrap_or_else(|e| {
+            errln!("      ⚠ {e}; assuming this is the last page");
+            None
+        })
+        .is_some()
+}
+
+/// Finds the target with the given `rel` in a response's `Link` header. A missing header
+/// is `Ok(None)`; a header that can't be parsed is a `MalformedLinkHeader` error.
+fn link_url(headers: &HeaderMap, base: &Url, rel: &str) -> Result<Option<Url>, AppError> {
+    let Some(value) = headers.get(LINK) else {
+        return Ok(None);
+    };
+    let value = value
+        .to_str()
+        .map_err(|_| AppError::MalformedLinkHeader("header is not valid ASCII".to_string()))?;
+
+    Ok(parse_link_header(value, base)?
+        .into_iter()
+        .find(|(_, rels)| rels.iter().any(|r| r.eq_ignore_ascii_case(rel)))
+        .map(|(url, _)| url))
+}
+
+/// Parses an RFC 8288 `Link` header into `(target, relations)` pairs. Relative targets
+/// are resolved against `base`, `rel` may be quoted or bare and may list several
+/// space-separated relations, and entries without a `rel` get an empty relation list.
+fn parse_link_header(value: &str, base: &Url) -> Result<Vec<(Url, Vec<String>)>, AppError> {
+    let malformed = |reason: &str| AppError::MalformedLinkHeader(format!("{reason} in `{value}`"));
+    let mut links = Vec::new();
+    let mut rest = value.trim();
+
+    while !rest.is_empty() {
+        let inner = rest
+            .strip_prefix('<')
+            .ok_or_else(|| malformed("expected `<` before link target"))?;
+        let end = inner
+            .find('>')
+            .ok_or_else(|| malformed("unterminated link target"))?;
+        let target = base
+            .join(inner[..end].trim())
+            .map_err(|err| malformed(&format!("invalid link target ({err})")))?;
+
+        // Parameters run up to the next comma that isn't inside a quoted value.
+        let after = &inner[end + 1..];
+        let mut in_quotes = false;
+        let params_end = after
+            .char_indices()
+            .find(|&(_, c)| {
+                if c == '"' {
+                    in_quotes = !in_quotes;
+                }
+                c == ',' && !in_quotes
+            })
+            .map_or(after.len(), |(i, _)| i);
+
+        let mut rels = Vec::new();
+        for param in after[..params_end].split(';') {
+            if let Some((key, val)) = param.split_once('=') {
+                if key.trim().eq_ignore_ascii_case("rel") {
+                    rels.extend(
+                        val.trim()
+                            .trim_matches('"')
+                            .split_whitespace()
+                            .map(String::from),
+                    );
+                }
+            }
+        }
+        links.push((target, rels));
+
+        rest = after[params_end..].trim_start_matches(',').trim_start();
+    }
+
+    Ok(links)
+}
+
+/// The `page` query parameter of a paginated URL.
+fn page_number(url: &Url) -> Option<u32> {
+    url.query_pairs()
+        .find(|(key, _)| key == "page")
+        .and_then(|(_, value)| value.parse().ok())
+}
+
+/// `url` with its `page` query parameter replaced.
+fn with_page(url: &Url, page: u32) -> Url {
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| key != "page")
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    let mut url = url.clone();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair("page", &page.to_string());
+    url
+}
+
+impl GitRepositoryService for GitService {
+    async fn fetch_top_repositories(
+        &self,
+        language: &str,
+        per_page: u8,
+    ) -> Result<Vec<Repo>, AppError> {
+        self.fetch_top_repositories(language, per_page).await
+    }
+
+    async fn fetch_repo_forks(&self, owner: &str, repo: &str) -> Result<Vec<Repo>, AppError> {
+        self.fetch_repo_forks(owner, repo).await
+    }
+
+    async fn fetch_repo_details(&self, owner: &str, repo: &str) -> Result<Repo, AppError> {
+        self.fetch_repo_details(owner, repo).await
+    }
+
+    async fn fetch_user_repositories(&self, login: &str) -> Result<Vec<Repo>, AppError> {
+        self.fetch_user_repositories(login).await
+    }
+
+    async fn fetch_recent_commits(
+        &self,
+        owner: &str,
+        repo: &str,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> Result<Vec<Commit>, AppError> {
+        self.fetch_recent_commits(owner, repo, since, until).await
+    }
+
+    async fn fetch_open_issues(&self, owner: &str, repo: &str) -> Result<Vec<Issue>, AppError> {
+        self.fetch_open_issues(owner, repo).await
+    }
+
+    async fn fetch_commit_with_files(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+    ) -> Result<Commit, AppError> {
+        self.fetch_commit_with_files(owner, repo, sha).await
+    }
+
+    async fn fetch_compare(
+        &self,
+        owner: &str,
+        repo: &str,
+        base: &str,
+        head: &str,
+    ) -> Result<Vec<CommitFile>, AppError> {
+        self.fetch_compare(owner, repo, base, head).await
+    }
+
+    async fn fetch_fork_ahead_by(
+        &self,
+        parent_owner: &str,
+        parent_repo: &str,
+        fork_owner: &str,
+        branch: &str,
+    ) -> Result<u64, AppError> {
+        self.fetch_fork_ahead_by(parent_owner, parent_repo, fork_owner, branch)
+            .await
+    }
+
+    async fn fetch_collaborators_count(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Option<u64>, AppError> {
+        self.fetch_collaborators_count(owner, repo).await
+    }
+
+    async fn fetch_tags_count(&self, owner: &str, repo: &str) -> Result<u64, AppError> {
+        self.fetch_tags_count(owner, repo).await
+    }
+
+    async fn fetch_branches_count(&self, owner: &str, repo: &str) -> Result<u64, AppError> {
+        self.fetch_branches_count(owner, repo).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ForkSort, RateLimitBehavior, RetryConfig};
+    use crate::util::backoff::JitterStrategy;
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    fn service_with_base(base_url: &str) -> GitService {
+        let config = GitHubConfig {
+            token: None,
+            api_base: base_url.to_string(),
+            user_agent: "ecs160-test-agent/0.1".to_string(),
+            ..GitHubConfig::default()
+        };
+
+        GitService::new(config).expect("failed to construct test client")
+    }
+
+    fn sample_response() -> serde_json::Value {
+        json!({
+            "total_count": 1,
+            "incomplete_results": false,
+            "items": [
+                {
+                    "id": 42,
+                    "name": "repo-one",
                     "full_name": "octocat/repo-one",
                     "html_url": "https://example.com/repo-one",
                     "forks_count": 5,
@@ -360,4 +1365,1657 @@ mod tests {
         assert_eq!(repos.len(), 1);
         mock.assert();
     }
+
+    fn user_repo(id: i64, name: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "name": name,
+            "full_name": format!("octo-org/{name}"),
+            "html_url": format!("https://example.com/{name}"),
+            "owner": {
+                "login": "octo-org",
+                "id": 9,
+                "html_url": "https://github.com/octo-org",
+                "site_admin": false
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn fetch_user_repositories_follows_pagination() {
+        let server = MockServer::start_async().await;
+        let next = format!(
+            "<{}/users/octo-org/repos?per_page=100&page=2>; rel=\"next\", <{}/users/octo-org/repos?per_page=100&page=2>; rel=\"last\"",
+            server.base_url(),
+            server.base_url()
+        );
+
+        let first_page = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/users/octo-org/repos")
+                    .query_param("page", "1");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .header("link", next.as_str())
+                    .json_body(json!([user_repo(1, "one"), user_repo(2, "two")]));
+            })
+            .await;
+
+        let second_page = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/users/octo-org/repos")
+                    .query_param("page", "2");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(json!([user_repo(3, "three")]));
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        let repos = service
+            .fetch_user_repositories("octo-org")
+            .await
+            .expect("request should succeed");
+
+        assert_eq!(repos.len(), 3);
+        assert_eq!(repos[2].slug(), "octo-org/three");
+        first_page.assert();
+        second_page.assert();
+    }
+
+    #[tokio::test]
+    async fn client_errors_surface_github_message() {
+        let server = MockServer::start_async().await;
+
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/search/repositories");
+                then.status(422)
+                    .header("content-type", "application/json")
+                    .json_body(json!({
+                        "message": "Validation Failed",
+                        "errors": [{ "resource": "Search", "code": "invalid" }]
+                    }));
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        let err = service
+            .fetch_top_repositories("Rust", 10)
+            .await
+            .expect_err("422 should be an error");
+
+        match &err {
+            AppError::GitHubApi { status, message } => {
+                assert_eq!(*status, 422);
+                assert!(message.contains("Validation Failed"), "{message}");
+            }
+            other => panic!("expected GitHubApi error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn error_detail_truncates_raw_bodies() {
+        assert_eq!(
+            error_detail("not json at all", 7).as_deref(),
+            Some("not jso…")
+        );
+        assert_eq!(error_detail("", 100), None);
+        assert_eq!(error_detail(r#"{"message":"Bad"}"#, 0), None);
+    }
+
+    #[tokio::test]
+    async fn fetch_top_repositories_appends_search_qualifiers() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/search/repositories")
+                    .query_param("q", "language:Rust stars:>100 -topic:awesome");
+
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(sample_response());
+            })
+            .await;
+
+        let service = GitService::new(GitHubConfig {
+            api_base: server.base_url(),
+            search_qualifiers: Some("stars:>100 -topic:awesome".to_string()),
+            ..GitHubConfig::default()
+        })
+        .unwrap();
+        service.fetch_top_repositories("Rust", 10).await.unwrap();
+
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn fetch_top_repositories_filters_by_creation_date() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/search/repositories").query_param(
+                    "q",
+                    "language:Rust created:>=2023-01-01 created:<=2023-12-31T23:59:59Z",
+                );
+
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(sample_response());
+            })
+            .await;
+
+        let service = GitService::new(GitHubConfig {
+            api_base: server.base_url(),
+            created_after: Some("2023-01-01".to_string()),
+            created_before: Some("2023-12-31T23:59:59Z".to_string()),
+            ..GitHubConfig::default()
+        })
+        .unwrap();
+        service.fetch_top_repositories("Rust", 10).await.unwrap();
+
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn fetch_top_repositories_resolves_language_aliases() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/search/repositories")
+                    .query_param("q", "language:C++");
+
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(sample_response());
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        service.fetch_top_repositories("cpp", 10).await.unwrap();
+
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn fetch_repo_forks_uses_configured_sort() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/repos/octocat/hello/forks")
+                    .query_param("sort", "stargazers");
+
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(json!([]));
+            })
+            .await;
+
+        let service = GitService::new(GitHubConfig {
+            api_base: server.base_url(),
+            fork_sort: ForkSort::StargazerCount,
+            ..GitHubConfig::default()
+        })
+        .unwrap();
+        let forks = service.fetch_repo_forks("octocat", "hello").await.unwrap();
+
+        assert!(forks.is_empty());
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn oversized_response_body_is_rejected() {
+        let server = MockServer::start_async().await;
+
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octocat/hello/forks");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(format!("[{}]", "0,".repeat(2048) + "0"));
+            })
+            .await;
+
+        let service = GitService::new(GitHubConfig {
+            api_base: server.base_url(),
+            max_response_bytes: 1024,
+            ..GitHubConfig::default()
+        })
+        .unwrap();
+        let err = service
+            .fetch_repo_forks("octocat", "hello")
+            .await
+            .unwrap_err();
+
+        match err {
+            AppError::GitHubApi { message, .. } => assert!(message.contains("exceeds 1024 bytes")),
+            other => panic!("expected GitHubApi error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn clones_share_exhausted_rate_limit() {
+        let server = MockServer::start_async().await;
+        let reset = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 600;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/search/repositories");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .header("x-ratelimit-remaining", "0")
+                    .header("x-ratelimit-reset", reset.to_string())
+                    .json_body(sample_response());
+            })
+            .await;
+
+        let service = GitService::new(GitHubConfig {
+            api_base: server.base_url(),
+            rate_limit_behavior: RateLimitBehavior::Wait,
+            rate_limit_max_wait: std::time::Duration::from_secs(60),
+            ..GitHubConfig::default()
+        })
+        .unwrap();
+        service.fetch_top_repositories("C", 10).await.unwrap();
+
+        let (rust, java) = (service.clone(), service.clone());
+        let (a, b) = tokio::join!(
+            rust.fetch_top_repositories("Rust", 10),
+            java.fetch_top_repositories("Java", 10)
+        );
+
+        // Both clones saw the exhausted window from the first request and sent nothing.
+        assert!(matches!(a, Err(AppError::RateLimited { .. })));
+        assert!(matches!(b, Err(AppError::RateLimited { .. })));
+        mock.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn rate_limited_403_fails_when_reset_is_beyond_max_wait() {
+        let server = MockServer::start_async().await;
+        let reset = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 600;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/search/repositories");
+                then.status(403)
+                    .header("content-type", "application/json")
+                    .header("x-ratelimit-remaining", "0")
+                    .header("x-ratelimit-reset", reset.to_string())
+                    .json_body(json!({ "message": "API rate limit exceeded" }));
+            })
+            .await;
+
+        let service = GitService::new(GitHubConfig {
+            api_base: server.base_url(),
+            rate_limit_behavior: RateLimitBehavior::Wait,
+            rate_limit_max_wait: std::time::Duration::from_millis(300),
+            ..GitHubConfig::default()
+        })
+        .unwrap();
+
+        let started = std::time::Instant::now();
+        let err = service
+            .fetch_top_repositories("Rust", 10)
+            .await
+            .unwrap_err();
+
+        // Waiting 300ms would not reach the reset 600s away, so there is no retry.
+        mock.assert_hits(1);
+        assert!(started.elapsed() < std::time::Duration::from_millis(300));
+        assert!(
+            matches!(err, AppError::RateLimited { reset_in_secs } if reset_in_secs > 500),
+            "expected RateLimited, got {err:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn exhausted_rate_limit_aborts_when_configured() {
+        let server = MockServer::start_async().await;
+        let reset = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 600;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/search/repositories");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .header("x-ratelimit-remaining", "0")
+                    .header("x-ratelimit-reset", reset.to_string())
+                    .json_body(sample_response());
+            })
+            .await;
+
+        let service = GitService::new(GitHubConfig {
+            api_base: server.base_url(),
+            rate_limit_behavior: RateLimitBehavior::Abort,
+            ..GitHubConfig::default()
+        })
+        .unwrap();
+        service.fetch_top_repositories("C", 10).await.unwrap();
+
+        let started = std::time::Instant::now();
+        let err = service
+            .fetch_top_repositories("Rust", 10)
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(err, AppError::RateLimited { reset_in_secs } if reset_in_secs > 500),
+            "expected RateLimited, got {err:?}"
+        );
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+        mock.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn fetch_compare_parses_changed_files() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/repos/octocat/hello/compare/abc1234...def5678");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(json!({
+                        "status": "ahead",
+                        "ahead_by": 3,
+                        "total_commits": 3,
+                        "commits": [],
+                        "files": [
+                            {
+                                "filename": "src/main.c",
+                                "status": "modified",
+                                "additions": 10,
+                                "deletions": 4,
+                                "changes": 14
+                            },
+                            {
+                                "filename": "README.md",
+                                "status": "added",
+                                "additions": 20,
+                                "deletions": 0,
+                                "changes": 20
+                            }
+                        ]
+                    }));
+            })
+            .await;
+
+        let service = GitService::new(GitHubConfig {
+            api_base: server.base_url(),
+            ..GitHubConfig::default()
+        })
+        .unwrap();
+        let files = service
+            .fetch_compare("octocat", "hello", "abc1234", "def5678")
+            .await
+            .unwrap();
+
+        mock.assert();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].filename, "src/main.c");
+        assert_eq!(files[0].additions, 10);
+        assert_eq!(files[0].deletions, 4);
+        assert_eq!(files[1].status, "added");
+    }
+
+    fn commit_page(shas: impl IntoIterator<Item = String>) -> serde_json::Value {
+        shas.into_iter()
+            .map(|sha| json!({ "sha": sha, "commit": { "message": "change" } }))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn fetch_recent_commits_oldest_first_walks_back_from_last_page() {
+        let server = MockServer::start_async().await;
+        let commits_url = format!("{}/repos/octocat/hello/commits", server.base_url());
+        let links = format!(
+            "<{commits_url}?per_page=50&page=2>; rel=\"next\", <{commits_url}?per_page=50&page=3>; rel=\"last\""
+        );
+
+        // 102 commits: page 1 holds n101..n52, page 2 n51..n2, page 3 n1 and n0.
+        let pages = [(1, 52..102), (2, 2..52), (3, 0..2)];
+        let mut mocks = Vec::new();
+        for (page, range) in pages {
+            let body = commit_page(range.rev().map(|n| format!("n{n}")));
+            let links = links.clone();
+            mocks.push(
+                server
+                    .mock_async(move |when, then| {
+                        when.method(GET)
+                            .path("/repos/octocat/hello/commits")
+                            .query_param("page", page.to_string());
+                        then.status(200)
+                            .header("content-type", "application/json")
+                            .header("link", links.as_str())
+                            .json_body(body);
+                    })
+                    .await,
+            );
+        }
+
+        let service = GitService::new(GitHubConfig {
+            api_base: server.base_url(),
+            commit_order: CommitOrder::Oldest,
+            ..GitHubConfig::default()
+        })
+        .unwrap();
+        let commits = service
+            .fetch_recent_commits("octocat", "hello", None, None)
+            .await
+            .unwrap();
+
+        let shas: Vec<_> = commits.iter().map(|c| c.sha.as_str()).collect();
+        let expected: Vec<_> = (0..50).map(|n| format!("n{n}")).collect();
+        assert_eq!(shas, expected);
+        for mock in &mocks {
+            mock.assert();
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_recent_commits_oldest_first_single_page_is_reversed() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octocat/hello/commits");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(commit_page(["c3", "c2", "c1"].map(String::from)));
+            })
+            .await;
+
+        let service = GitService::new(GitHubConfig {
+            api_base: server.base_url(),
+            commit_order: CommitOrder::Oldest,
+            ..GitHubConfig::default()
+        })
+        .unwrap();
+        let commits = service
+            .fetch_recent_commits("octocat", "hello", None, None)
+            .await
+            .unwrap();
+
+        let shas: Vec<_> = commits.iter().map(|c| c.sha.as_str()).collect();
+        assert_eq!(shas, vec!["c1", "c2", "c3"]);
+        mock.assert_hits(1);
+    }
+
+    fn links(value: &str) -> Vec<(String, Vec<String>)> {
+        let base = Url::parse("https://api.github.com/users/octo/repos?page=1").unwrap();
+        parse_link_header(value, &base)
+            .unwrap()
+            .into_iter()
+            .map(|(url, rels)| (url.to_string(), rels))
+            .collect()
+    }
+
+    #[test]
+    fn parse_link_header_handles_github_shape() {
+        let parsed = links(
+            r#"<https://api.github.com/users/octo/repos?page=2>; rel="next", <https://api.github.com/users/octo/repos?page=5>; rel="last""#,
+        );
+
+        assert_eq!(
+            parsed,
+            vec![
+                (
+                    "https://api.github.com/users/octo/repos?page=2".to_string(),
+                    vec!["next".to_string()]
+                ),
+                (
+                    "https://api.github.com/users/octo/repos?page=5".to_string(),
+                    vec!["last".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_link_header_splits_multiple_relations() {
+        let parsed = links(r#"<https://api.github.com/users/octo/repos?page=2>; rel="next last""#);
+
+        assert_eq!(parsed[0].1, vec!["next", "last"]);
+    }
+
+    #[test]
+    fn parse_link_header_resolves_relative_targets_and_bare_rel() {
+        let parsed = links("</users/octo/repos?page=3>; rel=next");
+
+        assert_eq!(
+            parsed,
+            vec![(
+                "https://api.github.com/users/octo/repos?page=3".to_string(),
+                vec!["next".to_string()]
+            )]
+        );
+    }
+
+    #[test]
+    fn parse_link_header_keeps_entries_without_rel() {
+        let parsed = links(
+            r#"<https://example.com/docs>; title="a, b", <https://api.github.com/users/octo/repos?page=2>; REL="Next""#,
+        );
+
+        assert_eq!(parsed.len(), 2);
+        assert!(parsed[0].1.is_empty());
+        assert_eq!(parsed[1].1, vec!["Next"]);
+    }
+
+    #[test]
+    fn parse_link_header_rejects_missing_angle_brackets() {
+        let base = Url::parse("https://api.github.com/").unwrap();
+
+        let err =
+            parse_link_header(r#"https://api.github.com/x?page=2; rel="next""#, &base).unwrap_err();
+
+        assert!(matches!(err, AppError::MalformedLinkHeader(_)));
+    }
+
+    #[tokio::test]
+    async fn fetch_user_repositories_falls_back_to_page_numbers_on_bad_link() {
+        let server = MockServer::start_async().await;
+        let full_page: Vec<_> = (0..100).map(|n| user_repo(n, &format!("r{n}"))).collect();
+
+        let first_page = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/users/octo-org/repos")
+                    .query_param("page", "1");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .header("link", "garbage; rel=\"next\"")
+                    .json_body(json!(full_page));
+            })
+            .await;
+
+        let second_page = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/users/octo-org/repos")
+                    .query_param("page", "2");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .header("link", "garbage")
+                    .json_body(json!([user_repo(100, "last")]));
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        let repos = service
+            .fetch_user_repositories("octo-org")
+            .await
+            .expect("a malformed Link header should not fail the request");
+
+        assert_eq!(repos.len(), 101);
+        first_page.assert();
+        second_page.assert();
+    }
+
+    fn commit_detail_body(sha: &str) -> serde_json::Value {
+        json!({
+            "sha": sha,
+            "commit": { "message": "change" },
+            "files": [{
+                "filename": "src/lib.rs",
+                "status": "modified",
+                "additions": 1,
+                "deletions": 1,
+                "changes": 2
+            }]
+        })
+    }
+
+    #[tokio::test]
+    async fn fetch_commit_with_files_reuses_cached_sha_across_repos() {
+        let server = MockServer::start_async().await;
+
+        let parent = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octocat/hello/commits/abc123");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(commit_detail_body("abc123"));
+            })
+            .await;
+        let fork = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/forker/hello/commits/abc123");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(commit_detail_body("abc123"));
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        let first = service
+            .fetch_commit_with_files("octocat", "hello", "abc123")
+            .await
+            .unwrap();
+        let again = service
+            .fetch_commit_with_files("octocat", "hello", "abc123")
+            .await
+            .unwrap();
+        let from_fork = service
+            .clone()
+            .fetch_commit_with_files("forker", "hello", "abc123")
+            .await
+            .unwrap();
+
+        assert_eq!(first.files.len(), 1);
+        assert_eq!(again.sha, "abc123");
+        assert_eq!(from_fork.files[0].filename, "src/lib.rs");
+        parent.assert_hits(1);
+        fork.assert_hits(0);
+    }
+
+    #[tokio::test]
+    async fn fetch_commit_with_files_refetches_when_cache_disabled() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octocat/hello/commits/abc123");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(commit_detail_body("abc123"));
+            })
+            .await;
+
+        let service = GitService::new(GitHubConfig {
+            api_base: server.base_url(),
+            cache_commit_details: false,
+            ..GitHubConfig::default()
+        })
+        .unwrap();
+        for _ in 0..2 {
+            service
+                .fetch_commit_with_files("octocat", "hello", "abc123")
+                .await
+                .unwrap();
+        }
+
+        mock.assert_hits(2);
+    }
+
+    #[tokio::test]
+    async fn enrich_repo_details_fills_search_missing_fields() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octo-org/engine");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(json!({
+                        "id": 7,
+                        "name": "engine",
+                        "full_name": "octo-org/engine",
+                        "html_url": "https://example.com/engine",
+                        "has_issues": false,
+                        "pushed_at": "2024-05-01T00:00:00Z",
+                        "default_branch": "trunk",
+                        "license": { "key": "mit", "name": "MIT License", "spdx_id": "MIT" },
+                        "owner": {
+                            "login": "octo-org",
+                            "id": 9,
+                            "html_url": "https://github.com/octo-org",
+                            "site_admin": false
+                        }
+                    }));
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        let mut repo = Repo::from_json(&user_repo(7, "engine")).unwrap();
+        assert!(repo.has_issues);
+        assert_eq!(repo.default_branch, None);
+
+        service.enrich_repo_details(&mut repo).await.unwrap();
+
+        mock.assert();
+        assert!(!repo.has_issues);
+        assert_eq!(repo.default_branch.as_deref(), Some("trunk"));
+        assert_eq!(repo.pushed_at.as_deref(), Some("2024-05-01T00:00:00Z"));
+        assert_eq!(repo.license.as_deref(), Some("MIT"));
+    }
+
+    #[tokio::test]
+    async fn fetch_fork_ahead_by_reads_compare_response() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/repos/octocat/hello/compare/main...forker:main");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(json!({
+                        "status": "diverged",
+                        "ahead_by": 4,
+                        "behind_by": 12,
+                        "total_commits": 4,
+                        "commits": [],
+                        "files": []
+                    }));
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        let ahead_by = service
+            .fetch_fork_ahead_by("octocat", "hello", "forker", "main")
+            .await
+            .unwrap();
+
+        mock.assert();
+        assert_eq!(ahead_by, 4);
+    }
+
+    #[tokio::test]
+    async fn metrics_count_commit_cache_hits() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octocat/hello/commits/abc123");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(commit_detail_body("abc123"));
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        for _ in 0..3 {
+            service
+                .fetch_commit_with_files("octocat", "hello", "abc123")
+                .await
+                .unwrap();
+        }
+
+        mock.assert_hits(1);
+        assert_eq!(
+            service.metrics(),
+            MetricsSnapshot {
+                requests: 1,
+                cache_hits: 2,
+                cache_misses: 1,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_recent_commits_treats_empty_repository_as_no_commits() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octocat/empty/commits");
+                then.status(409)
+                    .header("content-type", "application/json")
+                    .json_body(json!({
+                        "message": "Git Repository is empty.",
+                        "documentation_url": "https://docs.github.com/rest/commits/commits"
+                    }));
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        let commits = service
+            .fetch_recent_commits("octocat", "empty", None, None)
+            .await
+            .unwrap();
+
+        mock.assert();
+        assert!(commits.is_empty());
+    }
+
+    #[tokio::test]
+    async fn fetch_recent_commits_reports_other_conflicts() {
+        let server = MockServer::start_async().await;
+
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octocat/busy/commits");
+                then.status(409)
+                    .header("content-type", "application/json")
+                    .json_body(json!({ "message": "Something else conflicted" }));
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        let err = service
+            .fetch_recent_commits("octocat", "busy", None, None)
+            .await
+            .unwrap_err();
+
+        match err {
+            AppError::GitHubApi { status, message } => {
+                assert_eq!(status, 409);
+                assert!(message.contains("Something else conflicted"));
+            }
+            other => panic!("expected GitHubApi error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_collaborators_count_reads_last_page_from_link_header() {
+        let server = MockServer::start_async().await;
+        let last = format!(
+            "<{}/repositories/1/collaborators?per_page=1&page=7>; rel=\"last\"",
+            server.base_url()
+        );
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/repos/octocat/hello/collaborators")
+                    .query_param("per_page", "1");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .header("link", last.as_str())
+                    .json_body(json!([{ "login": "octocat", "id": 1 }]));
+            })
+            .await;
+
+        let service = service_with_token(&server.base_url());
+        let count = service
+            .fetch_collaborators_count("octocat", "hello")
+            .await
+            .unwrap();
+
+        mock.assert();
+        assert_eq!(count, Some(7));
+    }
+
+    #[tokio::test]
+    async fn fetch_collaborators_count_is_none_without_permission() {
+        let server = MockServer::start_async().await;
+
+        let forbidden = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octocat/hello/collaborators");
+                then.status(403)
+                    .header("content-type", "application/json")
+                    .json_body(json!({
+                        "message": "Must have push access to view repository collaborators."
+                    }));
+            })
+            .await;
+        let unauthorized = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octocat/other/collaborators");
+                then.status(401)
+                    .header("content-type", "application/json")
+                    .json_body(json!({ "message": "Bad credentials" }));
+            })
+            .await;
+
+        let service = service_with_token(&server.base_url());
+        for repo in ["hello", "other"] {
+            let count = service
+                .fetch_collaborators_count("octocat", repo)
+                .await
+                .unwrap();
+            assert_eq!(count, None, "{repo}");
+        }
+
+        forbidden.assert();
+        unauthorized.assert();
+    }
+
+    #[tokio::test]
+    async fn fetch_collaborators_count_skips_the_request_without_a_token() {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octocat/hello/collaborators");
+                then.status(200).json_body(json!([]));
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        let count = service
+            .fetch_collaborators_count("octocat", "hello")
+            .await
+            .unwrap();
+
+        assert_eq!(count, None);
+        mock.assert_hits(0);
+    }
+
+    /// Like `service_with_base`, authenticated with a dummy token.
+    fn service_with_token(base_url: &str) -> GitService {
+        GitService::new(GitHubConfig {
+            token: Some("ghp_test".to_string()),
+            api_base: base_url.to_string(),
+            ..GitHubConfig::default()
+        })
+        .expect("failed to construct test client")
+    }
+
+    fn service_with_retries(base_url: &str, max_attempts: u32) -> GitService {
+        GitService::new(GitHubConfig {
+            api_base: base_url.to_string(),
+            retry: RetryConfig {
+                jitter: JitterStrategy::None,
+                base_delay: std::time::Duration::from_millis(1),
+                max_delay: std::time::Duration::from_millis(5),
+                max_attempts,
+            },
+            ..GitHubConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn gateway_errors_are_retried_up_to_max_attempts() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octocat/hello/forks");
+                then.status(503);
+            })
+            .await;
+
+        let service = service_with_retries(&server.base_url(), 4);
+        let err = service
+            .fetch_repo_forks("octocat", "hello")
+            .await
+            .unwrap_err();
+
+        mock.assert_hits(4);
+        assert!(matches!(err, AppError::Http(_)), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn not_found_is_not_retried() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octocat/hello/issues");
+                then.status(404)
+                    .header("content-type", "application/json")
+                    .json_body(json!({ "message": "Not Found" }));
+            })
+            .await;
+
+        let service = service_with_retries(&server.base_url(), 4);
+        let err = service
+            .fetch_open_issues("octocat", "hello")
+            .await
+            .unwrap_err();
+
+        mock.assert_hits(1);
+        assert!(err.to_string().contains("Not Found"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn connection_failures_are_retried() {
+        // Nothing listens on the port once the listener is dropped.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let service = service_with_retries(&base_url, 3);
+        let err = service
+            .fetch_recent_commits("octocat", "hello", None, None)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, AppError::Http(_)), "{err:?}");
+        assert_eq!(service.metrics().requests, 3);
+    }
+
+    #[tokio::test]
+    async fn not_modified_reuses_the_cached_value_without_reparsing() {
+        let server = MockServer::start_async().await;
+
+        let fresh = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/search/repositories");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .header("etag", "\"v1\"")
+                    .json_body(sample_response());
+            })
+            .await;
+        let first = service_with_base(&server.base_url());
+        let repos = first.fetch_top_repositories("Rust", 10).await.unwrap();
+        fresh.assert();
+        fresh.delete_async().await;
+
+        // The body would fail to parse if it were read.
+        let not_modified = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/search/repositories")
+                    .header("if-none-match", "\"v1\"");
+                then.status(304).body("not json");
+            })
+            .await;
+        let clone = first.clone();
+        let cached = clone.fetch_top_repositories("Rust", 10).await.unwrap();
+
+        not_modified.assert();
+        assert_eq!(cached, repos);
+        assert_eq!(first.metrics().cache_hits, 1);
+    }
+
+    #[tokio::test]
+    async fn clear_cache_drops_etags() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octocat/hello/issues");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .header("etag", "\"v1\"")
+                    .json_body(json!([]));
+            })
+            .await;
+        let conditional = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/repos/octocat/hello/issues")
+                    .header_exists("if-none-match");
+                then.status(304);
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        service.fetch_open_issues("octocat", "hello").await.unwrap();
+        service.clear_cache();
+        service.fetch_open_issues("octocat", "hello").await.unwrap();
+
+        mock.assert_hits(2);
+        conditional.assert_hits(0);
+    }
+
+    fn service_with_http2(base_url: &str, prior_knowledge: bool) -> GitService {
+        GitService::new(GitHubConfig {
+            api_base: base_url.to_string(),
+            http2_prior_knowledge: prior_knowledge,
+            http2_adaptive_window: true,
+            retry: RetryConfig {
+                max_attempts: 1,
+                ..RetryConfig::default()
+            },
+            ..GitHubConfig::default()
+        })
+        .expect("client should build with HTTP/2 options")
+    }
+
+    #[tokio::test]
+    async fn http2_options_build_a_working_client() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/search/repositories");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(sample_response());
+            })
+            .await;
+
+        let service = service_with_http2(&server.base_url(), false);
+        let repos = service.fetch_top_repositories("Rust", 10).await.unwrap();
+        assert_eq!(repos.len(), 1);
+        mock.assert();
+
+        // The mock server only speaks HTTP/1.1, so a client forced onto HTTP/2 can't
+        // talk to it; this shows the option reaches the client.
+        let forced = service_with_http2(&server.base_url(), true);
+        let err = forced.fetch_top_repositories("Rust", 10).await.unwrap_err();
+        assert!(matches!(err, AppError::Http(_)), "{err:?}");
+        mock.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn fetch_issues_with_all_state_returns_open_and_closed() {
+        let server = MockServer::start_async().await;
+        let issue = |id: i64, state: &str| {
+            json!({
+                "id": id,
+                "number": id,
+                "title": format!("issue {id}"),
+                "state": state,
+                "created_at": "2024-01-01T00:00:00Z",
+                "updated_at": "2024-01-02T00:00:00Z"
+            })
+        };
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/repos/octocat/hello/issues")
+                    .query_param("state", "all");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(json!([issue(1, "open"), issue(2, "closed")]));
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        let issues = service
+            .fetch_issues("octocat", "hello", IssueState::All)
+            .await
+            .unwrap();
+
+        mock.assert();
+        let states: Vec<&str> = issues.iter().map(|i| i.state.as_str()).collect();
+        assert_eq!(states, vec!["open", "closed"]);
+    }
+
+    #[tokio::test]
+    async fn fetch_tags_count_reads_last_page_from_link_header() {
+        let server = MockServer::start_async().await;
+        let last = format!(
+            "<{}/repositories/1/tags?per_page=1&page=42>; rel=\"last\"",
+            server.base_url()
+        );
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/repos/octocat/hello/tags")
+                    .query_param("per_page", "1");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .header("link", last.as_str())
+                    .json_body(json!([{ "name": "v1.0.0" }]));
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        let count = service.fetch_tags_count("octocat", "hello").await.unwrap();
+
+        mock.assert();
+        assert_eq!(count, 42);
+    }
+
+    #[tokio::test]
+    async fn listing_counts_without_link_header_use_the_single_page() {
+        let server = MockServer::start_async().await;
+
+        let tags = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octocat/hello/tags");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(json!([]));
+            })
+            .await;
+        let branches = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/repos/octocat/hello/branches")
+                    .query_param("per_page", "1");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(json!([{ "name": "main" }]));
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+
+        assert_eq!(
+            service.fetch_tags_count("octocat", "hello").await.unwrap(),
+            0
+        );
+        assert_eq!(
+            service
+                .fetch_branches_count("octocat", "hello")
+                .await
+                .unwrap(),
+            1
+        );
+        tags.assert();
+        branches.assert();
+    }
+
+    #[tokio::test]
+    async fn fetch_open_issues_drops_pull_requests() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octocat/hello/issues");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(json!([
+                        {
+                            "id": 1,
+                            "number": 1,
+                            "title": "Crash on start",
+                            "state": "open",
+                            "created_at": "2024-01-01T00:00:00Z",
+                            "updated_at": "2024-01-02T00:00:00Z"
+                        },
+                        {
+                            "id": 2,
+                            "number": 2,
+                            "title": "Fix crash on start",
+                            "state": "open",
+                            "created_at": "2024-01-03T00:00:00Z",
+                            "updated_at": "2024-01-03T00:00:00Z",
+                            "pull_request": {
+                                "url": "https://api.github.com/repos/octocat/hello/pulls/2"
+                            }
+                        }
+                    ]));
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        let issues = service.fetch_open_issues("octocat", "hello").await.unwrap();
+
+        mock.assert();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].title, "Crash on start");
+        assert!(!issues[0].is_pull_request);
+    }
+
+    #[tokio::test]
+    async fn fetch_recent_commits_passes_since_and_until() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/repos/octocat/hello/commits")
+                    .query_param("since", "2024-01-01T00:00:00Z")
+                    .query_param("until", "2024-02-01T00:00:00Z");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(commit_page(["c2", "c1"].map(String::from)));
+            })
+            .await;
+
+        let service = GitService::new(GitHubConfig {
+            api_base: server.base_url(),
+            ..GitHubConfig::default()
+        })
+        .unwrap();
+        let commits = service
+            .fetch_recent_commits(
+                "octocat",
+                "hello",
+                Some("2024-01-01T00:00:00Z"),
+                Some("2024-02-01T00:00:00Z"),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(commits.len(), 2);
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn recorded_search_response_replays_offline() {
+        let server = MockServer::start_async().await;
+        let dir = std::env::temp_dir().join(format!("ecs160-record-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/search/repositories");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(sample_response());
+            })
+            .await;
+
+        let recorder = GitService::new(GitHubConfig {
+            api_base: server.base_url(),
+            record_dir: Some(dir.clone()),
+            ..GitHubConfig::default()
+        })
+        .unwrap();
+        let recorded = recorder.fetch_top_repositories("Rust", 10).await.unwrap();
+        mock.assert();
+        mock.delete_async().await;
+
+        let replayer = GitService::new(GitHubConfig {
+            api_base: server.base_url(),
+            replay_dir: Some(dir.clone()),
+            ..GitHubConfig::default()
+        })
+        .unwrap();
+        let replayed = replayer.fetch_top_repositories("Rust", 10).await.unwrap();
+        let missing = replayer.fetch_top_repositories("Java", 10).await;
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            serde_json::to_value(&replayed).unwrap(),
+            serde_json::to_value(&recorded).unwrap()
+        );
+        assert_eq!(replayed[0].name, "repo-one");
+        assert_eq!(replayer.metrics().requests, 0);
+        assert!(matches!(missing, Err(AppError::Io(_))));
+    }
+
+    #[tokio::test]
+    async fn recorded_status_and_link_header_replay_offline() {
+        let server = MockServer::start_async().await;
+        let dir = std::env::temp_dir().join(format!("ecs160-record-link-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let last = format!(
+            "<{}/repositories/1/tags?per_page=1&page=42>; rel=\"last\"",
+            server.base_url()
+        );
+
+        let tags = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octocat/hello/tags");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .header("link", last.as_str())
+                    .json_body(json!([{ "name": "v1.0.0" }]));
+            })
+            .await;
+        let blocked = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octocat/blocked/tags");
+                then.status(451)
+                    .json_body(json!({ "message": "Repository access blocked" }));
+            })
+            .await;
+
+        let recorder = GitService::new(GitHubConfig {
+            api_base: server.base_url(),
+            record_dir: Some(dir.clone()),
+            ..GitHubConfig::default()
+        })
+        .unwrap();
+        assert_eq!(
+            recorder.fetch_tags_count("octocat", "hello").await.unwrap(),
+            42
+        );
+        assert!(recorder
+            .fetch_tags_count("octocat", "blocked")
+            .await
+            .is_err());
+        tags.assert();
+        blocked.assert();
+        tags.delete_async().await;
+        blocked.delete_async().await;
+
+        let replayer = GitService::new(GitHubConfig {
+            api_base: server.base_url(),
+            replay_dir: Some(dir.clone()),
+            ..GitHubConfig::default()
+        })
+        .unwrap();
+        let count = replayer.fetch_tags_count("octocat", "hello").await;
+        let unavailable = replayer.fetch_tags_count("octocat", "blocked").await;
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(count.unwrap(), 42);
+        assert!(matches!(
+            unavailable,
+            Err(AppError::RepositoryUnavailable { status: 451, .. })
+        ));
+        assert_eq!(replayer.metrics().requests, 0);
+    }
+
+    #[tokio::test]
+    async fn fetch_top_repositories_page_reports_total_and_next_page() {
+        let server = MockServer::start_async().await;
+        let mut body = sample_response();
+        body["total_count"] = json!(1234);
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/search/repositories")
+                    .query_param("q", "language:Rust");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .header(
+                        "link",
+                        r#"</search/repositories?q=language%3ARust&page=2>; rel="next""#,
+                    )
+                    .json_body(body);
+            })
+            .await;
+        let last = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/search/repositories")
+                    .query_param("q", "language:Java");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(sample_response());
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        let (repos, info) = service
+            .fetch_top_repositories_page("Rust", 10)
+            .await
+            .unwrap();
+        let (_, last_info) = service
+            .fetch_top_repositories_page("Java", 10)
+            .await
+            .unwrap();
+
+        assert_eq!(repos.len(), 1);
+        assert_eq!(
+            info,
+            PageInfo {
+                total: Some(1234),
+                has_next: true
+            }
+        );
+        assert_eq!(
+            last_info,
+            PageInfo {
+                total: Some(1),
+                has_next: false
+            }
+        );
+        mock.assert();
+        last.assert();
+    }
+
+    #[tokio::test]
+    async fn fetch_repo_forks_page_reports_next_page_without_total() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/repos/octocat/hello/forks");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .header("link", r#"</repos/octocat/hello/forks?page=2>; rel="next""#)
+                    .json_body(json!([]));
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        let (forks, info) = service
+            .fetch_repo_forks_page("octocat", "hello")
+            .await
+            .unwrap();
+
+        assert!(forks.is_empty());
+        assert_eq!(
+            info,
+            PageInfo {
+                total: None,
+                has_next: true
+            }
+        );
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn fetch_top_repositories_with_total_returns_total_count() {
+        let server = MockServer::start_async().await;
+        let mut body = sample_response();
+        body["total_count"] = json!(48213);
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/search/repositories");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(body);
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        let (repos, total) = service
+            .fetch_top_repositories_with_total("Rust", 10)
+            .await
+            .unwrap();
+
+        assert_eq!(repos.len(), 1);
+        assert_eq!(total, 48213);
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn fetch_repositories_sends_the_built_query() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/search/repositories")
+                    .query_param(
+                        "q",
+                        "language:Rust stars:>=1000 pushed:>2023-01-01 license:mit",
+                    )
+                    .query_param("sort", "stars");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(sample_response());
+            })
+            .await;
+
+        // Configured qualifiers only apply to fetch_top_repositories.
+        let service = GitService::new(GitHubConfig {
+            api_base: server.base_url(),
+            search_qualifiers: Some("-topic:awesome".to_string()),
+            ..GitHubConfig::default()
+        })
+        .unwrap();
+        let query = RepoSearchQuery::new()
+            .language("Rust")
+            .min_stars(1000)
+            .pushed_after("2023-01-01")
+            .license("mit");
+        let repos = service.fetch_repositories(&query, 10).await.unwrap();
+
+        assert_eq!(repos.len(), 1);
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn fetch_top_repositories_sorted_passes_sort_and_order() {
+        let server = MockServer::start_async().await;
+
+        let default_sort = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/search/repositories")
+                    .query_param("sort", "stars")
+                    .query_param("order", "desc");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(sample_response());
+            })
+            .await;
+        let updated = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/search/repositories")
+                    .query_param("sort", "updated")
+                    .query_param("order", "asc");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(sample_response());
+            })
+            .await;
+        let forks = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/search/repositories")
+                    .query_param("sort", "forks")
+                    .query_param("order", "desc");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body(sample_response());
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        service.fetch_top_repositories("Rust", 10).await.unwrap();
+        service
+            .fetch_top_repositories_sorted("Rust", 10, SortField::Updated, SortOrder::Asc)
+            .await
+            .unwrap();
+        service
+            .fetch_top_repositories_sorted("Rust", 10, SortField::Forks, SortOrder::Desc)
+            .await
+            .unwrap();
+
+        default_sort.assert();
+        updated.assert();
+        forks.assert();
+    }
+
+    #[tokio::test]
+    async fn listing_counts_with_a_malformed_link_header_fail() {
+        let server = MockServer::start_async().await;
+
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/repos/octocat/hello/branches")
+                    .query_param("per_page", "1");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .header("link", "garbage; rel=\"last\"")
+                    .json_body(json!([{ "name": "main" }]));
+            })
+            .await;
+
+        let service = service_with_base(&server.base_url());
+        let err = service
+            .fetch_branches_count("octocat", "hello")
+            .await
+            .expect_err("a malformed Link header should not be counted as one item");
+
+        mock.assert();
+        assert!(matches!(err, AppError::MalformedLinkHeader(_)));
+    }
 }
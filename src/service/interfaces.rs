@@ -15,7 +15,13 @@ pub trait RepoFetcher {
 
 #[allow(async_fn_in_trait)]
 pub trait CommitFetcher {
-    async fn fetch_recent_commits(&self, owner: &str, repo: &str) -> Result<Vec<Commit>, AppError>;
+    async fn fetch_recent_commits(
+        &self,
+        owner: &str,
+        repo: &str,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> Result<Vec<Commit>, AppError>;
     async fn fetch_commit_with_files(
         &self,
         owner: &str,
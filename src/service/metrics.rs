@@ -0,0 +1,43 @@
+//! Request and cache counters shared across service clones.
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Counters shared (behind an `Arc`) by every clone of a `GitService`.
+#[derive(Debug, Default)]
+pub struct ServiceMetrics {
+    requests: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+}
+
+/// Point-in-time copy of [`ServiceMetrics`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    /// Requests actually sent to GitHub.
+    pub requests: u64,
+    /// Lookups answered from a cache without a full response from GitHub.
+    pub cache_hits: u64,
+    /// Cacheable lookups that had to be fetched.
+    pub cache_misses: u64,
+}
+
+impl ServiceMetrics {
+    pub fn record_request(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_cache_hit(&self) {
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_cache_miss(&self) {
+        self.cache_misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            requests: self.requests.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            cache_misses: self.cache_misses.load(Ordering::Relaxed),
+        }
+    }
+}
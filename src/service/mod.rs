@@ -1,11 +1,21 @@
 //! Service layer.
+pub mod bounded;
+pub mod etag_cache;
 pub mod git_service;
 pub mod interfaces;
+pub mod metrics;
+pub mod page_info;
+pub mod rate_limit;
+pub mod recording;
 pub mod redis_service;
+pub mod search_query;
 pub mod test_services;
 pub mod traits;
 
+pub use bounded::BoundedGitService;
 pub use git_service::GitService;
+pub use page_info::PageInfo;
 pub use redis_service::RedisService;
+pub use search_query::{RepoSearchQuery, SortField, SortOrder};
 pub use test_services::{TestGitService, TestStorageService};
 pub use traits::*;
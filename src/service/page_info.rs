@@ -0,0 +1,11 @@
+//! Pagination metadata for list endpoints.
+
+/// Where one fetched page sits in the full listing, e.g. to show "1-10 of 1,234".
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PageInfo {
+    /// Total number of matching items, for endpoints that report one (search's
+    /// `total_count`).
+    pub total: Option<u64>,
+    /// The `Link` header had a `rel="next"` page.
+    pub has_next: bool,
+}
@@ -0,0 +1,134 @@
+//! GitHub rate-limit tracking shared across service clones.
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::HeaderMap;
+
+use crate::config::RateLimitBehavior;
+use crate::errln;
+use crate::error::AppError;
+
+/// Last rate-limit window GitHub reported via `x-ratelimit-*` headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Window {
+    remaining: u64,
+    reset_at: SystemTime,
+}
+
+/// Rate-limit state shared (behind an `Arc`) by every clone of a `GitService`, so
+/// concurrently processed languages all pause once any of them sees the quota run out.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    window: Mutex<Option<Window>>,
+}
+
+impl RateLimiter {
+    /// Records the quota reported on a response; responses without the headers are ignored.
+    pub fn record(&self, headers: &HeaderMap) {
+        let header = |name: &str| -> Option<u64> { headers.get(name)?.to_str().ok()?.parse().ok() };
+        let (Some(remaining), Some(reset)) =
+            (header("x-ratelimit-remaining"), header("x-ratelimit-reset"))
+        else {
+            return;
+        };
+
+        let window = Window {
+            remaining,
+            reset_at: UNIX_EPOCH + Duration::from_secs(reset),
+        };
+        *self.window.lock().expect("rate limit lock poisoned") = Some(window);
+    }
+
+    /// How long to wait before the next request, if the quota is exhausted.
+    pub fn delay(&self) -> Option<Duration> {
+        let window = (*self.window.lock().expect("rate limit lock poisoned"))?;
+        if window.remaining > 0 {
+            return None;
+        }
+        window.reset_at.duration_since(SystemTime::now()).ok()
+    }
+
+    /// Lets a request through: waits for an exhausted window to reset, or fails with
+    /// `RateLimited` when `behavior` is `Abort` or the reset is more than `max_wait` away.
+    pub async fn acquire(
+        &self,
+        behavior: RateLimitBehavior,
+        max_wait: Duration,
+    ) -> Result<(), AppError> {
+        match behavior {
+            RateLimitBehavior::Wait => self.wait(max_wait).await,
+            RateLimitBehavior::Abort => match self.delay() {
+                Some(delay) => Err(AppError::RateLimited {
+                    reset_in_secs: delay.as_secs(),
+                }),
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// Sleeps until the rate-limit window resets when no requests remain. A reset more
+    /// than `max_wait` away fails with `RateLimited` right away: a request sent before it
+    /// would only hit the same exhausted quota.
+    pub async fn wait(&self, max_wait: Duration) -> Result<(), AppError> {
+        let Some(delay) = self.delay() else {
+            return Ok(());
+        };
+        if delay > max_wait {
+            errln!(
+                "      ⏳ GitHub rate limit exhausted, resets in {}s, more than the maximum wait of {}s",
+                delay.as_secs(),
+                max_wait.as_secs()
+            );
+            return Err(AppError::RateLimited {
+                reset_in_secs: delay.as_secs(),
+            });
+        }
+        errln!(
+            "      ⏳ GitHub rate limit exhausted, waiting {}s for reset",
+            delay.as_secs()
+        );
+        tokio::time::sleep(delay).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn exhausted_for(secs: u64) -> RateLimiter {
+        let reset = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + secs;
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+        headers.insert("x-ratelimit-reset", HeaderValue::from(reset));
+        let limiter = RateLimiter::default();
+        limiter.record(&headers);
+        limiter
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wait_sleeps_until_the_window_resets() {
+        let limiter = exhausted_for(30);
+
+        let started = tokio::time::Instant::now();
+        limiter.wait(Duration::from_secs(60)).await.unwrap();
+
+        assert!(started.elapsed() >= Duration::from_secs(29));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wait_fails_without_sleeping_past_max_wait() {
+        let limiter = exhausted_for(600);
+
+        let started = tokio::time::Instant::now();
+        let err = limiter.wait(Duration::from_secs(60)).await.unwrap_err();
+
+        assert!(matches!(err, AppError::RateLimited { .. }));
+        assert_eq!(started.elapsed(), Duration::ZERO);
+    }
+}
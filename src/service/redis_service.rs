@@ -1,56 +1,124 @@
 //! Redis storage.
 use redis::aio::ConnectionManager;
-use redis::AsyncCommands;
+use redis::{AsyncCommands, ConnectionInfo, IntoConnectionInfo};
 
 use crate::config::RedisConfig;
 use crate::error::AppError;
-use crate::model::{Issue, Owner, Repo};
+use crate::model::{CodeAnalysis, Issue, Owner, Repo};
 use crate::service::traits::DataStorageService;
 
+/// Version of the hash layout written by this service. Bump it when fields are renamed
+/// or removed so readers can detect data written by an incompatible version.
+pub const SCHEMA_VERSION: u32 = 1;
+
 #[derive(Clone)]
 pub struct RedisService {
     client: ConnectionManager,
+    store_issue_bodies: bool,
 }
 
 impl RedisService {
     pub async fn new(config: RedisConfig) -> Result<Self, AppError> {
-        let redis_client = redis::Client::open(config.url.as_str())
+        let redis_client = redis::Client::open(Self::connection_info(&config)?)
             .map_err(|e| AppError::Redis(format!("Failed to create Redis client: {e}")))?;
 
         let client = ConnectionManager::new(redis_client)
             .await
             .map_err(|e| AppError::Redis(format!("Failed to create connection manager: {e}")))?;
 
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            store_issue_bodies: config.store_issue_bodies,
+        })
     }
 
-    pub async fn store_repository(&mut self, repo: &Repo) -> Result<(), AppError> {
-        let repo_key = format!("repo:{}:{}", repo.owner.login, repo.name);
+    /// Connection settings from `config.url`, with `db`, `username` and `password`
+    /// replacing the URL's values when set.
+    pub fn connection_info(config: &RedisConfig) -> Result<ConnectionInfo, AppError> {
+        let mut info = config
+            .url
+            .as_str()
+            .into_connection_info()
+            .map_err(|e| AppError::Redis(format!("Invalid Redis URL: {e}")))?;
+        if let Some(db) = config.db {
+            info.redis.db = db;
+        }
+        if let Some(username) = &config.username {
+            info.redis.username = Some(username.clone());
+        }
+        if let Some(password) = &config.password {
+            info.redis.password = Some(password.clone());
+        }
+        Ok(info)
+    }
+
+    pub fn repo_key(repo: &Repo) -> String {
+        format!("repo:{}:{}", repo.owner.login, repo.name)
+    }
 
+    pub fn author_key(owner: &Owner) -> String {
+        format!("author:{}", owner.login)
+    }
+
+    pub fn issue_key(issue: &Issue) -> String {
+        format!("iss-{}", issue.id)
+    }
+
+    /// Key of the hash describing which crate and schema version wrote the data
+    pub fn meta_key() -> String {
+        format!("{}:meta", env!("CARGO_PKG_NAME"))
+    }
+
+    /// Fields written to [`Self::meta_key`]
+    pub fn meta_fields() -> Vec<(&'static str, String)> {
+        vec![
+            ("crate_version", env!("CARGO_PKG_VERSION").to_string()),
+            ("schema_version", SCHEMA_VERSION.to_string()),
+        ]
+    }
+
+    /// Lists the keys `store_repository` would write for `repo`, without touching Redis.
+    /// Useful for dry runs; commits are not stored, so only repo, author, issue and
+    /// meta keys appear.
+    pub fn plan_keys(repo: &Repo) -> Vec<String> {
+        let mut keys = vec![Self::repo_key(repo), Self::author_key(&repo.owner)];
+        keys.extend(repo.issues.iter().map(Self::issue_key));
+        keys.push(Self::meta_key());
+        keys
+    }
+
+    /// Hash fields written for a repository, including its `_schema_version`
+    pub fn repo_fields(repo: &Repo) -> Vec<(&'static str, String)> {
         // Create comma-separated list of issue IDs
         let issues_list = repo
             .issues
             .iter()
-            .map(|i| format!("iss-{}", i.id))
+            .map(Self::issue_key)
             .collect::<Vec<_>>()
             .join(",");
 
+        let data = repo.to_repo_data();
+
+        vec![
+            ("url", data.url.clone()),
+            ("Url", data.url), // Capitalized as requested
+            ("name", data.name),
+            ("owner", data.owner),
+            ("language", data.language),
+            ("stars", data.stars.to_string()),
+            ("forks", data.forks.to_string()),
+            ("open_issues", data.open_issues.to_string()),
+            ("full_name", repo.full_name.clone()),
+            ("Issues", issues_list), // Capitalized as requested
+            ("_schema_version", SCHEMA_VERSION.to_string()),
+        ]
+    }
+
+    pub async fn store_repository(&mut self, repo: &Repo) -> Result<(), AppError> {
+        let repo_key = Self::repo_key(repo);
+
         self.client
-            .hset_multiple::<_, _, _, ()>(
-                &repo_key,
-                &[
-                    ("url", repo.html_url.as_str()),
-                    ("Url", repo.html_url.as_str()), // Capitalized as requested
-                    ("name", repo.name.as_str()),
-                    ("owner", repo.owner.login.as_str()),
-                    ("language", &repo.language.as_deref().unwrap_or("unknown")),
-                    ("stars", &repo.stargazers_count.to_string()),
-                    ("forks", &repo.forks_count.to_string()),
-                    ("open_issues", &repo.open_issues_count.to_string()),
-                    ("full_name", repo.full_name.as_str()),
-                    ("Issues", &issues_list), // Capitalized as requested
-                ],
-            )
+            .hset_multiple::<_, _, _, ()>(&repo_key, &Self::repo_fields(repo))
             .await
             .map_err(|e| AppError::Redis(format!("Failed to store repo: {e}")))?;
 
@@ -60,11 +128,48 @@ impl RedisService {
             self.store_issue(issue).await?;
         }
 
+        self.client
+            .hset_multiple::<_, _, _, ()>(Self::meta_key(), &Self::meta_fields())
+            .await
+            .map_err(|e| AppError::Redis(format!("Failed to store schema metadata: {e}")))?;
+
+        Ok(())
+    }
+
+    /// Adds the clone-phase source analysis to an already stored repository hash
+    pub async fn store_code_analysis(
+        &mut self,
+        repo: &Repo,
+        analysis: &CodeAnalysis,
+    ) -> Result<(), AppError> {
+        let repo_key = Self::repo_key(repo);
+
+        self.client
+            .hset_multiple::<_, _, _, ()>(
+                &repo_key,
+                &[
+                    ("source_files", analysis.source_files.to_string()),
+                    ("total_files", analysis.total_files.to_string()),
+                    ("source_ratio", format!("{:.4}", analysis.source_ratio)),
+                    (
+                        "is_source_code_repo",
+                        analysis.is_source_code_repo.to_string(),
+                    ),
+                    ("is_monorepo", analysis.is_monorepo.to_string()),
+                    (
+                        "file_extensions",
+                        serde_json::to_string(&analysis.file_extensions)?,
+                    ),
+                ],
+            )
+            .await
+            .map_err(|e| AppError::Redis(format!("Failed to store code analysis: {e}")))?;
+
         Ok(())
     }
 
     async fn store_owner(&mut self, owner: &Owner) -> Result<(), AppError> {
-        let key = format!("author:{}", owner.login);
+        let key = Self::author_key(owner);
 
         self.client
             .hset_multiple::<_, _, _, ()>(
@@ -74,6 +179,7 @@ impl RedisService {
                     ("id", &owner.id.to_string()),
                     ("url", owner.html_url.as_str()),
                     ("site_admin", &owner.site_admin.to_string()),
+                    ("avatar_url", owner.avatar_url.as_deref().unwrap_or("")),
                 ],
             )
             .await
@@ -82,32 +188,46 @@ impl RedisService {
         Ok(())
     }
 
+    /// Hash fields written for an issue. With `include_bodies` false the body and its
+    /// `Description`/`description` copies are left out.
+    pub fn issue_fields(issue: &Issue, include_bodies: bool) -> Vec<(&'static str, String)> {
+        let mut fields = vec![
+            ("issueId", Self::issue_key(issue)), // Added issueId
+            ("title", issue.title.clone()),
+        ];
+
+        if include_bodies {
+            let body = issue.body.clone().unwrap_or_default();
+            fields.push(("body", body.clone()));
+            fields.push(("Description", body.clone()));
+            fields.push(("description", body)); // Added description (lowercase)
+        }
+
+        fields.extend([
+            ("state", issue.state.clone()),
+            ("url", issue.html_url.clone().unwrap_or_default()),
+            ("created_at", issue.created_at.clone()),
+            ("Date", issue.created_at.clone()),
+            ("updated_at", issue.updated_at.clone()),
+            ("comments", issue.comments.to_string()),
+            ("bug_type", "BUG".to_string()), // Added bug_type
+            ("filename", "unknown".to_string()), // Added filename
+            ("line", "0".to_string()), // Added line
+        ]);
+
+        fields
+    }
+
     /// Stores a single issue in Redis
     async fn store_issue(
         &mut self,
         issue: &Issue,
     ) -> Result<(), AppError> {
-        let key = format!("iss-{}", issue.id);
+        let key = Self::issue_key(issue);
+        let fields = Self::issue_fields(issue, self.store_issue_bodies);
 
         self.client
-            .hset_multiple::<_, _, _, ()>(
-                &key,
-                &[
-                    ("issueId", key.as_str()), // Added issueId
-                    ("title", issue.title.as_str()),
-                    ("body", issue.body.as_deref().unwrap_or("")),
-                    ("Description", issue.body.as_deref().unwrap_or("")),
-                    ("description", issue.body.as_deref().unwrap_or("")), // Added description (lowercase)
-                    ("state", issue.state.as_str()),
-                    ("url", issue.html_url.as_deref().unwrap_or("")),
-                    ("created_at", issue.created_at.as_str()),
-                    ("Date", issue.created_at.as_str()),
-                    ("updated_at", issue.updated_at.as_str()),
-                    ("bug_type", "BUG"), // Added bug_type
-                    ("filename", "unknown"), // Added filename
-                    ("line", "0"), // Added line
-                ],
-            )
+            .hset_multiple::<_, _, _, ()>(&key, &fields)
             .await
             .map_err(|e| AppError::Redis(format!("Failed to store issue: {e}")))?;
 
@@ -119,4 +239,12 @@ impl DataStorageService for RedisService {
     async fn store_repository(&mut self, repo: &Repo) -> Result<(), AppError> {
         self.store_repository(repo).await
     }
+
+    async fn store_code_analysis(
+        &mut self,
+        repo: &Repo,
+        analysis: &CodeAnalysis,
+    ) -> Result<(), AppError> {
+        self.store_code_analysis(repo, analysis).await
+    }
 }
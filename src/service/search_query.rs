@@ -0,0 +1,167 @@
+//! Repository search queries.
+
+/// What repository search results are ranked by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortField {
+    #[default]
+    Stars,
+    Forks,
+    /// Most recently updated.
+    Updated,
+}
+
+impl SortField {
+    /// Value of GitHub's `sort` query parameter for this field.
+    pub fn as_query_value(&self) -> &'static str {
+        match self {
+            SortField::Stars => "stars",
+            SortField::Forks => "forks",
+            SortField::Updated => "updated",
+        }
+    }
+}
+
+/// Direction of the search ranking.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    /// Value of GitHub's `order` query parameter for this direction.
+    pub fn as_query_value(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+/// The `q` value of a GitHub repository search, built from qualifiers, plus how the
+/// results are ranked (most stars first unless [`Self::sort_by`] says otherwise).
+///
+/// ```
+/// use ecs160_hw1::service::RepoSearchQuery;
+///
+/// let query = RepoSearchQuery::new()
+///     .language("Rust")
+///     .min_stars(1000)
+///     .pushed_after("2023-01-01");
+/// assert_eq!(query.to_query_string(), "language:Rust stars:>=1000 pushed:>2023-01-01");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoSearchQuery {
+    pub language: Option<String>,
+    pub min_stars: Option<u64>,
+    /// Only repositories pushed to after this date.
+    pub pushed_after: Option<String>,
+    /// License keyword, e.g. `mit` or `apache-2.0`.
+    pub license: Option<String>,
+    /// Only repositories created on or after this date.
+    pub created_after: Option<String>,
+    /// Only repositories created on or before this date.
+    pub created_before: Option<String>,
+    /// Raw qualifiers appended as given, e.g. `-topic:awesome`.
+    pub qualifiers: Vec<String>,
+    pub sort: SortField,
+    pub order: SortOrder,
+}
+
+impl RepoSearchQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn language(mut self, language: &str) -> Self {
+        self.language = Some(language.to_string());
+        self
+    }
+
+    pub fn min_stars(mut self, stars: u64) -> Self {
+        self.min_stars = Some(stars);
+        self
+    }
+
+    pub fn pushed_after(mut self, date: &str) -> Self {
+        self.pushed_after = Some(date.to_string());
+        self
+    }
+
+    pub fn license(mut self, license: &str) -> Self {
+        self.license = Some(license.to_string());
+        self
+    }
+
+    pub fn created_after(mut self, date: &str) -> Self {
+        self.created_after = Some(date.to_string());
+        self
+    }
+
+    pub fn created_before(mut self, date: &str) -> Self {
+        self.created_before = Some(date.to_string());
+        self
+    }
+
+    pub fn qualifier(mut self, qualifier: &str) -> Self {
+        self.qualifiers.push(qualifier.to_string());
+        self
+    }
+
+    pub fn sort_by(mut self, sort: SortField, order: SortOrder) -> Self {
+        self.sort = sort;
+        self.order = order;
+        self
+    }
+
+    /// The qualifiers joined by spaces, in field order.
+    pub fn to_query_string(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(language) = &self.language {
+            parts.push(format!("language:{language}"));
+        }
+        if let Some(stars) = self.min_stars {
+            parts.push(format!("stars:>={stars}"));
+        }
+        if let Some(date) = &self.pushed_after {
+            parts.push(format!("pushed:>{date}"));
+        }
+        if let Some(license) = &self.license {
+            parts.push(format!("license:{license}"));
+        }
+        if let Some(date) = &self.created_after {
+            parts.push(format!("created:>={date}"));
+        }
+        if let Some(date) = &self.created_before {
+            parts.push(format!("created:<={date}"));
+        }
+        parts.extend(self.qualifiers.iter().cloned());
+        parts.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn qualifiers_are_joined_in_field_order() {
+        let query = RepoSearchQuery::new()
+            .qualifier("-topic:awesome")
+            .license("mit")
+            .created_before("2024-12-31")
+            .language("Go")
+            .min_stars(50);
+
+        assert_eq!(
+            query.to_query_string(),
+            "language:Go stars:>=50 license:mit created:<=2024-12-31 -topic:awesome"
+        );
+    }
+
+    #[test]
+    fn empty_query_is_empty() {
+        assert_eq!(RepoSearchQuery::new().to_query_string(), "");
+    }
+}
@@ -1,9 +1,10 @@
 //! Test services.
 
 use crate::error::AppError;
-use crate::model::{Commit, Issue, Repo};
+use crate::model::{CodeAnalysis, Commit, CommitFile, Issue, Repo};
 use crate::service::traits::{DataStorageService, GitRepositoryService};
 
+#[derive(Default)]
 pub struct TestGitService {
     pub repos: Vec<Repo>,
     pub commits: Vec<Commit>,
@@ -12,11 +13,7 @@ pub struct TestGitService {
 
 impl TestGitService {
     pub fn new() -> Self {
-        Self {
-            repos: Vec::new(),
-            commits: Vec::new(),
-            issues: Vec::new(),
-        }
+        Self::default()
     }
 }
 
@@ -33,10 +30,27 @@ impl GitRepositoryService for TestGitService {
         Ok(Vec::new())
     }
 
+    async fn fetch_repo_details(&self, owner: &str, repo: &str) -> Result<Repo, AppError> {
+        self.repos
+            .iter()
+            .find(|r| r.owner.login == owner && r.name == repo)
+            .cloned()
+            .ok_or_else(|| AppError::GitHubApi {
+                status: 404,
+                message: format!("404 Not Found from /repos/{owner}/{repo}"),
+            })
+    }
+
+    async fn fetch_user_repositories(&self, _login: &str) -> Result<Vec<Repo>, AppError> {
+        Ok(self.repos.clone())
+    }
+
     async fn fetch_recent_commits(
         &self,
         _owner: &str,
         _repo: &str,
+        _since: Option<&str>,
+        _until: Option<&str>,
     ) -> Result<Vec<Commit>, AppError> {
         Ok(self.commits.clone())
     }
@@ -56,17 +70,57 @@ impl GitRepositoryService for TestGitService {
             .cloned()
             .ok_or_else(|| AppError::Config("No commits available".to_string()))
     }
+
+    async fn fetch_compare(
+        &self,
+        _owner: &str,
+        _repo: &str,
+        _base: &str,
+        _head: &str,
+    ) -> Result<Vec<CommitFile>, AppError> {
+        Ok(self
+            .commits
+            .iter()
+            .flat_map(|c| c.files.iter().cloned())
+            .collect())
+    }
+
+    async fn fetch_fork_ahead_by(
+        &self,
+        _parent_owner: &str,
+        _parent_repo: &str,
+        _fork_owner: &str,
+        _branch: &str,
+    ) -> Result<u64, AppError> {
+        Ok(self.commits.len() as u64)
+    }
+
+    async fn fetch_collaborators_count(
+        &self,
+        _owner: &str,
+        _repo: &str,
+    ) -> Result<Option<u64>, AppError> {
+        Ok(None)
+    }
+
+    async fn fetch_tags_count(&self, _owner: &str, _repo: &str) -> Result<u64, AppError> {
+        Ok(0)
+    }
+
+    async fn fetch_branches_count(&self, _owner: &str, _repo: &str) -> Result<u64, AppError> {
+        Ok(1)
+    }
 }
 
+#[derive(Default)]
 pub struct TestStorageService {
     pub stored_repos: std::collections::HashMap<String, ()>,
+    pub stored_analyses: std::collections::HashMap<String, CodeAnalysis>,
 }
 
 impl TestStorageService {
     pub fn new() -> Self {
-        Self {
-            stored_repos: std::collections::HashMap::new(),
-        }
+        Self::default()
     }
 }
 
@@ -76,4 +130,14 @@ impl DataStorageService for TestStorageService {
         self.stored_repos.insert(key, ());
         Ok(())
     }
+
+    async fn store_code_analysis(
+        &mut self,
+        repo: &Repo,
+        analysis: &CodeAnalysis,
+    ) -> Result<(), AppError> {
+        let key = format!("{}:{}", repo.owner.login, repo.name);
+        self.stored_analyses.insert(key, analysis.clone());
+        Ok(())
+    }
 }
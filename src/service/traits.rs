@@ -1,7 +1,7 @@
 //! Service traits.
 
 use crate::error::AppError;
-use crate::model::{Commit, Issue, Repo};
+use crate::model::{CodeAnalysis, Commit, CommitFile, Issue, Repo};
 
 /// Allows extension to different Git providers (GitHub, GitLab, etc.)
 #[allow(async_fn_in_trait)]
@@ -12,7 +12,18 @@ pub trait GitRepositoryService {
         per_page: u8,
     ) -> Result<Vec<Repo>, AppError>;
     async fn fetch_repo_forks(&self, owner: &str, repo: &str) -> Result<Vec<Repo>, AppError>;
-    async fn fetch_recent_commits(&self, owner: &str, repo: &str) -> Result<Vec<Commit>, AppError>;
+    /// The full repository object, including fields search results leave out.
+    async fn fetch_repo_details(&self, owner: &str, repo: &str) -> Result<Repo, AppError>;
+    async fn fetch_user_repositories(&self, login: &str) -> Result<Vec<Repo>, AppError>;
+    /// `since` and `until` are ISO-8601 timestamps bounding the commit dates; `None`
+    /// leaves that side open.
+    async fn fetch_recent_commits(
+        &self,
+        owner: &str,
+        repo: &str,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> Result<Vec<Commit>, AppError>;
     async fn fetch_open_issues(&self, owner: &str, repo: &str) -> Result<Vec<Issue>, AppError>;
     async fn fetch_commit_with_files(
         &self,
@@ -20,11 +31,40 @@ pub trait GitRepositoryService {
         repo: &str,
         sha: &str,
     ) -> Result<Commit, AppError>;
+    /// Aggregate file changes from `base` (exclusive) to `head`.
+    async fn fetch_compare(
+        &self,
+        owner: &str,
+        repo: &str,
+        base: &str,
+        head: &str,
+    ) -> Result<Vec<CommitFile>, AppError>;
+    /// Number of commits `fork_owner:branch` has on top of the parent's `branch`.
+    async fn fetch_fork_ahead_by(
+        &self,
+        parent_owner: &str,
+        parent_repo: &str,
+        fork_owner: &str,
+        branch: &str,
+    ) -> Result<u64, AppError>;
+    /// Number of collaborators, or `None` when the token isn't allowed to list them.
+    async fn fetch_collaborators_count(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Option<u64>, AppError>;
+    async fn fetch_tags_count(&self, owner: &str, repo: &str) -> Result<u64, AppError>;
+    async fn fetch_branches_count(&self, owner: &str, repo: &str) -> Result<u64, AppError>;
 }
 
 #[allow(async_fn_in_trait)]
 pub trait DataStorageService {
     async fn store_repository(&mut self, repo: &Repo) -> Result<(), AppError>;
+    async fn store_code_analysis(
+        &mut self,
+        repo: &Repo,
+        analysis: &CodeAnalysis,
+    ) -> Result<(), AppError>;
 }
 
 #[derive(Debug, Clone)]
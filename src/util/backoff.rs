@@ -0,0 +1,136 @@
+//! Retry backoff helpers.
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use crate::error::AppError;
+
+/// How random jitter is applied to an exponential backoff delay.
+///
+/// `Full` and `Equal` follow the AWS "Exponential Backoff and Jitter" formulas:
+/// full jitter sleeps `random(0, delay)`, equal jitter sleeps `delay / 2 + random(0, delay / 2)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JitterStrategy {
+    None,
+    #[default]
+    Full,
+    Equal,
+}
+
+impl std::str::FromStr for JitterStrategy {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "full" => Ok(Self::Full),
+            "equal" => Ok(Self::Equal),
+            other => Err(AppError::Config(format!(
+                "unknown RETRY_JITTER value: {other}"
+            ))),
+        }
+    }
+}
+
+impl JitterStrategy {
+    /// Applies the strategy to `delay`, where `unit` is a random sample in `[0, 1)`.
+    pub fn apply(self, delay: Duration, unit: f64) -> Duration {
+        let unit = unit.clamp(0.0, 1.0);
+        match self {
+            JitterStrategy::None => delay,
+            JitterStrategy::Full => delay.mul_f64(unit),
+            JitterStrategy::Equal => {
+                let half = delay / 2;
+                half + half.mul_f64(unit)
+            }
+        }
+    }
+}
+
+/// Exponential delay `base * 2^attempt`, capped at `max`, before jitter is applied.
+pub fn exponential_delay(base: Duration, max: Duration, attempt: u32) -> Duration {
+    base.checked_mul(2u32.saturating_pow(attempt))
+        .unwrap_or(max)
+        .min(max)
+}
+
+/// Backoff delay for a zero-based retry `attempt` with jitter applied.
+pub fn backoff_delay(
+    base: Duration,
+    max: Duration,
+    attempt: u32,
+    strategy: JitterStrategy,
+) -> Duration {
+    strategy.apply(exponential_delay(base, max, attempt), random_unit())
+}
+
+/// Cheap random sample in `[0, 1)` seeded from the standard library's hasher keys.
+fn random_unit() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: Duration = Duration::from_millis(100);
+    const MAX: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn exponential_delay_doubles_and_caps() {
+        assert_eq!(exponential_delay(BASE, MAX, 0), Duration::from_millis(100));
+        assert_eq!(exponential_delay(BASE, MAX, 3), Duration::from_millis(800));
+        assert_eq!(exponential_delay(BASE, MAX, 10), MAX);
+        assert_eq!(exponential_delay(BASE, MAX, 64), MAX);
+    }
+
+    #[test]
+    fn no_jitter_returns_exponential_delay() {
+        for attempt in 0..8 {
+            assert_eq!(
+                backoff_delay(BASE, MAX, attempt, JitterStrategy::None),
+                exponential_delay(BASE, MAX, attempt)
+            );
+        }
+    }
+
+    #[test]
+    fn full_jitter_stays_between_zero_and_delay() {
+        assert_eq!(JitterStrategy::Full.apply(MAX, 0.0), Duration::ZERO);
+        for attempt in 0..8 {
+            let ceiling = exponential_delay(BASE, MAX, attempt);
+            for _ in 0..50 {
+                let delay = backoff_delay(BASE, MAX, attempt, JitterStrategy::Full);
+                assert!(delay <= ceiling, "{delay:?} > {ceiling:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn equal_jitter_stays_between_half_and_full_delay() {
+        assert_eq!(JitterStrategy::Equal.apply(MAX, 0.0), MAX / 2);
+        for attempt in 0..8 {
+            let ceiling = exponential_delay(BASE, MAX, attempt);
+            for _ in 0..50 {
+                let delay = backoff_delay(BASE, MAX, attempt, JitterStrategy::Equal);
+                assert!(delay >= ceiling / 2, "{delay:?} < half of {ceiling:?}");
+                assert!(delay <= ceiling, "{delay:?} > {ceiling:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn random_unit_is_in_range() {
+        for _ in 0..100 {
+            let unit = random_unit();
+            assert!((0.0..1.0).contains(&unit));
+        }
+    }
+}
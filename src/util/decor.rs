@@ -0,0 +1,186 @@
+//! Console decoration (✓/✗/⚠ markers and `=====` banners) that can be switched to
+//! plain ASCII for logs and CI.
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+static PLAIN: AtomicBool = AtomicBool::new(false);
+static STDOUT_RESERVED: AtomicBool = AtomicBool::new(false);
+static CAPTURE: Mutex<Option<CapturedOutput>> = Mutex::new(None);
+
+/// Markers used in console output and their ASCII replacements.
+const MARKERS: &[(char, &str)] = &[
+    ('✓', "[OK]"),
+    ('✗', "[FAIL]"),
+    ('⚠', "[WARN]"),
+    ('⏳', "[WAIT]"),
+    ('…', "..."),
+    ('–', "-"),
+];
+
+/// Switches all output written through [`outln!`](crate::outln) and friends to plain ASCII.
+pub fn set_plain(plain: bool) {
+    PLAIN.store(plain, Ordering::Relaxed);
+}
+
+pub fn is_plain() -> bool {
+    PLAIN.load(Ordering::Relaxed)
+}
+
+/// Sends everything written through [`out!`](crate::out) and [`outln!`](crate::outln) to
+/// stderr, so stdout carries only machine-readable output written with [`write_data`].
+pub fn set_stdout_reserved(reserved: bool) {
+    STDOUT_RESERVED.store(reserved, Ordering::Relaxed);
+}
+
+/// Writes machine-readable output (e.g. Prometheus gauges) to stdout as is; it is never
+/// redirected or rewritten for plain output.
+pub fn write_data(text: &str) {
+    emit(text, false);
+}
+
+/// Output collected between [`capture_start`] and [`capture_finish`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CapturedOutput {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Collects everything written through this module instead of printing it, until
+/// [`capture_finish`]. Meant for tests that check the output of a whole run.
+pub fn capture_start() {
+    *CAPTURE.lock().unwrap_or_else(|e| e.into_inner()) = Some(CapturedOutput::default());
+}
+
+/// Stops collecting and returns what was written since [`capture_start`].
+pub fn capture_finish() -> CapturedOutput {
+    CAPTURE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+        .unwrap_or_default()
+}
+
+fn emit(text: &str, to_stderr: bool) {
+    if let Some(captured) = CAPTURE.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        let stream = if to_stderr {
+            &mut captured.stderr
+        } else {
+            &mut captured.stdout
+        };
+        stream.push_str(text);
+        return;
+    }
+    if to_stderr {
+        eprint!("{text}");
+    } else {
+        print!("{text}");
+    }
+}
+
+/// Returns `text` unchanged, or with `plain` its markers replaced by ASCII, any other
+/// non-ASCII character by `?`, and lines made only of `=` (section banners) dropped.
+pub fn render(text: &str, plain: bool) -> Cow<'_, str> {
+    if !plain || (text.is_ascii() && !text.contains("==")) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut out = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end();
+        if !content.is_empty() && content.chars().all(|c| c == '=') {
+            continue;
+        }
+        for c in line.chars() {
+            match MARKERS.iter().find(|(marker, _)| *marker == c) {
+                Some((_, ascii)) => out.push_str(ascii),
+                None if c.is_ascii() => out.push(c),
+                None => out.push('?'),
+            }
+        }
+    }
+    Cow::Owned(out)
+}
+
+#[doc(hidden)]
+pub fn write_stdout(text: &str) {
+    emit(
+        &render(text, is_plain()),
+        STDOUT_RESERVED.load(Ordering::Relaxed),
+    );
+}
+
+#[doc(hidden)]
+pub fn write_stderr(text: &str) {
+    emit(&render(text, is_plain()), true);
+}
+
+/// `print!` that honours plain output and [`set_stdout_reserved`](crate::util::decor::set_stdout_reserved).
+#[macro_export]
+macro_rules! out {
+    ($($arg:tt)*) => {
+        $crate::util::decor::write_stdout(&format!($($arg)*))
+    };
+}
+
+/// `println!` that honours plain output and [`set_stdout_reserved`](crate::util::decor::set_stdout_reserved).
+#[macro_export]
+macro_rules! outln {
+    () => {
+        $crate::util::decor::write_stdout("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::util::decor::write_stdout(&format!("{}\n", format_args!($($arg)*)))
+    };
+}
+
+/// `eprintln!` that honours plain output.
+#[macro_export]
+macro_rules! errln {
+    () => {
+        $crate::util::decor::write_stderr("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::util::decor::write_stderr(&format!("{}\n", format_args!($($arg)*)))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_render_is_ascii_without_banners() {
+        let text = "Processing language: Rust\n\
+                    ==================================================\n\
+                    \u{20}     ✓ octocat/hello: 50 commits\n\
+                    \u{20}     ✗ Failed to fetch forks\n\
+                    \u{20}     ⚠ Failed to compare fork\n\
+                    \u{20}     ⏳ GitHub rate limit exhausted\n\
+                    404 Not Found: truncated…\n\
+                    === Part A: Fetching GitHub Repository Data ===\n";
+
+        let plain = render(text, true);
+
+        assert!(plain.is_ascii(), "{plain}");
+        assert!(!plain.contains("=====\n"));
+        assert!(plain.contains("[OK] octocat/hello"));
+        assert!(plain.contains("[FAIL] Failed"));
+        assert!(plain.contains("[WARN] Failed"));
+        assert!(plain.contains("[WAIT] GitHub"));
+        assert!(plain.contains("=== Part A"));
+    }
+
+    #[test]
+    fn plain_render_replaces_other_non_ascii() {
+        let text = "  ✓ octocat/héllo: 日本 ⏱\n";
+
+        assert_eq!(render(text, true), "  [OK] octocat/h?llo: ?? ?\n");
+    }
+
+    #[test]
+    fn decorated_render_is_unchanged() {
+        let text = "✓ done\n=====\n";
+        assert_eq!(render(text, false), text);
+    }
+}
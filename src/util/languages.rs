@@ -0,0 +1,20 @@
+//! Source file extensions of the languages this tool knows.
+
+/// Source file extensions for a GitHub language name (matched case-insensitively), or
+/// `None` for languages this tool doesn't know.
+pub fn source_extensions(language: &str) -> Option<&'static [&'static str]> {
+    let extensions: &'static [&'static str] = match language.to_ascii_lowercase().as_str() {
+        "rust" => &["rs"],
+        "c" => &["c", "h"],
+        "c++" => &["cpp", "cc", "cxx", "hpp", "hh", "hxx", "h"],
+        "java" => &["java"],
+        "go" => &["go"],
+        "python" => &["py"],
+        "javascript" => &["js", "mjs", "cjs", "jsx"],
+        "typescript" => &["ts", "tsx"],
+        "c#" => &["cs"],
+        "kotlin" => &["kt", "kts"],
+        _ => return None,
+    };
+    Some(extensions)
+}
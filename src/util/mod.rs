@@ -1,2 +1,8 @@
 //! Utilities.
+pub mod backoff;
+pub mod clock;
+pub mod decor;
 pub mod json;
+pub mod languages;
+pub mod progress;
+pub mod time;
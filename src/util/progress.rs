@@ -0,0 +1,129 @@
+//! Run progress and time-remaining estimates.
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::outln;
+
+/// Counts finished work units (e.g. one repository's commits, or its forks) against a
+/// known total and prints an ETA at most once per `interval`.
+#[derive(Debug)]
+pub struct Progress {
+    total: usize,
+    completed: AtomicUsize,
+    started: Instant,
+    interval: Duration,
+    last_report: Mutex<Instant>,
+}
+
+impl Progress {
+    const DEFAULT_INTERVAL: Duration = Duration::from_secs(10);
+
+    pub fn new(total: usize) -> Self {
+        Self::with_interval(total, Self::DEFAULT_INTERVAL)
+    }
+
+    pub fn with_interval(total: usize, interval: Duration) -> Self {
+        let now = Instant::now();
+        Self {
+            total,
+            completed: AtomicUsize::new(0),
+            started: now,
+            interval,
+            last_report: Mutex::new(now),
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    pub fn completed(&self) -> usize {
+        self.completed.load(Ordering::Relaxed)
+    }
+
+    /// Marks `units` more as done and prints the ETA if the last one is old enough.
+    pub fn complete(&self, units: usize) {
+        let completed = self.completed.fetch_add(units, Ordering::Relaxed) + units;
+
+        let now = Instant::now();
+        {
+            let mut last = self.last_report.lock().unwrap_or_else(|e| e.into_inner());
+            if now.duration_since(*last) < self.interval {
+                return;
+            }
+            *last = now;
+        }
+
+        let elapsed = now.duration_since(self.started);
+        if let Some(remaining) = estimate_remaining(completed, self.total, elapsed) {
+            outln!(
+                "  ⏳ {}/{} steps done, about {} left",
+                completed.min(self.total),
+                self.total,
+                format_duration(remaining)
+            );
+        }
+    }
+}
+
+/// Time left at the rate observed so far: `elapsed / completed` per remaining unit.
+/// `None` before anything has completed, since there is no rate to go on yet.
+pub fn estimate_remaining(completed: usize, total: usize, elapsed: Duration) -> Option<Duration> {
+    if completed == 0 {
+        return None;
+    }
+    let remaining = total.saturating_sub(completed);
+    Some(elapsed.mul_f64(remaining as f64 / completed as f64))
+}
+
+/// `1h 5m`, `3m 12s` or `42s`.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 3600, secs % 3600 / 60, secs % 60) {
+        (0, 0, s) => format!("{s}s"),
+        (0, m, s) => format!("{m}m {s}s"),
+        (h, m, _) => format!("{h}h {m}m"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remaining_time_follows_the_observed_rate() {
+        // 10 units in 20s is 2s per unit; 30 units are left.
+        assert_eq!(
+            estimate_remaining(10, 40, Duration::from_secs(20)),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            estimate_remaining(40, 40, Duration::from_secs(20)),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(
+            estimate_remaining(50, 40, Duration::from_secs(20)),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(estimate_remaining(0, 40, Duration::from_secs(20)), None);
+    }
+
+    #[test]
+    fn durations_are_formatted_coarsely() {
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(192)), "3m 12s");
+        assert_eq!(format_duration(Duration::from_secs(3900)), "1h 5m");
+    }
+
+    #[test]
+    fn completed_units_accumulate() {
+        let progress = Progress::with_interval(8, Duration::from_secs(3600));
+
+        progress.complete(3);
+        progress.complete(2);
+
+        assert_eq!(progress.completed(), 5);
+        assert_eq!(progress.total(), 8);
+    }
+}
@@ -0,0 +1,85 @@
+//! GitHub timestamp parsing and date arithmetic.
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, Utc};
+
+use crate::util::clock::Clock;
+
+/// Parses a GitHub timestamp (`2024-01-15T10:30:00Z`, or with an offset such as
+/// `+02:00`) into UTC. Timestamps without any zone are taken to be UTC.
+pub fn parse_github_datetime(value: &str) -> Option<DateTime<Utc>> {
+    parse_github_datetime_local(value).map(|at| at.to_utc())
+}
+
+/// Like [`parse_github_datetime`], but keeps the offset the timestamp was written in,
+/// e.g. to tell the local hour a commit was authored at.
+pub fn parse_github_datetime_local(value: &str) -> Option<DateTime<FixedOffset>> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value).ok().or_else(|| {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
+            .ok()
+            .map(|naive| naive.and_utc().fixed_offset())
+    })
+}
+
+/// Whether `value` is a `YYYY-MM-DD` date or a timestamp GitHub search accepts.
+pub fn is_search_date(value: &str) -> bool {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").is_ok()
+        || DateTime::parse_from_rfc3339(value.trim()).is_ok()
+}
+
+/// Whole days from `at` to `clock.now()`; negative for times in the future.
+pub fn days_ago(at: DateTime<Utc>, clock: &impl Clock) -> i64 {
+    (clock.now() - at).num_days()
+}
+
+/// Whole days since the GitHub timestamp `value`, or `None` if it doesn't parse.
+pub fn days_since(value: &str, clock: &impl Clock) -> Option<i64> {
+    parse_github_datetime(value).map(|at| days_ago(at, clock))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::clock::FixedClock;
+    use chrono::{TimeZone, Timelike};
+
+    #[test]
+    fn parses_utc_timestamps() {
+        assert_eq!(
+            parse_github_datetime("2024-01-15T10:30:00Z"),
+            Some(Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap())
+        );
+        assert_eq!(
+            parse_github_datetime("2024-01-15T10:30:00"),
+            Some(Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn parses_offsets() {
+        assert_eq!(
+            parse_github_datetime("2024-01-15T10:30:00+02:00"),
+            Some(Utc.with_ymd_and_hms(2024, 1, 15, 8, 30, 0).unwrap())
+        );
+        let local = parse_github_datetime_local("2024-01-15T23:30:00-05:00").unwrap();
+        assert_eq!(local.hour(), 23);
+        assert_eq!(local.to_utc().hour(), 4);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for value in ["", "yesterday", "2024-13-01T00:00:00Z", "2024-01-15 10:30"] {
+            assert_eq!(parse_github_datetime(value), None, "{value:?}");
+        }
+        assert!(is_search_date("2023-01-01"));
+        assert!(!is_search_date("2023-1-1x"));
+    }
+
+    #[test]
+    fn counts_days_against_the_clock() {
+        let clock = FixedClock(Utc.with_ymd_and_hms(2024, 1, 31, 12, 0, 0).unwrap());
+
+        assert_eq!(days_since("2024-01-01T00:00:00Z", &clock), Some(30));
+        assert_eq!(days_since("2024-02-02T00:00:00Z", &clock), Some(-1));
+        assert_eq!(days_since("not a date", &clock), None);
+    }
+}
@@ -1,15 +1,33 @@
 //! App tests.
-use ecs160_hw1::app::collect_language_report;
-use ecs160_hw1::config::GitHubConfig;
+use ecs160_hw1::app::cache::RepoCache;
+use ecs160_hw1::app::clone::ClonedRepo;
+use ecs160_hw1::app::output::OutputFormatter;
+use ecs160_hw1::app::phase_error::Phase;
+use ecs160_hw1::app::repo_fetcher::{RepoFetcher, PROGRESS_UNITS_PER_LANGUAGE};
+use ecs160_hw1::app::{
+    collect_language_report, collect_language_report_bounded, collect_language_report_with_config,
+    collect_owner_report, orchestrate_stream, run_parts, store_cloned_repos, store_if_connected,
+};
+use ecs160_hw1::config::{AppConfig, FetchConfig, ForkDivergence, GitHubConfig, RedisConfig};
+use ecs160_hw1::error::AppError;
+use ecs160_hw1::model::{CodeAnalysis, Commit, CommitFile, Issue, Repo};
+use ecs160_hw1::service::{GitRepositoryService, RedisService, TestGitService, TestStorageService};
+use ecs160_hw1::util::progress::Progress;
 use ecs160_hw1::GitService;
+use futures::StreamExt;
 use httpmock::prelude::*;
 use serde_json::json;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
 
 fn service_with_base(base_url: &str) -> GitService {
     let config = GitHubConfig {
         token: None,
         api_base: base_url.to_string(),
         user_agent: "ecs160-test-agent/0.1".to_string(),
+        ..GitHubConfig::default()
     };
 
     GitService::new(config).expect("failed to construct test client")
@@ -96,6 +114,8 @@ fn sample_commit_detail_response() -> serde_json::Value {
 fn sample_issues_response() -> serde_json::Value {
     json!([
         {
+            "id": 1001,
+            "number": 1,
             "title": "Bug report",
             "body": "Something broke",
             "state": "open",
@@ -367,3 +387,1259 @@ async fn collect_language_report_handles_fork_errors() {
     issues_mock.assert();
     forks_mock.assert();
 }
+
+#[tokio::test]
+async fn collect_language_report_records_failed_sub_fetches() {
+    let server = MockServer::start_async().await;
+
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/search/repositories");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(sample_search_response());
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/repo-one/commits");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(sample_commits_response());
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/repo-one/issues");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(sample_issues_response());
+        })
+        .await;
+    // Repo details, commit details and forks are left unmocked or fail. Collaborators,
+    // tags and branches aren't counted unless asked for.
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/repo-one/forks");
+            then.status(500);
+        })
+        .await;
+
+    let service = service_with_base(&server.base_url());
+    let report = collect_language_report(&service, "Rust")
+        .await
+        .expect("report should still be collected when sub-fetches fail");
+
+    let phases: Vec<Phase> = report.errors.iter().map(|e| e.phase).collect();
+    assert_eq!(
+        phases,
+        vec![Phase::Details, Phase::CommitDetails, Phase::Forks]
+    );
+    assert!(report
+        .errors
+        .iter()
+        .all(|e| e.language == "Rust" && e.repo.as_deref() == Some("octocat/repo-one")));
+
+    let summary = OutputFormatter::format_phase_errors(&report.errors);
+    assert!(summary.starts_with("Rust:\n"));
+    assert_eq!(summary.lines().count(), 4);
+    assert!(summary.contains("[forks] octocat/repo-one: "));
+    assert!(summary.contains("[commit details] octocat/repo-one: commit abc123"));
+
+    let json = OutputFormatter::report_json(&report);
+    assert_eq!(json["errors"].as_array().unwrap().len(), 3);
+    assert_eq!(json["errors"][2]["phase"], "forks");
+}
+
+#[tokio::test]
+async fn ref_counts_are_kept_when_the_other_count_fails() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/search/repositories");
+            then.status(200).json_body(sample_search_response());
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/repo-one/tags");
+            then.status(500);
+        })
+        .await;
+    let last = format!(
+        "<{}/repositories/1/branches?per_page=1&page=12>; rel=\"last\"",
+        server.base_url()
+    );
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/repo-one/branches");
+            then.status(200)
+                .header("link", last.as_str())
+                .json_body(json!([{ "name": "main" }]));
+        })
+        .await;
+
+    let config = FetchConfig {
+        fetch_ref_counts: true,
+        ..FetchConfig::default()
+    };
+    let service = service_with_base(&server.base_url());
+    let fetcher = RepoFetcher::with_config(&service, config);
+    let repos = fetcher
+        .fetch_language_data("Rust")
+        .await
+        .expect("fetch should succeed");
+
+    assert_eq!(repos[0].tags_count, None);
+    assert_eq!(repos[0].branches_count, Some(12));
+    let errors = fetcher.take_errors("Rust");
+    let ref_errors: Vec<_> = errors
+        .iter()
+        .filter(|e| e.phase == Phase::RefCounts)
+        .collect();
+    assert_eq!(ref_errors.len(), 1);
+    assert!(ref_errors[0].message.starts_with("tags: "));
+}
+
+/// Runs a language fetch against a search result with `forks_count` forks and returns how
+/// often the forks endpoint was called.
+async fn forks_requests_for(forks_count: u64, config: FetchConfig) -> usize {
+    let server = MockServer::start_async().await;
+    let mut search = sample_search_response();
+    search["items"][0]["forks_count"] = json!(forks_count);
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/search/repositories");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(search);
+        })
+        .await;
+    let forks = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/repo-one/forks");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([]));
+        })
+        .await;
+
+    let service = service_with_base(&server.base_url());
+    RepoFetcher::with_config(&service, config)
+        .fetch_language_data("Rust")
+        .await
+        .expect("fetch should succeed");
+    forks.hits_async().await
+}
+
+#[tokio::test]
+async fn forks_are_not_fetched_for_repos_without_forks() {
+    assert_eq!(forks_requests_for(0, FetchConfig::default()).await, 0);
+    assert_eq!(forks_requests_for(5, FetchConfig::default()).await, 1);
+}
+
+#[tokio::test]
+async fn min_forks_to_fetch_skips_low_fork_repos() {
+    let config = FetchConfig {
+        min_forks_to_fetch: 6,
+        ..FetchConfig::default()
+    };
+
+    assert_eq!(forks_requests_for(5, config.clone()).await, 0);
+    assert_eq!(forks_requests_for(6, config).await, 1);
+}
+
+#[tokio::test]
+async fn commit_details_are_fetched_for_the_newest_commits() {
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/search/repositories");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(sample_search_response());
+        })
+        .await;
+    // Listed out of date order; c3 has only a committer date.
+    let listed = [
+        (
+            "c1",
+            json!({ "author": { "date": "2024-01-01T00:00:00Z" } }),
+        ),
+        (
+            "c2",
+            json!({ "author": { "date": "2024-03-01T00:00:00Z" } }),
+        ),
+        (
+            "c3",
+            json!({ "committer": { "date": "2024-04-01T00:00:00Z" } }),
+        ),
+        (
+            "c4",
+            json!({ "author": { "date": "2024-02-01T00:00:00Z" } }),
+        ),
+    ];
+    let commits: Vec<_> = listed
+        .iter()
+        .map(|(sha, dates)| {
+            let mut commit = dates.clone();
+            commit["message"] = json!("change");
+            json!({ "sha": sha, "commit": commit })
+        })
+        .collect();
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/repo-one/commits");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!(commits));
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/repo-one/issues");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([]));
+        })
+        .await;
+    let mut details = Vec::new();
+    for (sha, _) in &listed {
+        let body = json!({ "sha": sha, "commit": { "message": "change" }, "files": [] });
+        details.push(
+            server
+                .mock_async(|when, then| {
+                    when.method(GET)
+                        .path(format!("/repos/octocat/repo-one/commits/{sha}"));
+                    then.status(200)
+                        .header("content-type", "application/json")
+                        .json_body(body);
+                })
+                .await,
+        );
+    }
+
+    let service = service_with_base(&server.base_url());
+    let config = FetchConfig {
+        max_commit_details: 2,
+        ..FetchConfig::default()
+    };
+    let repos = RepoFetcher::with_config(&service, config)
+        .fetch_language_data("Rust")
+        .await
+        .expect("fetch should succeed");
+
+    let shas: Vec<_> = repos[0]
+        .recent_commits
+        .iter()
+        .map(|c| c.sha.as_str())
+        .collect();
+    assert_eq!(shas, vec!["c2", "c3"]);
+    let hits: Vec<_> = details.iter().map(|mock| mock.hits()).collect();
+    assert_eq!(hits, vec![0, 1, 1, 0]);
+    assert_eq!(repos[0].commit_count, 4);
+}
+
+fn test_repo(id: i64, name: &str, stars: u64, archived: bool) -> Repo {
+    Repo::from_json(&json!({
+        "id": id,
+        "name": name,
+        "full_name": format!("octocat/{name}"),
+        "html_url": format!("https://example.com/{name}"),
+        "stargazers_count": stars,
+        "archived": archived,
+        "owner": {
+            "login": "octocat",
+            "id": 1,
+            "html_url": "https://github.com/octocat",
+            "site_admin": false
+        }
+    }))
+    .expect("test repo should parse")
+}
+
+#[tokio::test]
+async fn fetch_language_data_excludes_archived_when_disabled() {
+    let mut service = TestGitService::new();
+    service.repos = vec![
+        test_repo(1, "active", 300, false),
+        test_repo(2, "archived", 200, true),
+        test_repo(3, "also-active", 100, false),
+    ];
+
+    let config = FetchConfig {
+        include_archived: false,
+        ..FetchConfig::default()
+    };
+    let repos = RepoFetcher::with_config(&service, config)
+        .fetch_language_data("Rust")
+        .await
+        .expect("fetch should succeed");
+
+    let names: Vec<_> = repos.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["active", "also-active"]);
+}
+
+#[tokio::test]
+async fn fetch_language_data_completes_a_language_of_progress() {
+    let mut service = TestGitService::new();
+    service.repos = vec![
+        test_repo(1, "alpha", 300, false),
+        test_repo(2, "beta", 200, false),
+    ];
+    let progress = Arc::new(Progress::new(PROGRESS_UNITS_PER_LANGUAGE));
+
+    RepoFetcher::new(&service)
+        .with_progress(progress.clone())
+        .fetch_language_data("Rust")
+        .await
+        .expect("fetch should succeed");
+
+    assert_eq!(progress.completed(), PROGRESS_UNITS_PER_LANGUAGE);
+}
+
+#[tokio::test]
+async fn fetch_language_data_keeps_archived_by_default() {
+    let mut service = TestGitService::new();
+    service.repos = vec![
+        test_repo(1, "active", 300, false),
+        test_repo(2, "archived", 200, true),
+    ];
+
+    let repos = RepoFetcher::new(&service)
+        .fetch_language_data("Rust")
+        .await
+        .expect("fetch should succeed");
+
+    assert_eq!(repos.len(), 2);
+}
+
+#[tokio::test]
+async fn fetch_language_data_strict_match_drops_other_languages() {
+    let mut service = TestGitService::new();
+    service.repos = vec![
+        Repo::builder().id(1).name("engine").language("C++").build(),
+        Repo::builder().id(2).name("kernel").language("C").build(),
+        Repo::builder().id(3).name("tool").language("c++").build(),
+        Repo::builder().id(4).name("docs").build(),
+    ];
+
+    let strict = FetchConfig {
+        strict_language_match: true,
+        ..FetchConfig::default()
+    };
+    let repos = RepoFetcher::with_config(&service, strict)
+        .fetch_language_data("C++")
+        .await
+        .expect("fetch should succeed");
+    let names: Vec<_> = repos.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["engine", "tool"]);
+
+    let lenient = RepoFetcher::new(&service)
+        .fetch_language_data("C++")
+        .await
+        .expect("fetch should succeed");
+    assert_eq!(lenient.len(), 4);
+}
+
+#[tokio::test]
+async fn collect_language_report_skips_commit_details_when_configured() {
+    let server = MockServer::start_async().await;
+
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/search/repositories");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(sample_search_response());
+        })
+        .await;
+
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/repo-one/commits");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(sample_commits_response());
+        })
+        .await;
+
+    let commit_detail_mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/repo-one/commits/abc123");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(sample_commit_detail_response());
+        })
+        .await;
+
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/repo-one/issues");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(sample_issues_response());
+        })
+        .await;
+
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/repo-one/forks");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!([]));
+        })
+        .await;
+
+    let mut config = AppConfig::default();
+    config.fetch.skip_commit_details = true;
+    let service = service_with_base(&server.base_url());
+    let report = collect_language_report_with_config(&service, "Rust", &config)
+        .await
+        .expect("report should be collected");
+
+    assert_eq!(report.total_repo_commits, 1);
+    assert_eq!(report.total_open_issues, 1);
+    assert_eq!(report.repos[0].recent_commits.len(), 1);
+    assert!(report.repo_metrics[0].top_files.is_empty());
+    commit_detail_mock.assert_hits(0);
+}
+
+#[tokio::test]
+async fn collect_language_report_skips_fork_commits_when_configured() {
+    let server = MockServer::start_async().await;
+
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/search/repositories");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(sample_search_response());
+        })
+        .await;
+
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/repo-one/commits");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(sample_commits_response());
+        })
+        .await;
+
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/repo-one/issues");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(sample_issues_response());
+        })
+        .await;
+
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/repo-one/forks");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(sample_forks_response());
+        })
+        .await;
+
+    let fork_commits_mock = server
+        .mock_async(|when, then| {
+            when.method(GET)
+                .path_contains("/repo-one/commits")
+                .matches(|req| !req.path.starts_with("/repos/octocat/"));
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(empty_commits_response());
+        })
+        .await;
+
+    let mut config = AppConfig::default();
+    config.fetch.skip_commit_details = true;
+    config.fetch.skip_fork_commits = true;
+    let service = service_with_base(&server.base_url());
+    let report = collect_language_report_with_config(&service, "Rust", &config)
+        .await
+        .expect("report should be collected");
+
+    assert_eq!(report.repos[0].forks.len(), 2);
+    assert_eq!(report.new_fork_commits, 0);
+    fork_commits_mock.assert_hits(0);
+}
+
+#[tokio::test]
+async fn orchestrate_stream_yields_every_language() {
+    let mut service = TestGitService::new();
+    service.repos = vec![
+        test_repo(1, "alpha", 300, false),
+        test_repo(2, "beta", 200, false),
+    ];
+
+    let config = AppConfig::default();
+    let outcomes: Vec<_> = orchestrate_stream(&service, &["Rust", "Java", "Go"], &config)
+        .collect()
+        .await;
+
+    let mut languages: Vec<_> = outcomes.iter().map(|o| o.language.as_str()).collect();
+    languages.sort();
+    assert_eq!(languages, vec!["Go", "Java", "Rust"]);
+    for outcome in &outcomes {
+        let report = outcome.result.as_ref().expect("language should succeed");
+        assert_eq!(report.language, outcome.language);
+        assert_eq!(report.total_stars, 500);
+    }
+}
+
+#[tokio::test]
+async fn collect_language_report_skips_repos_unavailable_for_legal_reasons() {
+    let server = MockServer::start_async().await;
+
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/search/repositories");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(sample_search_response());
+        })
+        .await;
+
+    let commits_mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/repo-one/commits");
+            then.status(451).json_body(json!({
+                "message": "Repository access blocked",
+                "block": { "reason": "dmca" }
+            }));
+        })
+        .await;
+
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/repo-one/issues");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(sample_issues_response());
+        })
+        .await;
+
+    let forks_mock = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/repo-one/forks");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(sample_forks_response());
+        })
+        .await;
+
+    let service = service_with_base(&server.base_url());
+    let report = collect_language_report(&service, "Rust")
+        .await
+        .expect("an unavailable repo should not abort the report");
+
+    assert_eq!(report.repos.len(), 1);
+    assert!(report.repos[0].unavailable);
+    assert!(report.repos[0].recent_commits.is_empty());
+    assert_eq!(report.repo_metrics.len(), 1);
+    assert!(report.repo_metrics[0].unavailable);
+    assert_eq!(report.total_repo_commits, 0);
+    commits_mock.assert();
+    forks_mock.assert_hits(0);
+}
+
+#[tokio::test]
+async fn collect_owner_report_is_keyed_by_login() {
+    let mut service = TestGitService::new();
+    service.repos = vec![
+        test_repo(1, "alpha", 30, false),
+        test_repo(2, "beta", 20, false),
+    ];
+
+    let report = collect_owner_report(&service, "octocat", &AppConfig::default())
+        .await
+        .expect("owner report should be collected");
+
+    assert_eq!(report.language, "octocat");
+    assert_eq!(report.repos.len(), 2);
+    assert_eq!(report.total_stars, 50);
+}
+
+#[tokio::test]
+async fn store_cloned_repos_persists_code_analysis() {
+    let analysis = CodeAnalysis {
+        source_files: 120,
+        total_files: 200,
+        source_ratio: 0.6,
+        is_source_code_repo: true,
+        file_extensions: [("rs".to_string(), 8), ("toml".to_string(), 2)].into(),
+        is_monorepo: false,
+        test_files: 20,
+        production_files: 100,
+    };
+    let cloned = vec![ClonedRepo {
+        repo: test_repo(1, "engine", 500, false),
+        analysis: analysis.clone(),
+    }];
+    let mut storage = TestStorageService::new();
+
+    store_cloned_repos(&mut storage, &cloned)
+        .await
+        .expect("storage should succeed");
+
+    assert!(storage.stored_repos.contains_key("octocat:engine"));
+    let stored = &storage.stored_analyses["octocat:engine"];
+    assert_eq!(stored, &analysis);
+    assert_eq!(stored.source_files, 120);
+    assert_eq!(stored.total_files, 200);
+}
+
+#[tokio::test]
+async fn reports_survive_unreachable_redis() {
+    let mut service = TestGitService::new();
+    service.repos = vec![test_repo(1, "engine", 500, false)];
+    let config = AppConfig::default();
+
+    let report = collect_language_report_with_config(&service, "Rust", &config)
+        .await
+        .expect("report should be collected");
+    let cloned = vec![ClonedRepo {
+        repo: report.repos[0].clone(),
+        analysis: CodeAnalysis {
+            source_files: 1,
+            total_files: 1,
+            source_ratio: 1.0,
+            is_source_code_repo: true,
+            file_extensions: [("rs".to_string(), 1)].into(),
+            is_monorepo: false,
+            test_files: 0,
+            production_files: 1,
+        },
+    }];
+
+    // Nothing listens on port 1, so connecting fails immediately.
+    let redis = RedisService::new(RedisConfig {
+        url: "redis://127.0.0.1:1".to_string(),
+        ..RedisConfig::default()
+    })
+    .await;
+    assert!(redis.is_err());
+
+    let stored = store_if_connected(redis, &cloned)
+        .await
+        .expect("an unreachable Redis should not fail the run");
+
+    assert!(!stored);
+    assert_eq!(report.repos.len(), 1);
+    assert_eq!(report.total_stars, 500);
+}
+
+#[tokio::test]
+async fn store_if_connected_stores_when_connected() {
+    let cloned = vec![ClonedRepo {
+        repo: test_repo(1, "engine", 500, false),
+        analysis: CodeAnalysis {
+            source_files: 1,
+            total_files: 1,
+            source_ratio: 1.0,
+            is_source_code_repo: true,
+            file_extensions: Default::default(),
+            is_monorepo: false,
+            test_files: 0,
+            production_files: 1,
+        },
+    }];
+
+    let stored = store_if_connected(Ok(TestStorageService::new()), &cloned)
+        .await
+        .expect("storage should succeed");
+
+    assert!(stored);
+}
+
+fn pushed_repo(id: i64, name: &str, pushed_at: &str) -> serde_json::Value {
+    json!({
+        "id": id,
+        "name": name,
+        "full_name": format!("octocat/{name}"),
+        "html_url": format!("https://example.com/{name}"),
+        "stargazers_count": 10,
+        "pushed_at": pushed_at,
+        "owner": {
+            "login": "octocat",
+            "id": 1,
+            "html_url": "https://github.com/octocat",
+            "site_admin": false
+        }
+    })
+}
+
+/// Answers the details, collaborators, tags and branches requests for `octocat/<name>`.
+async fn serve_details_and_ref_counts(server: &MockServer, name: &str, pushed_at: &str) {
+    let details = pushed_repo(3, name, pushed_at);
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path(format!("/repos/octocat/{name}"));
+            then.status(200).json_body(details);
+        })
+        .await;
+    for listing in ["collaborators", "tags", "branches"] {
+        server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path(format!("/repos/octocat/{name}/{listing}"));
+                then.status(200).json_body(json!([]));
+            })
+            .await;
+    }
+}
+
+#[tokio::test]
+async fn collect_language_report_does_not_cache_partly_enriched_repos() {
+    let cache_dir =
+        std::env::temp_dir().join(format!("ecs160-cache-partial-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&cache_dir);
+
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/search/repositories");
+            then.status(200).json_body(json!({
+                "items": [
+                    pushed_repo(1, "steady", "2024-05-01T00:00:00Z"),
+                    pushed_repo(2, "busy", "2024-06-01T00:00:00Z")
+                ]
+            }));
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path_contains("/commits");
+            then.status(200).json_body(json!([]));
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/steady/issues");
+            then.status(200).json_body(json!([]));
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/busy/issues");
+            then.status(500);
+        })
+        .await;
+    serve_details_and_ref_counts(&server, "steady", "2024-05-01T00:00:00Z").await;
+    serve_details_and_ref_counts(&server, "busy", "2024-06-01T00:00:00Z").await;
+
+    let mut config = AppConfig::default();
+    config.fetch.cache_dir = Some(cache_dir.clone());
+    let service = service_with_base(&server.base_url());
+    let report = collect_language_report_with_config(&service, "Rust", &config)
+        .await
+        .expect("report should be collected");
+
+    let phases: Vec<Phase> = report.errors.iter().map(|e| e.phase).collect();
+    assert_eq!(phases, vec![Phase::Issues]);
+    let cache = RepoCache::new(&cache_dir);
+    assert!(cache.load_unchanged(&report.repos[0]).is_some());
+    assert!(cache.load_unchanged(&report.repos[1]).is_none());
+
+    let _ = std::fs::remove_dir_all(&cache_dir);
+}
+
+#[tokio::test]
+async fn collect_language_report_reuses_cache_for_unchanged_repos() {
+    let cache_dir = std::env::temp_dir().join(format!("ecs160-cache-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&cache_dir);
+    let cache = RepoCache::new(&cache_dir);
+
+    let mut unchanged = Repo::from_json(&pushed_repo(1, "steady", "2024-05-01T00:00:00Z")).unwrap();
+    unchanged.issues = vec![Issue::from_json(&sample_issues_response()[0]).unwrap()];
+    unchanged.commit_count = 7;
+    cache.store(&unchanged).unwrap();
+    let outdated = Repo::from_json(&pushed_repo(2, "busy", "2024-05-01T00:00:00Z")).unwrap();
+    cache.store(&outdated).unwrap();
+
+    let server = MockServer::start_async().await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/search/repositories");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body(json!({
+                    "items": [
+                        pushed_repo(1, "steady", "2024-05-01T00:00:00Z"),
+                        pushed_repo(2, "busy", "2024-06-01T00:00:00Z")
+                    ]
+                }));
+        })
+        .await;
+    let steady_commits = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/steady/commits");
+            then.status(200).json_body(json!([]));
+        })
+        .await;
+    let busy_commits = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/busy/commits");
+            then.status(200).json_body(sample_commits_response());
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path("/repos/octocat/busy/commits/abc123");
+            then.status(200).json_body(sample_commit_detail_response());
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path_contains("/issues");
+            then.status(200).json_body(json!([]));
+        })
+        .await;
+    server
+        .mock_async(|when, then| {
+            when.method(GET).path_contains("/forks");
+            then.status(200).json_body(json!([]));
+        })
+        .await;
+    serve_details_and_ref_counts(&server, "busy", "2024-06-01T00:00:00Z").await;
+
+    let mut config = AppConfig::default();
+    config.fetch.cache_dir = Some(cache_dir.clone());
+    let service = service_with_base(&server.base_url());
+    let report = collect_language_report_with_config(&service, "Rust", &config)
+        .await
+        .expect("report should be collected");
+
+    steady_commits.assert_hits(0);
+    busy_commits.assert_hits(1);
+    assert_eq!(report.repos[0].commit_count, 7);
+    assert_eq!(report.repos[0].issues.len(), 1);
+    assert_eq!(report.repos[1].commit_count, 1);
+
+    let refreshed = cache
+        .load_unchanged(&report.repos[1])
+        .expect("re-enriched repo should be cached with its new pushed_at");
+    assert_eq!(refreshed.commit_count, 1);
+
+    config.fetch.force_refresh = true;
+    collect_language_report_with_config(&service, "Rust", &config)
+        .await
+        .expect("report should be collected");
+    steady_commits.assert_hits(1);
+
+    let _ = std::fs::remove_dir_all(&cache_dir);
+}
+
+/// Serves one repository with ten forks and records how many fork-commit requests
+/// are in flight at once.
+#[derive(Default)]
+struct ForkConcurrencyProbe {
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+    fork_requests: AtomicUsize,
+}
+
+impl GitRepositoryService for ForkConcurrencyProbe {
+    async fn fetch_top_repositories(
+        &self,
+        _language: &str,
+        _per_page: u8,
+    ) -> Result<Vec<Repo>, AppError> {
+        Ok(vec![Repo::builder()
+            .id(1)
+            .name("engine")
+            .forks_count(10)
+            .build()])
+    }
+
+    async fn fetch_repo_forks(&self, _owner: &str, _repo: &str) -> Result<Vec<Repo>, AppError> {
+        Ok((0..10)
+            .map(|n| {
+                Repo::builder()
+                    .id(100 + n)
+                    .name("engine")
+                    .owner(&format!("forker{n}"))
+                    .build()
+            })
+            .collect())
+    }
+
+    async fn fetch_repo_details(&self, _owner: &str, _repo: &str) -> Result<Repo, AppError> {
+        Err(AppError::NotImplemented)
+    }
+
+    async fn fetch_user_repositories(&self, _login: &str) -> Result<Vec<Repo>, AppError> {
+        Ok(Vec::new())
+    }
+
+    async fn fetch_recent_commits(
+        &self,
+        owner: &str,
+        _repo: &str,
+        _since: Option<&str>,
+        _until: Option<&str>,
+    ) -> Result<Vec<Commit>, AppError> {
+        let Some(n) = owner.strip_prefix("forker") else {
+            return Ok(Vec::new());
+        };
+        self.fork_requests.fetch_add(1, Ordering::SeqCst);
+        let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+        // Later forks answer sooner, so requests finish out of order.
+        let n: u64 = n.parse().unwrap();
+        tokio::time::sleep(Duration::from_millis(30 - 2 * n)).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        // forkerN has N commits.
+        (0..n)
+            .map(|i| commit_with_sha(&format!("{owner}-{i}")))
+            .collect()
+    }
+
+    async fn fetch_open_issues(&self, _owner: &str, _repo: &str) -> Result<Vec<Issue>, AppError> {
+        Ok(Vec::new())
+    }
+
+    async fn fetch_commit_with_files(
+        &self,
+        _owner: &str,
+        _repo: &str,
+        _sha: &str,
+    ) -> Result<Commit, AppError> {
+        Err(AppError::NotImplemented)
+    }
+
+    async fn fetch_compare(
+        &self,
+        _owner: &str,
+        _repo: &str,
+        _base: &str,
+        _head: &str,
+    ) -> Result<Vec<CommitFile>, AppError> {
+        Ok(Vec::new())
+    }
+
+    async fn fetch_fork_ahead_by(
+        &self,
+        _parent_owner: &str,
+        _parent_repo: &str,
+        fork_owner: &str,
+        _branch: &str,
+    ) -> Result<u64, AppError> {
+        let n: u64 = fork_owner.strip_prefix("forker").unwrap().parse().unwrap();
+        self.fork_requests.fetch_add(1, Ordering::SeqCst);
+        let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(30 - 2 * n)).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        // forkerN is N commits ahead.
+        Ok(n)
+    }
+
+    async fn fetch_collaborators_count(
+        &self,
+        _owner: &str,
+        _repo: &str,
+    ) -> Result<Option<u64>, AppError> {
+        Ok(None)
+    }
+
+    async fn fetch_tags_count(&self, _owner: &str, _repo: &str) -> Result<u64, AppError> {
+        Ok(0)
+    }
+
+    async fn fetch_branches_count(&self, _owner: &str, _repo: &str) -> Result<u64, AppError> {
+        Ok(1)
+    }
+}
+
+#[tokio::test]
+async fn max_concurrency_bounds_in_flight_fork_requests() {
+    let service = ForkConcurrencyProbe::default();
+
+    RepoFetcher::new(&service)
+        .with_max_concurrency(3)
+        .fetch_language_data("Rust")
+        .await
+        .expect("fetch should succeed");
+
+    assert_eq!(service.fork_requests.load(Ordering::SeqCst), 10);
+    assert_eq!(service.max_in_flight.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn every_fork_gets_its_commit_count_under_the_concurrency_cap() {
+    let service = ForkConcurrencyProbe::default();
+
+    let repos = RepoFetcher::new(&service)
+        .with_max_concurrency(4)
+        .fetch_language_data("Rust")
+        .await
+        .expect("fetch should succeed");
+
+    let counts: Vec<_> = repos[0]
+        .forks
+        .iter()
+        .map(|fork| (fork.owner.login.clone(), fork.commit_count))
+        .collect();
+    let expected: Vec<_> = (0..10).map(|n| (format!("forker{n}"), n)).collect();
+    assert_eq!(counts, expected);
+    assert_eq!(service.max_in_flight.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn compare_requests_share_the_concurrency_cap() {
+    let service = ForkConcurrencyProbe::default();
+    let config = FetchConfig {
+        fork_divergence: ForkDivergence::Compare,
+        ..FetchConfig::default()
+    };
+
+    let repos = RepoFetcher::with_config(&service, config)
+        .with_max_concurrency(3)
+        .fetch_language_data("Rust")
+        .await
+        .expect("fetch should succeed");
+
+    let ahead: Vec<_> = repos[0].forks.iter().map(|fork| fork.ahead_by).collect();
+    let expected: Vec<_> = (0..10).map(Some).collect();
+    assert_eq!(ahead, expected);
+    assert_eq!(service.fork_requests.load(Ordering::SeqCst), 10);
+    assert_eq!(service.max_in_flight.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn shared_semaphore_bounds_requests_across_languages() {
+    let service = ForkConcurrencyProbe::default();
+    let config = AppConfig::default();
+    let permits = Arc::new(Semaphore::new(3));
+
+    let (rust, java) = tokio::join!(
+        collect_language_report_bounded(&service, "Rust", &config, permits.clone()),
+        collect_language_report_bounded(&service, "Java", &config, permits.clone())
+    );
+    rust.expect("Rust report should succeed");
+    java.expect("Java report should succeed");
+
+    assert_eq!(service.fork_requests.load(Ordering::SeqCst), 20);
+    assert_eq!(service.max_in_flight.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn closed_semaphore_fails_the_bounded_report() {
+    let service = ForkConcurrencyProbe::default();
+    let permits = Arc::new(Semaphore::new(3));
+    permits.close();
+
+    let err = collect_language_report_bounded(&service, "Rust", &AppConfig::default(), permits)
+        .await
+        .expect_err("no request should be sent without a permit");
+
+    assert!(matches!(err, AppError::Config(_)));
+    assert_eq!(service.fork_requests.load(Ordering::SeqCst), 0);
+}
+
+#[derive(Default)]
+struct RepoConcurrencyProbe {
+    repos_in_flight: AtomicUsize,
+    max_repos_in_flight: AtomicUsize,
+    details_in_flight: AtomicUsize,
+    max_details_in_flight: AtomicUsize,
+}
+
+async fn track_in_flight(in_flight: &AtomicUsize, max_in_flight: &AtomicUsize) {
+    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+    max_in_flight.fetch_max(now, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    in_flight.fetch_sub(1, Ordering::SeqCst);
+}
+
+fn commit_with_sha(sha: &str) -> Result<Commit, AppError> {
+    Commit::from_json(&json!({ "sha": sha, "commit": { "message": "change" } }))
+}
+
+impl GitRepositoryService for RepoConcurrencyProbe {
+    async fn fetch_top_repositories(
+        &self,
+        _language: &str,
+        _per_page: u8,
+    ) -> Result<Vec<Repo>, AppError> {
+        Ok((0..6)
+            .map(|n| Repo::builder().id(n).name(&format!("repo{n}")).build())
+            .collect())
+    }
+
+    async fn fetch_repo_forks(&self, _owner: &str, _repo: &str) -> Result<Vec<Repo>, AppError> {
+        Ok(Vec::new())
+    }
+
+    async fn fetch_repo_details(&self, _owner: &str, _repo: &str) -> Result<Repo, AppError> {
+        Err(AppError::NotImplemented)
+    }
+
+    async fn fetch_user_repositories(&self, _login: &str) -> Result<Vec<Repo>, AppError> {
+        Ok(Vec::new())
+    }
+
+    async fn fetch_recent_commits(
+        &self,
+        _owner: &str,
+        repo: &str,
+        _since: Option<&str>,
+        _until: Option<&str>,
+    ) -> Result<Vec<Commit>, AppError> {
+        track_in_flight(&self.repos_in_flight, &self.max_repos_in_flight).await;
+        (0..4)
+            .map(|n| commit_with_sha(&format!("{repo}-{n}")))
+            .collect()
+    }
+
+    async fn fetch_open_issues(&self, _owner: &str, _repo: &str) -> Result<Vec<Issue>, AppError> {
+        Ok(Vec::new())
+    }
+
+    async fn fetch_commit_with_files(
+        &self,
+        _owner: &str,
+        _repo: &str,
+        sha: &str,
+    ) -> Result<Commit, AppError> {
+        track_in_flight(&self.details_in_flight, &self.max_details_in_flight).await;
+        if sha.ends_with("-3") {
+            return Err(AppError::GitHubApi {
+                status: 404,
+                message: "404 Not Found".to_string(),
+            });
+        }
+        commit_with_sha(sha)
+    }
+
+    async fn fetch_compare(
+        &self,
+        _owner: &str,
+        _repo: &str,
+        _base: &str,
+        _head: &str,
+    ) -> Result<Vec<CommitFile>, AppError> {
+        Ok(Vec::new())
+    }
+
+    async fn fetch_fork_ahead_by(
+        &self,
+        _parent_owner: &str,
+        _parent_repo: &str,
+        _fork_owner: &str,
+        _branch: &str,
+    ) -> Result<u64, AppError> {
+        Ok(0)
+    }
+
+    async fn fetch_collaborators_count(
+        &self,
+        _owner: &str,
+        _repo: &str,
+    ) -> Result<Option<u64>, AppError> {
+        Ok(None)
+    }
+
+    async fn fetch_tags_count(&self, _owner: &str, _repo: &str) -> Result<u64, AppError> {
+        Ok(0)
+    }
+
+    async fn fetch_branches_count(&self, _owner: &str, _repo: &str) -> Result<u64, AppError> {
+        Ok(1)
+    }
+}
+
+#[tokio::test]
+async fn repos_and_commit_details_are_enriched_with_bounded_concurrency() {
+    let service = RepoConcurrencyProbe::default();
+    let config = FetchConfig {
+        repo_concurrency: 3,
+        commit_detail_concurrency: 2,
+        ..FetchConfig::default()
+    };
+
+    let fetcher = RepoFetcher::with_config(&service, config);
+    let repos = fetcher
+        .fetch_language_data("Rust")
+        .await
+        .expect("fetch should succeed");
+
+    assert_eq!(repos.len(), 6);
+    for repo in &repos {
+        let shas: Vec<_> = repo.recent_commits.iter().map(|c| c.sha.as_str()).collect();
+        let name = &repo.name;
+        assert_eq!(
+            shas,
+            vec![
+                format!("{name}-0"),
+                format!("{name}-1"),
+                format!("{name}-2")
+            ]
+        );
+        assert_eq!(repo.commit_count, 4);
+    }
+    let detail_errors = fetcher
+        .take_errors("Rust")
+        .into_iter()
+        .filter(|e| e.phase == Phase::CommitDetails)
+        .count();
+    assert_eq!(detail_errors, 6);
+    assert_eq!(service.max_repos_in_flight.load(Ordering::SeqCst), 3);
+    // Up to 3 repos fetch details at the same time, at most 2 commits each.
+    let max_details = service.max_details_in_flight.load(Ordering::SeqCst);
+    assert!((2..=6).contains(&max_details), "{max_details}");
+}
+
+#[tokio::test]
+async fn run_parts_a_b_never_clones_or_stores() {
+    let clone_dir = std::env::temp_dir().join(format!("ecs160-parts-ab-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&clone_dir);
+    let connected = AtomicBool::new(false);
+    let config = AppConfig {
+        parts: "A,B".parse().unwrap(),
+        ..AppConfig::default()
+    };
+
+    let errors = run_parts(&TestGitService::new(), &config, &clone_dir, || async {
+        connected.store(true, Ordering::SeqCst);
+        Ok(TestStorageService::new())
+    })
+    .await
+    .expect("parts A and B should run");
+
+    assert!(errors.is_empty());
+    assert!(!clone_dir.exists(), "cloning must not run");
+    assert!(
+        !connected.load(Ordering::SeqCst),
+        "storage must not be connected"
+    );
+}
+
+#[tokio::test]
+async fn run_parts_runs_clone_and_store_by_default() {
+    let clone_dir = std::env::temp_dir().join(format!("ecs160-parts-all-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&clone_dir);
+    let connected = AtomicBool::new(false);
+
+    run_parts(
+        &TestGitService::new(),
+        &AppConfig::default(),
+        &clone_dir,
+        || async {
+            connected.store(true, Ordering::SeqCst);
+            Ok(TestStorageService::new())
+        },
+    )
+    .await
+    .expect("all parts should run");
+
+    assert!(clone_dir.join("clone_summary.json").exists());
+    assert!(connected.load(Ordering::SeqCst));
+    let _ = std::fs::remove_dir_all(&clone_dir);
+}
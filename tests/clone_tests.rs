@@ -0,0 +1,789 @@
+//! Clone selection tests.
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use ecs160_hw1::app::clone::{
+    check_for_source_code, clone_best_repos, find_best_code_repo, select_code_repo_with,
+    CloneSkipReason, CodeAnalysis, CodeDetectionRules, DefaultSelector, RepoSelector,
+};
+use ecs160_hw1::app::dependencies::{declared_dependencies, ManifestDependencies};
+use ecs160_hw1::app::output::OutputFormatter;
+use ecs160_hw1::app::phase_error::Phase;
+use ecs160_hw1::app::stats::StatsCalculator;
+use ecs160_hw1::config::{CloneConfig, SelectionMetric, StatsConfig};
+use ecs160_hw1::model::Repo;
+use serde_json::json;
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ecs160-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("failed to create scratch dir");
+    dir
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("git should be installed")
+        .status;
+    assert!(status.success(), "git {args:?} failed");
+}
+
+/// Creates a local "remote" at `<remote_root>/octocat/<name>.git` holding the given files.
+fn create_remote(remote_root: &Path, name: &str, files: &[&str]) {
+    create_owned_remote(remote_root, "octocat", name, files);
+}
+
+/// Like [`create_remote`], for a repository under another owner.
+fn create_owned_remote(remote_root: &Path, owner: &str, name: &str, files: &[&str]) {
+    let dir = remote_root.join(owner).join(format!("{name}.git"));
+    std::fs::create_dir_all(&dir).unwrap();
+    for file in files {
+        std::fs::write(dir.join(file), "content").unwrap();
+    }
+    git(&dir, &["init", "-q"]);
+    git(&dir, &["add", "."]);
+    git(&dir, &["commit", "-q", "-m", "init"]);
+}
+
+fn test_repo(id: i64, name: &str, stars: u64) -> Repo {
+    Repo::from_json(&json!({
+        "id": id,
+        "name": name,
+        "full_name": format!("octocat/{name}"),
+        "html_url": format!("https://example.com/{name}"),
+        "stargazers_count": stars,
+        "owner": {
+            "login": "octocat",
+            "id": 1,
+            "html_url": "https://github.com/octocat",
+            "site_admin": false
+        }
+    }))
+    .expect("test repo should parse")
+}
+
+#[tokio::test]
+async fn concurrent_clone_picks_highest_starred_code_repo() {
+    let root = scratch_dir("concurrent-clone");
+    let remote_root = root.join("remote");
+    let clone_root = root.join("clones");
+
+    create_remote(&remote_root, "docs", &["README.md", "GUIDE.md", "FAQ.md"]);
+    create_remote(&remote_root, "engine", &["main.rs", "lib.rs", "README.md"]);
+    create_remote(&remote_root, "toolkit", &["main.rs", "util.rs"]);
+
+    let repos = vec![
+        test_repo(1, "docs", 300),
+        test_repo(2, "toolkit", 100),
+        test_repo(3, "engine", 200),
+    ];
+    let config = CloneConfig {
+        min_source_ratio: 0.01,
+        parallelism: 3,
+        git_base_url: format!("file://{}", remote_root.display()),
+        ..CloneConfig::default()
+    };
+
+    let (best, analysis) = find_best_code_repo(&repos, "Rust", &clone_root, &config)
+        .await
+        .expect("selection should not fail")
+        .expect("a code repository should be found");
+
+    assert_eq!(best.name, "engine");
+    assert_eq!(analysis.source_files, 2);
+    assert!(clone_root.join("rust-octocat-engine").exists());
+    assert!(!clone_root.join("rust-octocat-toolkit").exists());
+    assert!(!clone_root.join("rust-octocat-docs").exists());
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[tokio::test]
+async fn concurrent_clone_keeps_same_named_repos_apart() {
+    let root = scratch_dir("concurrent-clone-same-name");
+    let remote_root = root.join("remote");
+    let clone_root = root.join("clones");
+
+    create_remote(&remote_root, "engine", &["README.md", "GUIDE.md", "FAQ.md"]);
+    create_owned_remote(&remote_root, "hubot", "engine", &["main.rs", "lib.rs"]);
+
+    let mut namesake = test_repo(2, "engine", 100);
+    namesake.owner.login = "hubot".to_string();
+    let repos = vec![test_repo(1, "engine", 200), namesake];
+    let config = CloneConfig {
+        min_source_ratio: 0.01,
+        parallelism: 2,
+        git_base_url: format!("file://{}", remote_root.display()),
+        ..CloneConfig::default()
+    };
+
+    let (best, analysis) = find_best_code_repo(&repos, "Rust", &clone_root, &config)
+        .await
+        .expect("selection should not fail")
+        .expect("a code repository should be found");
+
+    assert_eq!(best.slug(), "hubot/engine");
+    assert_eq!(analysis.source_files, 2);
+    assert!(clone_root.join("rust-hubot-engine/main.rs").exists());
+    assert!(!clone_root.join("rust-octocat-engine").exists());
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[tokio::test]
+async fn repo_language_picks_clone_dir_and_source_extensions() {
+    let root = scratch_dir("clone-repo-language");
+    let remote_root = root.join("remote");
+    let clone_root = root.join("clones");
+
+    create_remote(
+        &remote_root,
+        "engine",
+        &["main.cpp", "util.cpp", "util.hpp", "shim.c"],
+    );
+
+    let mut repo = test_repo(1, "engine", 100);
+    repo.language = Some("C++".to_string());
+    let config = CloneConfig {
+        min_source_ratio: 0.01,
+        git_base_url: format!("file://{}", remote_root.display()),
+        use_repo_language: true,
+        ..CloneConfig::default()
+    };
+
+    let (_, analysis) = find_best_code_repo(&[repo], "C", &clone_root, &config)
+        .await
+        .expect("selection should not fail")
+        .expect("the repository should be selected");
+
+    // C++ extensions count the .cpp and .hpp files; the searched language's would only
+    // have counted shim.c.
+    assert_eq!(analysis.source_files, 3);
+    assert!(clone_root.join("c++-octocat-engine").exists());
+    assert!(!clone_root.join("c-octocat-engine").exists());
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[tokio::test]
+async fn clone_skips_repos_over_the_size_limit() {
+    let root = scratch_dir("clone-size-limit");
+    let remote_root = root.join("remote");
+    let clone_root = root.join("clones");
+
+    create_remote(&remote_root, "huge", &["main.rs", "lib.rs"]);
+    create_remote(&remote_root, "small", &["main.rs"]);
+
+    let mut huge = test_repo(1, "huge", 500);
+    huge.size = 4_000_000;
+    let mut small = test_repo(2, "small", 100);
+    small.size = 120;
+    let config = CloneConfig {
+        min_source_ratio: 0.01,
+        parallelism: 1,
+        git_base_url: format!("file://{}", remote_root.display()),
+        max_size_kb: Some(1024),
+        ..CloneConfig::default()
+    };
+
+    let (best, _) = find_best_code_repo(&[huge, small], "Rust", &clone_root, &config)
+        .await
+        .expect("selection should not fail")
+        .expect("the small repository should be selected");
+
+    assert_eq!(best.name, "small");
+    assert!(!clone_root.join("rust-octocat-huge").exists());
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[tokio::test]
+async fn clone_outcomes_explain_each_skipped_language() {
+    let root = scratch_dir("clone-outcomes");
+    let remote_root = root.join("remote");
+    let clone_root = root.join("clones");
+
+    create_remote(&remote_root, "engine", &["main.rs", "lib.rs"]);
+    create_remote(&remote_root, "docs", &["README.md", "GUIDE.md"]);
+
+    let mut huge = test_repo(4, "huge", 10);
+    huge.size = 4_000_000;
+    let reports: Vec<_> = [
+        ("Rust", vec![test_repo(1, "engine", 10)]),
+        ("Docs", vec![test_repo(2, "docs", 10)]),
+        ("Missing", vec![test_repo(3, "missing", 10)]),
+        ("Huge", vec![huge]),
+        ("Empty", Vec::new()),
+    ]
+    .into_iter()
+    .map(|(language, repos)| {
+        StatsCalculator::build_language_report_with_config(language, repos, &StatsConfig::default())
+    })
+    .collect();
+    let config = CloneConfig {
+        min_source_ratio: 0.01,
+        git_base_url: format!("file://{}", remote_root.display()),
+        max_size_kb: Some(1024),
+        ..CloneConfig::default()
+    };
+
+    let outcomes = clone_best_repos(&reports, &clone_root, &config)
+        .await
+        .expect("clone phase should not fail");
+
+    assert_eq!(outcomes.len(), 5);
+    assert_eq!(
+        outcomes[0].result.as_ref().map(|c| c.repo.name.as_str()),
+        Ok("engine")
+    );
+    assert_eq!(
+        outcomes[1].result.as_ref().err(),
+        Some(&CloneSkipReason::NoSourceCode)
+    );
+    assert!(matches!(
+        outcomes[2].result,
+        Err(CloneSkipReason::CloneFailed(_))
+    ));
+    assert_eq!(
+        outcomes[3].result.as_ref().err(),
+        Some(&CloneSkipReason::TooLarge)
+    );
+    assert_eq!(
+        outcomes[4].result.as_ref().err(),
+        Some(&CloneSkipReason::NoRepos)
+    );
+
+    let json = OutputFormatter::clone_outcomes_json(&outcomes);
+    let statuses: Vec<_> = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|o| {
+            o["reason"]
+                .as_str()
+                .unwrap_or(o["status"].as_str().unwrap())
+        })
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            "cloned",
+            "no_source_code",
+            "clone_failed",
+            "too_large",
+            "no_repos"
+        ]
+    );
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[tokio::test]
+async fn clone_outcomes_keep_failures_of_candidates_before_the_winner() {
+    let root = scratch_dir("clone-candidate-errors");
+    let remote_root = root.join("remote");
+    let clone_root = root.join("clones");
+
+    create_remote(&remote_root, "engine", &["main.rs", "lib.rs"]);
+
+    let repos = vec![test_repo(1, "missing", 20), test_repo(2, "engine", 10)];
+    let reports = vec![StatsCalculator::build_language_report_with_config(
+        "Rust",
+        repos,
+        &StatsConfig::default(),
+    )];
+    let config = CloneConfig {
+        min_source_ratio: 0.01,
+        git_base_url: format!("file://{}", remote_root.display()),
+        retries: 0,
+        ..CloneConfig::default()
+    };
+
+    let outcomes = clone_best_repos(&reports, &clone_root, &config)
+        .await
+        .expect("clone phase should not fail");
+
+    assert_eq!(
+        outcomes[0].result.as_ref().map(|c| c.repo.name.as_str()),
+        Ok("engine")
+    );
+    let errors = &outcomes[0].errors;
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].phase, Phase::Clone);
+    assert_eq!(errors[0].language, "Rust");
+    assert_eq!(errors[0].repo.as_deref(), Some("octocat/missing"));
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+/// Writes an executable stand-in for git that fails its first clone with a network
+/// error, leaving a partial directory behind, and succeeds on the next attempt.
+#[cfg(unix)]
+fn flaky_git(dir: &Path, stderr: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let script = dir.join("flaky-git.sh");
+    let attempts = dir.join("attempts");
+    std::fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\n\
+             target=\"$5\"\n\
+             echo attempt >> '{attempts}'\n\
+             mkdir -p \"$target\"\n\
+             if [ \"$(wc -l < '{attempts}')\" -eq 1 ]; then\n\
+             echo partial > \"$target/partial.txt\"\n\
+             echo 'fatal: {stderr}' >&2\n\
+             exit 128\n\
+             fi\n\
+             echo 'fn main() {{}}' > \"$target/main.rs\"\n",
+            attempts = attempts.display(),
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    script
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn clone_retries_after_network_error() {
+    let root = scratch_dir("clone-retry");
+    let clone_root = root.join("clones");
+    let git = flaky_git(
+        &root,
+        "unable to access: Could not resolve host: github.com",
+    );
+
+    let config = CloneConfig {
+        min_source_ratio: 0.01,
+        git_program: git.display().to_string(),
+        retry_delay: std::time::Duration::ZERO,
+        ..CloneConfig::default()
+    };
+
+    let (best, _) =
+        find_best_code_repo(&[test_repo(1, "engine", 10)], "Rust", &clone_root, &config)
+            .await
+            .expect("selection should not fail")
+            .expect("the second clone attempt should succeed");
+
+    assert_eq!(best.name, "engine");
+    let attempts = std::fs::read_to_string(root.join("attempts")).unwrap();
+    assert_eq!(attempts.lines().count(), 2);
+    assert!(clone_root.join("rust-octocat-engine/main.rs").exists());
+    assert!(!clone_root.join("rust-octocat-engine/partial.txt").exists());
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn clone_does_not_retry_other_failures() {
+    let root = scratch_dir("clone-no-retry");
+    let clone_root = root.join("clones");
+    let git = flaky_git(&root, "repository not found");
+
+    let config = CloneConfig {
+        git_program: git.display().to_string(),
+        retry_delay: std::time::Duration::ZERO,
+        ..CloneConfig::default()
+    };
+
+    let result = find_best_code_repo(&[test_repo(1, "engine", 10)], "Rust", &clone_root, &config)
+        .await
+        .expect("selection should not fail");
+
+    assert!(result.is_none());
+    let attempts = std::fs::read_to_string(root.join("attempts")).unwrap();
+    assert_eq!(attempts.lines().count(), 1);
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+/// Writes a stand-in for git that marks itself running in `dir/running` and logs how
+/// many clones are running when it starts, holding the process open for a moment
+/// before handing off to the real git.
+#[cfg(unix)]
+fn counting_git(dir: &Path) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let script = dir.join("counting-git.sh");
+    let running = dir.join("running");
+    std::fs::create_dir_all(&running).unwrap();
+    std::fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\n\
+             touch '{running}'/$$\n\
+             ls '{running}' | wc -l >> '{log}'\n\
+             sleep 0.2\n\
+             git \"$@\"\n\
+             status=$?\n\
+             rm '{running}'/$$\n\
+             exit $status\n",
+            running = running.display(),
+            log = dir.join("clones-running").display(),
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    script
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn clone_process_limit_bounds_concurrent_git_processes() {
+    let root = scratch_dir("clone-process-limit");
+    let remote_root = root.join("remote");
+    let clone_root = root.join("clones");
+    let git = counting_git(&root);
+
+    let names = ["a", "b", "c", "d", "e", "f"];
+    for name in names {
+        create_remote(&remote_root, name, &["main.rs"]);
+    }
+    let repos: Vec<Repo> = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| test_repo(i as i64, name, 100 - i as u64))
+        .collect();
+    let config = CloneConfig {
+        min_source_ratio: 0.01,
+        parallelism: names.len(),
+        git_base_url: format!("file://{}", remote_root.display()),
+        git_program: git.display().to_string(),
+        process_limit: 2,
+        ..CloneConfig::default()
+    };
+
+    find_best_code_repo(&repos, "Rust", &clone_root, &config)
+        .await
+        .expect("selection should not fail")
+        .expect("a repository should be selected");
+
+    let log = std::fs::read_to_string(root.join("clones-running")).unwrap();
+    let running: Vec<usize> = log.lines().map(|n| n.trim().parse().unwrap()).collect();
+    assert_eq!(running.len(), names.len());
+    assert_eq!(running.iter().max(), Some(&2));
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[tokio::test]
+async fn sequential_clone_returns_none_when_nothing_passes() {
+    let root = scratch_dir("sequential-clone");
+    let remote_root = root.join("remote");
+    let clone_root = root.join("clones");
+
+    create_remote(&remote_root, "docs", &["README.md"]);
+
+    let config = CloneConfig {
+        min_source_ratio: 0.01,
+        parallelism: 1,
+        git_base_url: format!("file://{}", remote_root.display()),
+        ..CloneConfig::default()
+    };
+
+    let result = find_best_code_repo(&[test_repo(1, "docs", 10)], "Rust", &clone_root, &config)
+        .await
+        .expect("selection should not fail");
+
+    assert!(result.is_none());
+    assert!(!clone_root.join("rust-octocat-docs").exists());
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[tokio::test]
+async fn weighted_selection_metric_prefers_code_heavy_repo() {
+    let root = scratch_dir("weighted-clone");
+    let remote_root = root.join("remote");
+
+    let docs_heavy: Vec<String> = (0..20).map(|i| format!("chapter{i}.md")).collect();
+    let mut popular_files: Vec<&str> = docs_heavy.iter().map(String::as_str).collect();
+    popular_files.push("main.rs");
+    create_remote(&remote_root, "popular", &popular_files);
+    let code_files: Vec<String> = (0..10).map(|i| format!("mod{i}.rs")).collect();
+    let code_files: Vec<&str> = code_files.iter().map(String::as_str).collect();
+    create_remote(&remote_root, "compact", &code_files);
+
+    let repos = vec![test_repo(1, "popular", 200), test_repo(2, "compact", 150)];
+    let mut winners = Vec::new();
+
+    for metric in [SelectionMetric::Stars, SelectionMetric::StarsTimesRatio] {
+        let clone_root = root.join(format!("clones-{metric:?}"));
+        let config = CloneConfig {
+            min_source_ratio: 0.01,
+            git_base_url: format!("file://{}", remote_root.display()),
+            selection_metric: metric,
+            ..CloneConfig::default()
+        };
+
+        let (best, _) = find_best_code_repo(&repos, "Rust", &clone_root, &config)
+            .await
+            .expect("selection should not fail")
+            .expect("a code repository should be found");
+        winners.push(best.name);
+    }
+
+    assert_eq!(winners, vec!["popular", "compact"]);
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+/// Keeps the candidate with the most source files, however few stars it has.
+struct MostSourceFiles;
+
+impl RepoSelector for MostSourceFiles {
+    fn select(&self, candidates: &[Repo], analyses: &[CodeAnalysis]) -> Option<Repo> {
+        candidates
+            .iter()
+            .zip(analyses)
+            .max_by_key(|(_, analysis)| analysis.source_files)
+            .map(|(repo, _)| repo.clone())
+    }
+}
+
+#[tokio::test]
+async fn custom_selector_overrides_default_choice() {
+    let root = scratch_dir("custom-selector");
+    let remote_root = root.join("remote");
+
+    create_remote(&remote_root, "popular", &["main.rs"]);
+    create_remote(&remote_root, "sprawling", &["a.rs", "b.rs", "c.rs"]);
+
+    let repos = vec![test_repo(1, "popular", 200), test_repo(2, "sprawling", 5)];
+    let config = CloneConfig {
+        min_source_ratio: 0.01,
+        git_base_url: format!("file://{}", remote_root.display()),
+        ..CloneConfig::default()
+    };
+
+    let default_root = root.join("clones-default");
+    let (default_pick, _) =
+        select_code_repo_with(&repos, "Rust", &default_root, &config, &DefaultSelector)
+            .await
+            .expect("default selector should find a repo");
+    let custom_root = root.join("clones-custom");
+    let (custom_pick, analysis) =
+        select_code_repo_with(&repos, "Rust", &custom_root, &config, &MostSourceFiles)
+            .await
+            .expect("custom selector should find a repo");
+
+    assert_eq!(default_pick.name, "popular");
+    assert_eq!(custom_pick.name, "sprawling");
+    assert_eq!(analysis.source_files, 3);
+    assert!(custom_root.join("rust-octocat-sprawling").exists());
+    assert!(!custom_root.join("rust-octocat-popular").exists());
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+fn write_tree(root: &Path, files: &[&str]) {
+    for file in files {
+        let path = root.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "content").unwrap();
+    }
+}
+
+#[test]
+fn check_for_source_code_detects_monorepo() {
+    let root = scratch_dir("monorepo");
+    write_tree(
+        &root,
+        &[
+            "Cargo.toml",
+            "crates/core/Cargo.toml",
+            "crates/core/src/lib.rs",
+            "web/package.json",
+            "web/index.js",
+        ],
+    );
+
+    let analysis = check_for_source_code(&root, &CodeDetectionRules::default()).unwrap();
+
+    assert!(analysis.is_monorepo);
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn check_for_source_code_counts_files_per_extension() {
+    let root = scratch_dir("extension-histogram");
+    write_tree(
+        &root,
+        &[
+            "src/main.rs",
+            "src/lib.rs",
+            "src/util/MOD.RS",
+            "Cargo.toml",
+            "docs/guide.md",
+            "Makefile",
+        ],
+    );
+
+    let analysis = check_for_source_code(&root, &CodeDetectionRules::default()).unwrap();
+
+    assert_eq!(analysis.file_extensions.len(), 3);
+    assert_eq!(analysis.file_extensions["rs"], 3);
+    assert_eq!(analysis.file_extensions["toml"], 1);
+    assert_eq!(analysis.file_extensions["md"], 1);
+    assert_eq!(
+        analysis.extensions_by_count(),
+        vec![("rs", 3), ("md", 1), ("toml", 1)]
+    );
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn check_for_source_code_splits_test_files() {
+    let root = scratch_dir("test-split");
+    write_tree(
+        &root,
+        &[
+            "src/lib.rs",
+            "tests/api.rs",
+            "tests/fixtures/data.json",
+            "tests/README.md",
+            "crates/core/test/unit.rs",
+            "README.md",
+            "LICENSE",
+        ],
+    );
+
+    let mixed = check_for_source_code(&root, &CodeDetectionRules::default()).unwrap();
+    let rules = CodeDetectionRules {
+        exclude_tests_from_ratio: true,
+        ..CodeDetectionRules::default()
+    };
+    let production_only = check_for_source_code(&root, &rules).unwrap();
+
+    assert_eq!(mixed.source_files, 4);
+    assert_eq!(mixed.test_files, 3);
+    assert_eq!(mixed.production_files, 1);
+    assert!((mixed.source_ratio - 4.0 / 7.0).abs() < 1e-9);
+    // Only src/lib.rs, README.md and LICENSE remain outside the test directories.
+    assert!((production_only.source_ratio - 1.0 / 3.0).abs() < 1e-9);
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn check_for_source_code_single_project_is_not_monorepo() {
+    let root = scratch_dir("single-project");
+    write_tree(
+        &root,
+        &["Cargo.toml", "src/main.rs", "examples/demo/Cargo.toml"],
+    );
+
+    let analysis = check_for_source_code(&root, &CodeDetectionRules::default()).unwrap();
+
+    assert!(!analysis.is_monorepo);
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn declared_dependencies_are_read_from_cargo_toml() {
+    let dir = scratch_dir("deps-cargo");
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        r#"
+[package]
+name = "demo"
+version = "0.1.0"
+
+[dependencies]
+serde = { version = "1", features = ["derive"] } # comment
+clap = { version = "4", features = [
+    "derive",
+], default-features = false }
+axum = {
+    version = "0.7",
+    features = ["macros"] }
+tokio.workspace = true
+"quoted-name" = "0.3"
+
+[dev-dependencies]
+httpmock = "0.7"
+
+[target.'cfg(unix)'.dependencies]
+libc = "0.2"
+
+[dependencies.reqwest]
+version = "0.12"
+"#,
+    )
+    .unwrap();
+
+    let deps = declared_dependencies(&dir);
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(
+        deps,
+        vec![ManifestDependencies {
+            manifest: "Cargo.toml",
+            names: [
+                "serde",
+                "clap",
+                "axum",
+                "tokio",
+                "quoted-name",
+                "libc",
+                "reqwest"
+            ]
+            .map(String::from)
+            .to_vec(),
+        }]
+    );
+}
+
+#[test]
+fn declared_dependencies_tolerate_malformed_manifests() {
+    let dir = scratch_dir("deps-malformed");
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        "[dependencies\nserde = \"1\"\n[[bin]",
+    )
+    .unwrap();
+    std::fs::write(dir.join("package.json"), "{ \"dependencies\": ").unwrap();
+    std::fs::write(
+        dir.join("pom.xml"),
+        "<dependency><groupId>junit</groupId><artifactId> junit </artifactId></dependency>\
+         <dependency><artifactId>guava",
+    )
+    .unwrap();
+
+    let deps = declared_dependencies(&dir);
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let names: Vec<_> = deps.iter().map(|d| (d.manifest, d.names.clone())).collect();
+    assert_eq!(
+        names,
+        vec![
+            ("Cargo.toml", vec!["serde".to_string()]),
+            ("package.json", vec![]),
+            ("pom.xml", vec!["junit".to_string()]),
+        ]
+    );
+}
+
+#[test]
+fn declared_dependencies_read_package_json() {
+    let dir = scratch_dir("deps-npm");
+    std::fs::write(
+        dir.join("package.json"),
+        r#"{ "name": "app", "dependencies": { "react": "^18", "lodash": "4" } }"#,
+    )
+    .unwrap();
+
+    let deps = declared_dependencies(&dir);
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(deps.len(), 1);
+    assert_eq!(deps[0].manifest, "package.json");
+    assert_eq!(deps[0].names, vec!["lodash", "react"]);
+}
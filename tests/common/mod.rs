@@ -0,0 +1,64 @@
+//! Helpers shared by the integration tests.
+
+/// A parsed sample: metric name, labels in order, value.
+pub type Sample = (String, Vec<(String, String)>, f64);
+
+/// Checks `text` against the Prometheus text exposition format and returns the samples
+/// as `(name, labels, value)`. Panics on the first malformed line.
+pub fn parse_prometheus(text: &str) -> Vec<Sample> {
+    let is_name = |name: &str| {
+        name.chars().enumerate().all(|(i, c)| {
+            c.is_ascii_alphabetic() || c == '_' || c == ':' || (i > 0 && c.is_ascii_digit())
+        }) && !name.is_empty()
+    };
+    let mut typed = std::collections::HashSet::new();
+    let mut samples = Vec::new();
+
+    for line in text.lines().filter(|l| !l.is_empty()) {
+        if let Some(comment) = line.strip_prefix("# ") {
+            let mut parts = comment.splitn(3, ' ');
+            let kind = parts.next().unwrap();
+            let name = parts.next().expect("comment names a metric");
+            assert!(is_name(name), "bad metric name in {line:?}");
+            if kind == "TYPE" {
+                assert_eq!(parts.next(), Some("gauge"), "{line:?}");
+                assert!(typed.insert(name.to_string()), "duplicate TYPE for {name}");
+            }
+            continue;
+        }
+
+        let (series, value) = line.rsplit_once(' ').expect("sample has a value");
+        let value: f64 = value.parse().expect("sample value is a number");
+        let (name, labels) = match series.split_once('{') {
+            Some((name, rest)) => (name, rest.strip_suffix('}').expect("labels are closed")),
+            None => (series, ""),
+        };
+        assert!(is_name(name), "bad metric name in {line:?}");
+        assert!(typed.contains(name), "{name} sampled before its TYPE line");
+
+        let mut parsed = Vec::new();
+        let mut rest = labels;
+        while !rest.is_empty() {
+            let (key, after) = rest.split_once("=\"").expect("label is key=\"value\"");
+            assert!(is_name(key), "bad label name in {line:?}");
+            let mut value = String::new();
+            let mut chars = after.char_indices();
+            let end = loop {
+                match chars.next().expect("label value is closed") {
+                    (_, '\\') => value.push(match chars.next().unwrap().1 {
+                        'n' => '\n',
+                        c => c,
+                    }),
+                    (i, '"') => break i,
+                    (_, c) => value.push(c),
+                }
+            };
+            parsed.push((key.to_string(), value));
+            rest = after[end + 1..]
+                .strip_prefix(',')
+                .unwrap_or(&after[end + 1..]);
+        }
+        samples.push((name.to_string(), parsed, value));
+    }
+    samples
+}
@@ -0,0 +1,171 @@
+//! Configuration loading tests.
+use std::collections::HashMap;
+
+use ecs160_hw1::config::{AppConfig, ConfigSource, Part, Parts};
+use ecs160_hw1::error::AppError;
+
+struct MapSource(HashMap<&'static str, &'static str>);
+
+impl MapSource {
+    fn new(pairs: &[(&'static str, &'static str)]) -> Self {
+        Self(pairs.iter().copied().collect())
+    }
+}
+
+impl ConfigSource for MapSource {
+    fn get(&self, key: &str) -> Option<String> {
+        self.0.get(key).map(|v| v.to_string())
+    }
+}
+
+#[test]
+fn search_qualifiers_are_loaded() {
+    let source = MapSource::new(&[("SEARCH_QUALIFIERS", " stars:>100 pushed:>2024-01-01 ")]);
+
+    let config = AppConfig::from_source(&source).expect("config should load");
+
+    assert_eq!(
+        config.github.search_qualifiers.as_deref(),
+        Some("stars:>100 pushed:>2024-01-01")
+    );
+}
+
+#[test]
+fn search_qualifiers_reject_newlines() {
+    let source = MapSource::new(&[("SEARCH_QUALIFIERS", "stars:>100\nfoo")]);
+
+    let err = AppConfig::from_source(&source).expect_err("newlines should be rejected");
+
+    assert!(matches!(err, AppError::Config(_)));
+}
+
+#[test]
+fn created_dates_must_be_dates_or_rfc3339() {
+    let valid = MapSource::new(&[
+        ("CREATED_AFTER", "2023-01-01"),
+        ("CREATED_BEFORE", "2023-12-31T23:59:59+02:00"),
+    ]);
+    let config = AppConfig::from_source(&valid).unwrap();
+    assert_eq!(config.github.created_after.as_deref(), Some("2023-01-01"));
+
+    let invalid = MapSource::new(&[("CREATED_AFTER", "last year")]);
+    let err = AppConfig::from_source(&invalid).expect_err("free text should be rejected");
+    assert!(matches!(err, AppError::Config(_)));
+}
+
+#[test]
+fn required_token_missing_fails_fast() {
+    let source = MapSource::new(&[("REQUIRE_TOKEN", "true")]);
+
+    let config = AppConfig::from_source(&source).expect("config should load");
+
+    assert!(matches!(
+        config.github.check_token_requirement(),
+        Err(AppError::MissingGitHubToken)
+    ));
+}
+
+#[test]
+fn required_token_present_or_optional_passes() {
+    let with_token = MapSource::new(&[("REQUIRE_TOKEN", "yes"), ("GITHUB_TOKEN", "ghp_test")]);
+    let optional = MapSource::new(&[]);
+
+    for source in [with_token, optional] {
+        let config = AppConfig::from_source(&source).expect("config should load");
+        assert!(config.github.check_token_requirement().is_ok());
+    }
+}
+
+#[test]
+fn language_aliases_include_built_ins_and_overrides() {
+    let source = MapSource::new(&[("LANGUAGE_ALIASES", "cpp=C, Zig-Lang = Zig")]);
+    let config = AppConfig::from_source(&source).unwrap();
+    let aliases = &config.github.language_aliases;
+
+    assert_eq!(aliases.resolve("cpp"), "C");
+    assert_eq!(aliases.resolve("zig-lang"), "Zig");
+    assert_eq!(aliases.resolve("GoLang"), "Go");
+    assert_eq!(aliases.resolve("Haskell"), "Haskell");
+}
+
+#[test]
+fn language_aliases_reject_entries_without_a_name() {
+    let source = MapSource::new(&[("LANGUAGE_ALIASES", "cpp=")]);
+    let err = AppConfig::from_source(&source).unwrap_err();
+
+    assert!(matches!(err, AppError::Config(msg) if msg.contains("cpp=")));
+}
+
+#[test]
+fn per_page_sizes_are_loaded() {
+    let defaults = AppConfig::from_source(&MapSource::new(&[])).unwrap();
+    assert_eq!(defaults.github.commits_per_page, 50);
+    assert_eq!(defaults.github.issues_per_page, 100);
+    assert_eq!(defaults.github.forks_per_page, 100);
+
+    let source = MapSource::new(&[
+        ("GITHUB_COMMITS_PER_PAGE", "100"),
+        ("GITHUB_ISSUES_PER_PAGE", "25"),
+        ("GITHUB_FORKS_PER_PAGE", "lots"),
+    ]);
+    let config = AppConfig::from_source(&source).unwrap();
+    assert_eq!(config.github.commits_per_page, 100);
+    assert_eq!(config.github.issues_per_page, 25);
+    assert_eq!(config.github.forks_per_page, 100);
+}
+
+#[test]
+fn max_concurrency_is_loaded() {
+    let defaults = AppConfig::from_source(&MapSource::new(&[])).unwrap();
+    assert_eq!(defaults.github.max_concurrency, 8);
+
+    let config = AppConfig::from_source(&MapSource::new(&[("GITHUB_MAX_CONCURRENCY", "3")]));
+    assert_eq!(config.unwrap().github.max_concurrency, 3);
+
+    for invalid in ["0", "many"] {
+        let source = MapSource::new(&[("GITHUB_MAX_CONCURRENCY", invalid)]);
+        assert_eq!(
+            AppConfig::from_source(&source)
+                .unwrap()
+                .github
+                .max_concurrency,
+            8
+        );
+    }
+
+    // The deprecated name still applies, unless the new one is set too.
+    let old = MapSource::new(&[("FORK_COMMIT_BATCH_SIZE", "5")]);
+    assert_eq!(
+        AppConfig::from_source(&old).unwrap().github.max_concurrency,
+        5
+    );
+    let both = MapSource::new(&[
+        ("FORK_COMMIT_BATCH_SIZE", "5"),
+        ("GITHUB_MAX_CONCURRENCY", "2"),
+    ]);
+    assert_eq!(
+        AppConfig::from_source(&both)
+            .unwrap()
+            .github
+            .max_concurrency,
+        2
+    );
+}
+
+#[test]
+fn parts_select_pipeline_stages() {
+    let all = AppConfig::from_source(&MapSource::new(&[])).unwrap();
+    assert!(all.parts.contains(Part::Store));
+
+    let source = MapSource::new(&[("PARTS", "a, B")]);
+    let parts = AppConfig::from_source(&source).unwrap().parts;
+    assert!(parts.contains(Part::Fetch));
+    assert!(parts.contains(Part::Stats));
+    assert!(!parts.contains(Part::Clone));
+    assert!(!parts.contains(Part::Store));
+
+    for invalid in ["C", "A,D", "A,E", ""] {
+        let err = invalid.parse::<Parts>().expect_err(invalid);
+        assert!(matches!(err, AppError::Config(_)), "{invalid:?}");
+    }
+}
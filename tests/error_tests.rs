@@ -0,0 +1,68 @@
+//! Error tests.
+use ecs160_hw1::error::AppError;
+
+#[test]
+fn exit_codes_follow_error_category() {
+    let http = reqwest::Client::new()
+        .get("not a url")
+        .build()
+        .expect_err("invalid url should fail to build");
+    let json = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+
+    let cases = [
+        (AppError::Config("bad".to_string()), 2),
+        (AppError::MissingGitHubToken, 3),
+        (AppError::RateLimited { reset_in_secs: 60 }, 3),
+        (
+            AppError::GitHubApi {
+                status: 401,
+                message: "401 Unauthorized from /user".to_string(),
+            },
+            3,
+        ),
+        (
+            AppError::GitHubApi {
+                status: 403,
+                message: "403 Forbidden from /search/repositories: API rate limit exceeded"
+                    .to_string(),
+            },
+            3,
+        ),
+        (
+            AppError::GitHubApi {
+                status: 429,
+                message: "429 Too Many Requests from /repos/a/b".to_string(),
+            },
+            3,
+        ),
+        (
+            AppError::GitHubApi {
+                status: 404,
+                message: "404 Not Found from /repos/a/b".to_string(),
+            },
+            4,
+        ),
+        (AppError::Http(http), 4),
+        (AppError::MalformedLinkHeader("garbage".to_string()), 4),
+        (
+            AppError::RepositoryUnavailable {
+                repo: "a/b".to_string(),
+                status: 451,
+            },
+            4,
+        ),
+        (AppError::Redis("connection refused".to_string()), 5),
+        (AppError::Serialization(json), 1),
+        (AppError::Io(std::io::Error::other("disk")), 1),
+        (AppError::Git("clone failed".to_string()), 1),
+        (AppError::NotImplemented, 1),
+    ];
+
+    for (error, expected) in cases {
+        assert_eq!(
+            error.exit_code(),
+            expected,
+            "unexpected exit code for {error}"
+        );
+    }
+}
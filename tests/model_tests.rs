@@ -0,0 +1,206 @@
+//! Model parsing tests.
+use std::collections::HashSet;
+
+use ecs160_hw1::model::{Commit, Issue, Owner, Repo};
+use serde_json::json;
+
+fn sample_repo_json() -> serde_json::Value {
+    json!({
+        "id": 42,
+        "name": "repo-one",
+        "full_name": "octocat/repo-one",
+        "html_url": "https://example.com/repo-one",
+        "forks_count": 5,
+        "stargazers_count": 100,
+        "open_issues_count": 7,
+        "language": "Rust",
+        "owner": {
+            "login": "octocat",
+            "id": 1,
+            "html_url": "https://github.com/octocat",
+            "site_admin": false
+        }
+    })
+}
+
+#[test]
+fn repo_from_json_parses_archived_flag() {
+    let mut value = sample_repo_json();
+    value["archived"] = json!(true);
+
+    let repo = Repo::from_json(&value).expect("repo should parse");
+
+    assert!(repo.archived);
+}
+
+#[test]
+fn repo_from_json_defaults_archived_to_false() {
+    let repo = Repo::from_json(&sample_repo_json()).expect("repo should parse");
+
+    assert!(!repo.archived);
+}
+
+#[test]
+fn repo_equality_and_hash_use_id_only() {
+    let repo = Repo::from_json(&sample_repo_json()).expect("repo should parse");
+    let mut enriched = repo.clone();
+    enriched.commit_count = 12;
+    enriched.stargazers_count = 101;
+
+    let mut other = repo.clone();
+    other.id = 43;
+
+    let set: HashSet<Repo> = [repo.clone(), enriched.clone(), other.clone()]
+        .into_iter()
+        .collect();
+
+    assert_eq!(repo, enriched);
+    assert_ne!(repo, other);
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn repo_builder_fills_defaults_and_derived_fields() {
+    let repo = Repo::builder()
+        .name("engine")
+        .owner("octocat")
+        .stars(7)
+        .build();
+
+    assert_eq!(repo.id, 0);
+    assert_eq!(repo.slug(), "octocat/engine");
+    assert_eq!(repo.full_name, "octocat/engine");
+    assert_eq!(repo.html_url, "https://github.com/octocat/engine");
+    assert_eq!(repo.owner.html_url, "https://github.com/octocat");
+    assert_eq!(repo.stargazers_count, 7);
+    assert_eq!(repo.forks_count, 0);
+    assert!(repo.has_issues);
+    assert!(!repo.archived);
+    assert!(!repo.unavailable);
+    assert!(repo.language.is_none());
+    assert!(repo.forks.is_empty() && repo.issues.is_empty());
+    assert_eq!(repo.commit_count, 0);
+}
+
+#[test]
+fn owner_from_json_parses_avatar_url() {
+    let owner = Owner::from_json(&json!({
+        "login": "octocat",
+        "id": 1,
+        "html_url": "https://github.com/octocat",
+        "site_admin": false,
+        "avatar_url": "https://avatars.githubusercontent.com/u/1?v=4"
+    }))
+    .expect("owner should parse");
+
+    assert_eq!(
+        owner.avatar_url.as_deref(),
+        Some("https://avatars.githubusercontent.com/u/1?v=4")
+    );
+}
+
+#[test]
+fn owner_from_json_without_avatar_url_is_none() {
+    let repo = Repo::from_json(&sample_repo_json()).expect("repo should parse");
+
+    assert!(repo.owner.avatar_url.is_none());
+}
+
+fn sample_issue_json() -> serde_json::Value {
+    json!({
+        "id": 1001,
+        "number": 1,
+        "title": "Bug report",
+        "body": "Something broke",
+        "state": "open",
+        "created_at": "2024-01-02T00:00:00Z",
+        "updated_at": "2024-01-02T00:00:00Z"
+    })
+}
+
+#[test]
+fn issue_from_json_parses_user_login() {
+    let mut value = sample_issue_json();
+    value["user"] = json!({ "login": "reporter", "id": 7 });
+
+    let issue = Issue::from_json(&value).expect("issue should parse");
+
+    assert_eq!(issue.user_login.as_deref(), Some("reporter"));
+}
+
+#[test]
+fn issue_from_json_without_user_has_no_login() {
+    let issue = Issue::from_json(&sample_issue_json()).expect("issue should parse");
+
+    assert!(issue.user_login.is_none());
+}
+
+#[test]
+fn issue_from_json_parses_comments() {
+    let mut value = sample_issue_json();
+    value["comments"] = json!(14);
+
+    let issue = Issue::from_json(&value).expect("issue should parse");
+
+    assert_eq!(issue.comments, 14);
+}
+
+#[test]
+fn issue_from_json_defaults_comments_to_zero() {
+    let issue = Issue::from_json(&sample_issue_json()).expect("issue should parse");
+
+    assert_eq!(issue.comments, 0);
+    assert!(!issue.is_pull_request);
+
+    let mut value = sample_issue_json();
+    value["pull_request"] = json!({ "url": "https://api.github.com/repos/o/r/pulls/1" });
+    assert!(Issue::from_json(&value).unwrap().is_pull_request);
+}
+
+#[test]
+fn commit_from_json_parses_parents() {
+    let commit = Commit::from_json(&json!({
+        "sha": "m3rge",
+        "commit": { "message": "Merge branch 'feature'" },
+        "parents": [
+            { "sha": "aaa111", "url": "https://api.github.com/commits/aaa111" },
+            { "sha": "bbb222", "url": "https://api.github.com/commits/bbb222" }
+        ]
+    }))
+    .expect("commit should parse");
+
+    assert_eq!(commit.parents, vec!["aaa111", "bbb222"]);
+    assert!(commit.is_merge());
+}
+
+#[test]
+fn commit_from_json_without_parents_is_not_a_merge() {
+    let commit = Commit::from_json(&json!({
+        "sha": "abc123",
+        "commit": { "message": "Initial commit" }
+    }))
+    .expect("commit should parse");
+
+    assert!(commit.parents.is_empty());
+    assert!(!commit.is_merge());
+}
+
+#[test]
+fn repo_to_repo_data_maps_summary_fields() {
+    let repo = Repo::from_json(&sample_repo_json()).unwrap();
+
+    let data = repo.to_repo_data();
+
+    assert_eq!(data.url, "https://example.com/repo-one");
+    assert_eq!(data.name, "repo-one");
+    assert_eq!(data.owner, "octocat");
+    assert_eq!(data.language, "Rust");
+    assert_eq!(data.stars, 100);
+    assert_eq!(data.forks, 5);
+    assert_eq!(data.open_issues, 7);
+
+    let mut value = sample_repo_json();
+    value["language"] = json!(null);
+    let unknown = Repo::from_json(&value).unwrap().to_repo_data();
+    assert_eq!(unknown.language, "unknown");
+}
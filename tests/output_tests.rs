@@ -0,0 +1,296 @@
+//! Output formatting tests.
+mod common;
+
+use ecs160_hw1::app::clone::ClonedRepo;
+use ecs160_hw1::app::output::{report_file_stem, OutputFormatter};
+use ecs160_hw1::app::phase_error::{Phase, PhaseError};
+use ecs160_hw1::app::stats::StatsCalculator;
+use ecs160_hw1::app::LanguageReport;
+use ecs160_hw1::config::{OutputSort, StatsConfig, VerboseCommits};
+use ecs160_hw1::model::{CodeAnalysis, Repo};
+use ecs160_hw1::model::{Commit, CommitFile};
+use serde_json::json;
+
+fn test_repo(id: i64, name: &str, stars: u64) -> Repo {
+    Repo::from_json(&json!({
+        "id": id,
+        "name": name,
+        "full_name": format!("octocat/{name}"),
+        "html_url": format!("https://example.com/{name}"),
+        "stargazers_count": stars,
+        "owner": {
+            "login": "octocat",
+            "id": 1,
+            "html_url": "https://github.com/octocat",
+            "site_admin": false
+        }
+    }))
+    .expect("test repo should parse")
+}
+
+fn report(language: &str, stars: u64) -> LanguageReport {
+    StatsCalculator::build_language_report(language, vec![test_repo(1, language, stars)])
+}
+
+fn languages(reports: &[LanguageReport]) -> Vec<&str> {
+    reports.iter().map(|r| r.language.as_str()).collect()
+}
+
+#[test]
+fn sort_reports_by_stars_puts_most_starred_first() {
+    let mut reports = vec![report("Java", 100), report("Rust", 500)];
+
+    OutputFormatter::sort_reports(&mut reports, OutputSort::Stars);
+
+    assert_eq!(languages(&reports), vec!["Rust", "Java"]);
+}
+
+#[test]
+fn sort_reports_config_order_is_unchanged() {
+    let mut reports = vec![report("Rust", 100), report("C", 500)];
+
+    OutputFormatter::sort_reports(&mut reports, OutputSort::Config);
+
+    assert_eq!(languages(&reports), vec!["Rust", "C"]);
+}
+
+#[test]
+fn sort_reports_alphabetical() {
+    let mut reports = vec![report("Rust", 100), report("C", 50), report("Java", 500)];
+
+    OutputFormatter::sort_reports(&mut reports, OutputSort::Alphabetical);
+
+    assert_eq!(languages(&reports), vec!["C", "Java", "Rust"]);
+}
+
+fn commit_touching(files: &[(&str, i64)]) -> Commit {
+    let mut commit = Commit::from_json(&json!({
+        "sha": "abc123",
+        "commit": { "message": "change" }
+    }))
+    .expect("test commit should parse");
+    commit.files = files
+        .iter()
+        .map(|(name, changes)| CommitFile {
+            filename: name.to_string(),
+            additions: *changes,
+            deletions: 0,
+            changes: *changes,
+            status: "modified".to_string(),
+            previous_filename: None,
+        })
+        .collect();
+    commit
+}
+
+#[test]
+fn summary_reports_configured_number_of_top_files() {
+    let mut repo = test_repo(1, "engine", 100);
+    repo.recent_commits = vec![commit_touching(&[
+        ("a.rs", 60),
+        ("b.rs", 50),
+        ("c.rs", 40),
+        ("d.rs", 30),
+        ("e.rs", 20),
+        ("f.rs", 10),
+    ])];
+    let config = StatsConfig {
+        top_files_count: 5,
+        ..StatsConfig::default()
+    };
+
+    let report = StatsCalculator::build_language_report_with_config("Rust", vec![repo], &config);
+    let summary = OutputFormatter::format_summary(&report);
+
+    assert_eq!(
+        report.repo_metrics[0].top_files,
+        vec!["a.rs", "b.rs", "c.rs", "d.rs", "e.rs"]
+    );
+    assert!(summary.contains("Top-5 Most modified file per repo:"));
+    assert!(summary.contains("    File name1: a.rs"));
+    assert!(summary.contains("    File name5: e.rs"));
+    assert!(!summary.contains("File name6"));
+}
+
+fn report_with_commits() -> LanguageReport {
+    let mut repo = test_repo(1, "engine", 100);
+    let mut second = commit_touching(&[("b.rs", 3)]);
+    second.sha = "def456".to_string();
+    second.files[0].deletions = 2;
+    repo.recent_commits = vec![commit_touching(&[("a.rs", 5), ("c.rs", 1)]), second];
+    StatsCalculator::build_language_report("Rust", vec![repo])
+}
+
+#[test]
+fn commit_details_text_lists_each_commit_and_file() {
+    let report = report_with_commits();
+
+    let details = OutputFormatter::format_commit_details(&report, VerboseCommits::Text)
+        .expect("text mode should produce output");
+
+    assert_eq!(
+        details,
+        "Commits for octocat/engine:\n  abc123\n    a.rs (+5 -0)\n    c.rs (+1 -0)\n  def456\n    b.rs (+3 -2)\n"
+    );
+}
+
+#[test]
+fn commit_details_json_matches_input_commits() {
+    let report = report_with_commits();
+
+    let details = OutputFormatter::format_commit_details(&report, VerboseCommits::Json)
+        .expect("json mode should produce output");
+    let parsed: serde_json::Value = serde_json::from_str(&details).expect("valid json");
+
+    let commits = &parsed[0]["commits"];
+    assert_eq!(parsed[0]["repo"], "octocat/engine");
+    assert_eq!(commits.as_array().unwrap().len(), 2);
+    assert_eq!(commits[0]["sha"], "abc123");
+    assert_eq!(commits[0]["files"][1]["filename"], "c.rs");
+    assert_eq!(commits[1]["files"][0]["deletions"], 2);
+}
+
+#[test]
+fn commit_details_off_prints_nothing() {
+    let report = report_with_commits();
+
+    assert!(OutputFormatter::format_commit_details(&report, VerboseCommits::Off).is_none());
+}
+
+#[test]
+fn report_file_stem_is_filesystem_safe() {
+    assert_eq!(report_file_stem("C++"), "Cpp");
+    assert_eq!(report_file_stem("C#"), "CSharp");
+    assert_eq!(report_file_stem("Rust"), "Rust");
+    assert_eq!(report_file_stem("../Objective C"), "___Objective_C");
+}
+
+#[test]
+fn write_report_files_creates_one_file_per_language() {
+    let dir = std::env::temp_dir().join(format!("ecs160-reports-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let reports = vec![report("Rust", 30), report("C++", 20)];
+
+    let paths = OutputFormatter::write_report_files(&reports, &dir).unwrap();
+
+    assert_eq!(paths, vec![dir.join("Rust.json"), dir.join("Cpp.json")]);
+    for (path, expected) in paths.iter().zip(&reports) {
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(written, OutputFormatter::report_json(expected));
+        assert_eq!(written["language"], expected.language.as_str());
+        assert_eq!(written["total_stars"], expected.total_stars);
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn report_json_includes_the_owner_avatar() {
+    let mut with_avatar = test_repo(1, "engine", 30);
+    with_avatar.owner.avatar_url = Some("https://avatars.githubusercontent.com/u/1?v=4".into());
+    let report =
+        StatsCalculator::build_language_report("Rust", vec![with_avatar, test_repo(2, "docs", 10)]);
+
+    let json = OutputFormatter::report_json(&report);
+
+    assert_eq!(
+        json["repos"][0]["owner_avatar_url"],
+        "https://avatars.githubusercontent.com/u/1?v=4"
+    );
+    assert!(json["repos"][1]["owner_avatar_url"].is_null());
+}
+
+#[test]
+fn prometheus_output_is_valid_exposition_text() {
+    let mut rust = report("Rust", 123);
+    rust.repos[0].forks_count = 7;
+    let reports = vec![rust, report("C\"++", 20)];
+
+    let text = OutputFormatter::format_prometheus(&reports);
+    let samples = common::parse_prometheus(&text);
+
+    let find = |name: &str, language: &str| {
+        samples
+            .iter()
+            .find(|(n, labels, _)| {
+                n == name && labels.iter().any(|(k, v)| k == "language" && v == language)
+            })
+            .unwrap_or_else(|| panic!("missing {name} for {language}"))
+    };
+    let (_, labels, stars) = find("github_repo_stars", "Rust");
+    assert_eq!(*stars, 123.0);
+    assert_eq!(
+        labels,
+        &vec![
+            ("language".to_string(), "Rust".to_string()),
+            ("repo".to_string(), "octocat/Rust".to_string()),
+        ]
+    );
+    assert_eq!(find("github_repo_forks", "Rust").2, 7.0);
+    assert_eq!(find("github_language_stars", "Rust").2, 123.0);
+    assert_eq!(find("github_language_stars", "C\"++").2, 20.0);
+    assert!(text.contains("# HELP github_language_stars "));
+}
+
+#[test]
+fn winner_block_names_the_cloned_repo() {
+    let report = StatsCalculator::build_language_report(
+        "Rust",
+        vec![test_repo(1, "popular", 900), test_repo(2, "source", 400)],
+    );
+    let cloned = ClonedRepo {
+        repo: report.repos[1].clone(),
+        analysis: CodeAnalysis {
+            source_files: 40,
+            total_files: 50,
+            source_ratio: 0.8,
+            is_source_code_repo: true,
+            file_extensions: Default::default(),
+            is_monorepo: false,
+            test_files: 10,
+            production_files: 30,
+        },
+    };
+
+    let block = OutputFormatter::format_winner(&report, Some(&cloned)).unwrap();
+
+    assert!(block.contains("Winner for Rust: octocat/source"), "{block}");
+    assert!(block.contains("Stars: 400"), "{block}");
+    assert!(
+        block.contains("Source files: 40 (80.0% of files)"),
+        "{block}"
+    );
+}
+
+#[test]
+fn winner_block_falls_back_to_the_most_starred_repo() {
+    let report = StatsCalculator::build_language_report(
+        "Rust",
+        vec![test_repo(1, "small", 10), test_repo(2, "popular", 900)],
+    );
+
+    let block = OutputFormatter::format_winner(&report, None).unwrap();
+
+    assert!(
+        block.contains("Winner for Rust: octocat/popular (most starred)"),
+        "{block}"
+    );
+    assert!(!block.contains("Source files"), "{block}");
+}
+
+#[test]
+fn phase_errors_get_one_header_per_language() {
+    let errors = vec![
+        PhaseError::new(Phase::Forks, Some("octocat/a".to_string()), "500").in_language("Rust"),
+        PhaseError::new(Phase::Search, None, "timeout").in_language("Java"),
+        PhaseError::new(Phase::Clone, Some("octocat/b".to_string()), "gone").in_language("Rust"),
+    ];
+
+    let summary = OutputFormatter::format_phase_errors(&errors);
+
+    assert_eq!(
+        summary,
+        "Rust:\n  ✗ [forks] octocat/a: 500\n  ✗ [clone] octocat/b: gone\nJava:\n  ✗ [search] timeout\n"
+    );
+}
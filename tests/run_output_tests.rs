@@ -0,0 +1,148 @@
+//! Output of pipeline runs, captured through `util::decor`.
+mod common;
+
+use ecs160_hw1::app::clone::select_code_repo;
+use ecs160_hw1::app::{report_errors, run_parts};
+use ecs160_hw1::config::{AppConfig, CloneConfig, OutputConfig, OutputFormat};
+use ecs160_hw1::model::Repo;
+use ecs160_hw1::service::{TestGitService, TestStorageService};
+use ecs160_hw1::util::decor::{self, CapturedOutput};
+use tokio::sync::Mutex;
+
+/// Output capture and the decor settings are process-wide, so runs take turns.
+static RUN_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Runs every part against canned data, with clones pointed at a missing local remote so
+/// Part C fails fast without touching the network, then prints the failures like `run`
+/// does. Returns everything written.
+async fn captured_run(name: &str, output: OutputConfig) -> CapturedOutput {
+    let _guard = RUN_LOCK.lock().await;
+    let base = std::env::temp_dir().join(format!("ecs160-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&base);
+    let service = TestGitService {
+        repos: vec![Repo::builder()
+            .id(1)
+            .name("hello")
+            .owner("octocat")
+            .stars(42)
+            .open_issues_count(3)
+            .build()],
+        ..TestGitService::new()
+    };
+    let config = AppConfig {
+        output,
+        clone: CloneConfig {
+            git_base_url: base.join("missing-remote").display().to_string(),
+            retries: 0,
+            ..CloneConfig::default()
+        },
+        ..AppConfig::default()
+    };
+
+    decor::capture_start();
+    let result = run_parts(&service, &config, &base.join("clones"), || async {
+        Ok(TestStorageService::new())
+    })
+    .await;
+    if let Ok(errors) = &result {
+        report_errors(errors);
+    }
+    let captured = decor::capture_finish();
+    decor::set_plain(false);
+    decor::set_stdout_reserved(false);
+    let _ = std::fs::remove_dir_all(&base);
+
+    result.expect("run should succeed");
+    captured
+}
+
+#[tokio::test]
+async fn prometheus_run_writes_only_the_exposition_to_stdout() {
+    let captured = captured_run(
+        "prometheus-run",
+        OutputConfig {
+            format: OutputFormat::Prometheus,
+            ..OutputConfig::default()
+        },
+    )
+    .await;
+
+    let samples = common::parse_prometheus(&captured.stdout);
+    assert!(samples
+        .iter()
+        .all(|(name, _, _)| name.starts_with("github_")));
+    assert!(captured
+        .stdout
+        .contains("github_repo_stars{language=\"C\",repo=\"octocat/hello\"} 42"));
+    // Everything else still reaches the user, on stderr.
+    assert!(captured.stderr.contains("=== Part A"));
+    assert!(captured.stderr.contains("Clone summary:"));
+    assert!(captured.stderr.contains("=== Part D"));
+}
+
+#[tokio::test]
+async fn plain_run_writes_only_ascii_without_banners() {
+    let captured = captured_run(
+        "plain-run",
+        OutputConfig {
+            plain: true,
+            ..OutputConfig::default()
+        },
+    )
+    .await;
+    let all = format!("{}{}", captured.stdout, captured.stderr);
+
+    assert!(all.is_ascii(), "non-ASCII output in plain mode:\n{all}");
+    for line in all.lines() {
+        assert!(
+            line.is_empty() || !line.chars().all(|c| c == '='),
+            "banner line in plain mode: {line:?}"
+        );
+    }
+    assert!(captured.stdout.contains("=== Part A"));
+    assert!(captured.stdout.contains("[OK] Found 1 repositories"));
+    // The failed clone shows up in the error summary, assembled at runtime.
+    assert!(
+        captured.stdout.contains("=== Errors encountered ==="),
+        "{all}"
+    );
+    assert!(all.contains("  [FAIL] ["), "{all}");
+}
+
+#[tokio::test]
+async fn sequential_clone_says_why_each_candidate_was_skipped() {
+    let _guard = RUN_LOCK.lock().await;
+    let base = std::env::temp_dir().join(format!("ecs160-skip-lines-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&base);
+    let mut huge = Repo::builder().id(1).name("huge").owner("octocat").build();
+    huge.size = 900;
+    let missing = Repo::builder()
+        .id(2)
+        .name("missing")
+        .owner("octocat")
+        .build();
+    let config = CloneConfig {
+        parallelism: 1,
+        max_size_kb: Some(100),
+        git_base_url: base.join("missing-remote").display().to_string(),
+        retries: 0,
+        ..CloneConfig::default()
+    };
+
+    decor::capture_start();
+    let result = select_code_repo(&[huge, missing], "Rust", &base.join("clones"), &config).await;
+    let captured = decor::capture_finish();
+    let _ = std::fs::remove_dir_all(&base);
+
+    assert!(result.is_err());
+    let out = captured.stdout;
+    assert!(
+        out.contains("✗ octocat/huge exceeds the clone size limit"),
+        "{out}"
+    );
+    assert!(
+        out.contains("✗ octocat/missing could not be cloned or inspected: "),
+        "{out}"
+    );
+    assert!(!out.contains("documentation"), "{out}");
+}
@@ -1,16 +1,10 @@
 //! Statistics tests.
 
-use ecs160_hw1::model::{Commit, CommitAuthor, CommitFile, CommitSummary, Issue, Owner, Repo};
-
-/// Helper function to create a test Owner
-fn create_test_owner(login: &str, id: i64) -> Owner {
-    Owner {
-        login: login.to_string(),
-        id,
-        html_url: format!("https://github.com/{}", login),
-        site_admin: false,
-    }
-}
+use chrono::{TimeZone, Utc, Weekday};
+use ecs160_hw1::app::stats::StatsCalculator;
+use ecs160_hw1::config::{AuthorIdentities, BotAuthors, CommitDateSource, StatsConfig};
+use ecs160_hw1::model::{Commit, CommitAuthor, CommitFile, CommitSummary, Issue, Repo};
+use ecs160_hw1::util::clock::FixedClock;
 
 /// Helper function to create a test Repo
 fn create_test_repo(
@@ -20,22 +14,16 @@ fn create_test_repo(
     forks: u64,
     open_issues: u64,
 ) -> Repo {
-    Repo {
-        id: 1,
-        name: name.to_string(),
-        full_name: format!("{}/{}", owner_login, name),
-        html_url: format!("https://github.com/{}/{}", owner_login, name),
-        forks_count: forks,
-        stargazers_count: stars,
-        open_issues_count: open_issues,
-        language: Some("Rust".to_string()),
-        owner: create_test_owner(owner_login, 1),
-        created_at: Some("2024-01-01T00:00:00Z".to_string()),
-        forks: Vec::new(),
-        recent_commits: Vec::new(),
-        issues: Vec::new(),
-        commit_count: 0,
-    }
+    Repo::builder()
+        .id(1)
+        .name(name)
+        .owner(owner_login)
+        .language("Rust")
+        .stars(stars)
+        .forks_count(forks)
+        .open_issues_count(open_issues)
+        .created_at("2024-01-01T00:00:00Z")
+        .build()
 }
 
 /// Helper function to create a test Commit with files
@@ -54,6 +42,7 @@ fn create_test_commit(sha: &str, files: Vec<CommitFile>) -> Commit {
             committer: None,
         },
         files,
+        parents: Vec::new(),
     }
 }
 
@@ -65,18 +54,24 @@ fn create_test_file(filename: &str, additions: i64, deletions: i64, changes: i64
         deletions,
         changes,
         status: "modified".to_string(),
+        previous_filename: None,
     }
 }
 
 /// Helper function to create a test Issue
 fn create_test_issue(title: &str, state: &str) -> Issue {
     Issue {
+        id: 1,
+        number: 1,
         title: title.to_string(),
         body: Some("Test issue body".to_string()),
         state: state.to_string(),
         html_url: Some("https://github.com/issues/1".to_string()),
         created_at: "2024-01-01T00:00:00Z".to_string(),
         updated_at: "2024-01-02T00:00:00Z".to_string(),
+        user_login: None,
+        comments: 0,
+        is_pull_request: false,
     }
 }
 
@@ -87,7 +82,7 @@ fn create_test_issue(title: &str, state: &str) -> Issue {
 #[test]
 fn test_total_stars_single_repo() {
     let repo = create_test_repo("test-repo", "owner1", 100, 5, 3);
-    let repos = vec![repo];
+    let repos = [repo];
 
     let total_stars: u64 = repos.iter().map(|r| r.stargazers_count).sum();
 
@@ -96,7 +91,7 @@ fn test_total_stars_single_repo() {
 
 #[test]
 fn test_total_stars_multiple_repos() {
-    let repos = vec![
+    let repos = [
         create_test_repo("repo1", "owner1", 100, 5, 3),
         create_test_repo("repo2", "owner2", 200, 10, 5),
         create_test_repo("repo3", "owner3", 50, 2, 1),
@@ -118,7 +113,7 @@ fn test_total_stars_empty_repos() {
 
 #[test]
 fn test_total_stars_zero_stars() {
-    let repos = vec![
+    let repos = [
         create_test_repo("repo1", "owner1", 0, 5, 3),
         create_test_repo("repo2", "owner2", 0, 10, 5),
     ];
@@ -135,7 +130,7 @@ fn test_total_stars_zero_stars() {
 #[test]
 fn test_total_forks_single_repo() {
     let repo = create_test_repo("test-repo", "owner1", 100, 5, 3);
-    let repos = vec![repo];
+    let repos = [repo];
 
     let total_forks: u64 = repos.iter().map(|r| r.forks_count).sum();
 
@@ -144,7 +139,7 @@ fn test_total_forks_single_repo() {
 
 #[test]
 fn test_total_forks_multiple_repos() {
-    let repos = vec![
+    let repos = [
         create_test_repo("repo1", "owner1", 100, 5, 3),
         create_test_repo("repo2", "owner2", 200, 10, 5),
         create_test_repo("repo3", "owner3", 50, 15, 1),
@@ -176,7 +171,7 @@ fn test_total_open_issues_single_repo() {
         create_test_issue("Issue 2", "open"),
         create_test_issue("Issue 3", "open"),
     ];
-    let repos = vec![repo];
+    let repos = [repo];
 
     let total_open_issues: usize = repos.iter().map(|r| r.issues.len()).sum();
 
@@ -198,7 +193,7 @@ fn test_total_open_issues_multiple_repos() {
         create_test_issue("Issue 5", "open"),
     ];
 
-    let repos = vec![repo1, repo2];
+    let repos = [repo1, repo2];
 
     let total_open_issues: usize = repos.iter().map(|r| r.issues.len()).sum();
 
@@ -207,7 +202,7 @@ fn test_total_open_issues_multiple_repos() {
 
 #[test]
 fn test_total_open_issues_no_issues() {
-    let repos = vec![
+    let repos = [
         create_test_repo("repo1", "owner1", 100, 5, 0),
         create_test_repo("repo2", "owner2", 200, 10, 0),
     ];
@@ -413,6 +408,657 @@ fn test_new_fork_commits_all_commits_before_fork() {
     assert_eq!(new_commits, 0);
 }
 
+// ============================================================================
+// Test 6: Merging Language Reports
+// ============================================================================
+
+#[test]
+fn test_merge_language_reports_dedups_and_resums() {
+    let mut repo1 = create_test_repo("repo1", "owner1", 100, 5, 0);
+    repo1.id = 1;
+    repo1.issues = vec![create_test_issue("Issue 1", "open")];
+    let mut repo2 = create_test_repo("repo2", "owner2", 200, 10, 0);
+    repo2.id = 2;
+    let mut repo3 = create_test_repo("repo3", "owner3", 50, 1, 0);
+    repo3.id = 3;
+    repo3.issues = vec![
+        create_test_issue("Issue 2", "open"),
+        create_test_issue("Issue 3", "open"),
+    ];
+
+    let first = StatsCalculator::build_language_report("Rust", vec![repo1, repo2.clone()]);
+    let second = StatsCalculator::build_language_report("Rust", vec![repo2, repo3]);
+
+    let merged = first.merge(second);
+
+    assert_eq!(merged.language, "Rust");
+    assert_eq!(merged.repos.len(), 3);
+    assert_eq!(merged.repo_metrics.len(), 3);
+    assert_eq!(merged.total_stars, 350);
+    assert_eq!(merged.total_forks, 16);
+    assert_eq!(merged.total_open_issues, 3);
+}
+
+#[test]
+#[should_panic(expected = "different languages")]
+fn test_merge_language_reports_rejects_mismatched_language() {
+    let rust = StatsCalculator::build_language_report("Rust", Vec::new());
+    let java = StatsCalculator::build_language_report("Java", Vec::new());
+
+    let _ = rust.merge(java);
+}
+
+// ============================================================================
+// Test 7: Top Issue Reporters
+// ============================================================================
+
+#[test]
+fn test_top_reporters_ranked_by_issue_count() {
+    let mut repo = create_test_repo("test-repo", "owner1", 100, 5, 0);
+    repo.issues = ["alice", "bob", "alice", "carol", "bob", "alice", "dave"]
+        .iter()
+        .map(|login| {
+            let mut issue = create_test_issue("Issue", "open");
+            issue.user_login = Some(login.to_string());
+            issue
+        })
+        .collect();
+    repo.issues.push(create_test_issue("Anonymous", "open"));
+
+    let (metrics, _) = StatsCalculator::calculate_repo_stats(&[repo]);
+
+    assert_eq!(metrics[0].top_reporters, vec!["alice", "bob", "carol"]);
+}
+
+// ============================================================================
+// Test 8: Commit Time Distribution
+// ============================================================================
+
+#[test]
+fn test_commit_time_distribution_buckets_by_weekday_and_hour() {
+    let mut repo = create_test_repo("test-repo", "owner1", 100, 5, 0);
+    repo.recent_commits = vec![
+        // Monday 2024-01-15
+        create_test_commit_with_date("a", "2024-01-15T09:30:00Z"),
+        create_test_commit_with_date("b", "2024-01-15T17:00:00Z"),
+        // Wednesday 2024-01-17
+        create_test_commit_with_date("c", "2024-01-17T09:05:00Z"),
+        // Sunday 2024-01-21
+        create_test_commit_with_date("d", "2024-01-21T23:59:59Z"),
+    ];
+
+    let distribution = StatsCalculator::commit_time_distribution(&repo);
+
+    assert_eq!(distribution.by_weekday, [2, 0, 1, 0, 0, 0, 1]);
+    assert_eq!(distribution.by_hour[9], 2);
+    assert_eq!(distribution.by_hour[17], 1);
+    assert_eq!(distribution.by_hour[23], 1);
+    assert_eq!(distribution.undated, 0);
+    assert_eq!(distribution.busiest_weekday(), Some(Weekday::Mon));
+}
+
+#[test]
+fn test_commit_time_distribution_uses_author_offset() {
+    let mut repo = create_test_repo("test-repo", "owner1", 100, 5, 0);
+    // Saturday 03:00 UTC, but still Friday evening for the author
+    repo.recent_commits = vec![create_test_commit_with_date(
+        "a",
+        "2024-01-19T22:00:00-05:00",
+    )];
+
+    let distribution = StatsCalculator::commit_time_distribution(&repo);
+
+    assert_eq!(
+        distribution.by_weekday[Weekday::Fri.num_days_from_monday() as usize],
+        1
+    );
+    assert_eq!(distribution.by_hour[22], 1);
+}
+
+#[test]
+fn test_commit_time_distribution_counts_missing_dates() {
+    let mut repo = create_test_repo("test-repo", "owner1", 100, 5, 0);
+    let mut undated = create_test_commit("a", Vec::new());
+    undated.commit.author = None;
+    repo.recent_commits = vec![undated, create_test_commit_with_date("b", "not a date")];
+
+    let (metrics, _) = StatsCalculator::calculate_repo_stats(&[repo]);
+
+    assert_eq!(metrics[0].commit_times.undated, 2);
+    assert_eq!(metrics[0].commit_times.by_weekday, [0; 7]);
+    assert_eq!(metrics[0].commit_times.busiest_weekday(), None);
+}
+
+// ============================================================================
+// Test 9: Issue Age Buckets
+// ============================================================================
+
+#[test]
+fn test_issue_age_buckets_with_fixed_clock() {
+    let clock = FixedClock(Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap());
+    let mut repo = create_test_repo("test-repo", "owner1", 100, 5, 0);
+    repo.issues = [
+        "2024-05-30T12:00:00Z", // 2 days
+        "2024-05-25T12:00:00Z", // 7 days
+        "2024-05-02T12:00:00Z", // 30 days
+        "2023-06-02T12:00:00Z", // 365 days
+        "2020-01-01T00:00:00Z",
+        "yesterday",
+    ]
+    .iter()
+    .map(|created_at| {
+        let mut issue = create_test_issue("Issue", "open");
+        issue.created_at = created_at.to_string();
+        issue
+    })
+    .collect();
+
+    let buckets = StatsCalculator::issue_age_buckets(&repo, &clock);
+
+    assert_eq!(buckets.under_week, 1);
+    assert_eq!(buckets.under_month, 1);
+    assert_eq!(buckets.under_year, 1);
+    assert_eq!(buckets.older, 2);
+    assert_eq!(buckets.undated, 1);
+}
+
+#[test]
+fn test_repo_metrics_use_injected_clock() {
+    let mut repo = create_test_repo("test-repo", "owner1", 100, 5, 0);
+    let mut issue = create_test_issue("Issue", "open");
+    issue.created_at = "2024-01-01T00:00:00Z".to_string();
+    repo.issues = vec![issue];
+
+    let early = FixedClock(Utc.with_ymd_and_hms(2024, 1, 3, 0, 0, 0).unwrap());
+    let late = FixedClock(Utc.with_ymd_and_hms(2025, 1, 3, 0, 0, 0).unwrap());
+    let config = StatsConfig::default();
+    let repos = [repo];
+    let (early_metrics, _) =
+        StatsCalculator::calculate_repo_stats_with_clock(&repos, &config, &early);
+    let (late_metrics, _) =
+        StatsCalculator::calculate_repo_stats_with_clock(&repos, &config, &late);
+
+    assert_eq!(early_metrics[0].issue_ages.under_week, 1);
+    assert_eq!(late_metrics[0].issue_ages.older, 1);
+}
+
+// ============================================================================
+// Test 10: Top Files by Change Frequency
+// ============================================================================
+
+#[test]
+fn test_top_files_by_frequency_favors_frequently_touched_files() {
+    let mut repo = create_test_repo("test-repo", "owner1", 100, 5, 0);
+    repo.recent_commits = vec![
+        create_test_commit(
+            "c1",
+            vec![
+                create_test_file("config.h", 1, 0, 1),
+                create_test_file("big_rewrite.c", 900, 400, 1300),
+            ],
+        ),
+        create_test_commit("c2", vec![create_test_file("config.h", 1, 1, 2)]),
+        create_test_commit(
+            "c3",
+            vec![
+                create_test_file("config.h", 0, 1, 1),
+                create_test_file("util.c", 10, 2, 12),
+            ],
+        ),
+    ];
+
+    let (metrics, _) = StatsCalculator::calculate_repo_stats(&[repo]);
+
+    assert_eq!(metrics[0].top_files[0], "big_rewrite.c");
+    assert_eq!(
+        metrics[0].top_files_by_frequency,
+        vec!["config.h", "big_rewrite.c", "util.c"]
+    );
+}
+
+// ============================================================================
+// Test 11: Overflow Protection
+// ============================================================================
+
+#[test]
+fn test_churn_sums_saturate_instead_of_overflowing() {
+    let mut repo = create_test_repo("test-repo", "owner1", 100, 5, 0);
+    repo.recent_commits = vec![
+        create_test_commit("c1", vec![create_test_file("huge.c", 0, 0, i64::MAX - 1)]),
+        create_test_commit("c2", vec![create_test_file("huge.c", 0, 0, i64::MAX - 1)]),
+        create_test_commit(
+            "c3",
+            vec![
+                create_test_file("wide.c", i64::MAX, i64::MAX, 0),
+                create_test_file("small.c", 1, 1, 2),
+            ],
+        ),
+    ];
+
+    let (metrics, _) = StatsCalculator::calculate_repo_stats(&[repo]);
+
+    // Both large files saturate to i64::MAX and tie, so they're ordered by name.
+    assert_eq!(metrics[0].top_files, vec!["huge.c", "wide.c", "small.c"]);
+}
+
+#[test]
+fn test_star_and_fork_totals_saturate() {
+    let repos = vec![
+        create_test_repo("a", "owner1", u64::MAX - 1, u64::MAX, 0),
+        create_test_repo("b", "owner1", 10, 10, 0),
+    ];
+
+    let report = StatsCalculator::build_language_report("Rust", repos);
+
+    assert_eq!(report.total_stars, u64::MAX);
+    assert_eq!(report.total_forks, u64::MAX);
+}
+
+// ============================================================================
+// Test 12: Issue Comments
+// ============================================================================
+
+#[test]
+fn test_average_issue_comments() {
+    let mut repo = create_test_repo("test-repo", "owner1", 100, 5, 0);
+    repo.issues = [0, 3, 6]
+        .iter()
+        .map(|&comments| {
+            let mut issue = create_test_issue("Issue", "open");
+            issue.comments = comments;
+            issue
+        })
+        .collect();
+    let quiet = create_test_repo("quiet", "owner1", 1, 0, 0);
+
+    let (metrics, _) = StatsCalculator::calculate_repo_stats(&[repo, quiet]);
+
+    assert_eq!(metrics[0].avg_issue_comments, 3.0);
+    assert_eq!(metrics[1].avg_issue_comments, 0.0);
+}
+
+// ============================================================================
+// Test 13: Fork Commit Date Source
+// ============================================================================
+
+#[test]
+fn test_new_fork_commits_author_vs_committer_date() {
+    let mut repo = create_test_repo("test-repo", "owner1", 100, 5, 0);
+    let mut fork = create_test_repo("fork", "forker", 0, 0, 0);
+    fork.created_at = Some("2024-03-01T00:00:00Z".to_string());
+    // Cherry-picked onto the fork: old author date, new committer date.
+    let mut picked = create_test_commit_with_date("c1", "2024-01-15T00:00:00Z");
+    picked.commit.committer = Some(CommitAuthor {
+        name: Some("Forker".to_string()),
+        email: Some("forker@example.com".to_string()),
+        date: Some("2024-03-10T00:00:00Z".to_string()),
+    });
+    fork.recent_commits = vec![
+        picked,
+        // No committer recorded, so both modes use the author date.
+        create_test_commit_with_date("c2", "2024-03-05T00:00:00Z"),
+    ];
+    repo.forks = vec![fork];
+    let repos = [repo];
+
+    let author = StatsConfig::default();
+    let committer = StatsConfig {
+        fork_commit_date: CommitDateSource::Committer,
+        ..StatsConfig::default()
+    };
+    let (_, by_author) = StatsCalculator::calculate_repo_stats_with_config(&repos, &author);
+    let (_, by_committer) = StatsCalculator::calculate_repo_stats_with_config(&repos, &committer);
+
+    assert_eq!(by_author, 1);
+    assert_eq!(by_committer, 2);
+}
+
+// ============================================================================
+// Test 14: Fork Divergence From Compare
+// ============================================================================
+
+#[test]
+fn test_new_fork_commits_prefers_compare_ahead_by() {
+    let mut repo = create_test_repo("test-repo", "owner1", 100, 5, 0);
+    let mut compared = create_test_repo("fork-a", "forker", 0, 0, 0);
+    compared.ahead_by = Some(7);
+    let mut fetched = create_test_repo("fork-b", "other", 0, 0, 0);
+    fetched.created_at = Some("2024-03-01T00:00:00Z".to_string());
+    fetched.recent_commits = vec![create_test_commit_with_date("c1", "2024-03-05T00:00:00Z")];
+    repo.forks = vec![compared, fetched];
+
+    let (_, new_fork_commits) =
+        StatsCalculator::calculate_repo_stats_with_config(&[repo], &StatsConfig::default());
+
+    assert_eq!(new_fork_commits, 8);
+}
+
+// ============================================================================
+// Test 15: Active Forks
+// ============================================================================
+
+#[test]
+fn test_active_forks_sorted_by_new_commits() {
+    let mut repo = create_test_repo("test-repo", "owner1", 100, 5, 0);
+    let mut quiet = create_test_repo("quiet", "forker-a", 0, 0, 0);
+    quiet.created_at = Some("2024-03-01T00:00:00Z".to_string());
+    quiet.recent_commits = vec![
+        create_test_commit_with_date("q1", "2024-03-02T00:00:00Z"),
+        // Inherited from the parent, so not new.
+        create_test_commit_with_date("q0", "2024-02-01T00:00:00Z"),
+    ];
+    let mut busy = create_test_repo("busy", "forker-b", 0, 0, 0);
+    busy.created_at = Some("2024-03-01T00:00:00Z".to_string());
+    busy.recent_commits = vec![
+        create_test_commit_with_date("b1", "2024-03-02T00:00:00Z"),
+        create_test_commit_with_date("b2", "2024-03-03T00:00:00Z"),
+        create_test_commit_with_date("b3", "2024-03-04T00:00:00Z"),
+    ];
+    let idle = create_test_repo("idle", "forker-c", 0, 0, 0);
+    repo.forks = vec![quiet, idle, busy];
+
+    let (metrics, total) =
+        StatsCalculator::calculate_repo_stats_with_config(&[repo], &StatsConfig::default());
+
+    assert_eq!(
+        metrics[0].active_forks,
+        vec![
+            ("forker-b/busy".to_string(), 3),
+            ("forker-a/quiet".to_string(), 1)
+        ]
+    );
+    assert_eq!(total, 4);
+}
+
+// ============================================================================
+// Test 16: Language-Only Top Files
+// ============================================================================
+
+#[test]
+fn test_top_files_language_only_skips_other_file_types() {
+    let mut repo = create_test_repo("test-repo", "owner1", 100, 5, 0);
+    repo.recent_commits = vec![create_test_commit(
+        "c1",
+        vec![
+            create_test_file("README.md", 500, 200, 700),
+            create_test_file("src/main.rs", 30, 10, 40),
+            create_test_file("src/lib.rs", 5, 5, 10),
+        ],
+    )];
+    let mut unknown = repo.clone();
+    unknown.language = Some("Brainfuck".to_string());
+    let config = StatsConfig {
+        language_files_only: true,
+        ..StatsConfig::default()
+    };
+
+    let (all_files, _) =
+        StatsCalculator::calculate_repo_stats_with_config(&[repo.clone()], &StatsConfig::default());
+    let (rust_only, _) = StatsCalculator::calculate_repo_stats_with_config(&[repo], &config);
+    let (fallback, _) = StatsCalculator::calculate_repo_stats_with_config(&[unknown], &config);
+
+    assert_eq!(all_files[0].top_files[0], "README.md");
+    assert_eq!(rust_only[0].top_files, vec!["src/main.rs", "src/lib.rs"]);
+    assert_eq!(fallback[0].top_files[0], "README.md");
+}
+
+// ============================================================================
+// Test 17: Minimum Commit Count
+// ============================================================================
+
+#[test]
+fn test_min_commits_excludes_small_repos_from_stats_only() {
+    let commits = |n: usize| {
+        (0..n)
+            .map(|i| create_test_commit(&format!("c{i}"), Vec::new()))
+            .collect::<Vec<_>>()
+    };
+    let project = Repo::builder()
+        .id(1)
+        .name("project")
+        .owner("owner1")
+        .stars(100)
+        .commits(commits(5))
+        .build();
+    let template = Repo::builder()
+        .id(2)
+        .name("template")
+        .owner("owner2")
+        .stars(900)
+        .commits(commits(1))
+        .build();
+    let config = StatsConfig {
+        min_commits: 2,
+        ..StatsConfig::default()
+    };
+
+    let report = StatsCalculator::build_language_report_with_config(
+        "Rust",
+        vec![project, template],
+        &config,
+    );
+
+    assert_eq!(report.repos.len(), 2);
+    assert_eq!(report.total_stars, 100);
+    assert_eq!(report.total_repo_commits, 5);
+    let slugs: Vec<_> = report
+        .repo_metrics
+        .iter()
+        .map(|m| m.slug.as_str())
+        .collect();
+    assert_eq!(slugs, vec!["owner1/project"]);
+}
+
+// ============================================================================
+// Test 18: Renamed Files
+// ============================================================================
+
+#[test]
+fn test_top_files_merges_renamed_file_churn() {
+    let mut renamed = create_test_file("src/engine.rs", 5, 5, 10);
+    renamed.status = "renamed".to_string();
+    renamed.previous_filename = Some("src/core.rs".to_string());
+    let repo = Repo::builder()
+        .commits(vec![
+            create_test_commit("new", vec![renamed]),
+            create_test_commit(
+                "old",
+                vec![
+                    create_test_file("src/core.rs", 30, 10, 40),
+                    create_test_file("README.md", 20, 0, 20),
+                ],
+            ),
+        ])
+        .build();
+
+    let (metrics, _) = StatsCalculator::calculate_repo_stats_with_config(
+        std::slice::from_ref(&repo),
+        &StatsConfig::default(),
+    );
+    assert_eq!(metrics[0].top_files, vec!["src/engine.rs", "README.md"]);
+
+    let config = StatsConfig {
+        merge_renames: false,
+        ..StatsConfig::default()
+    };
+    let (metrics, _) = StatsCalculator::calculate_repo_stats_with_config(&[repo], &config);
+    assert_eq!(
+        metrics[0].top_files,
+        vec!["src/core.rs", "README.md", "src/engine.rs"]
+    );
+}
+
+// ============================================================================
+// Test 19: Author Identities
+// ============================================================================
+
+#[test]
+fn test_identity_map_merges_author_emails() {
+    let commit_by = |sha: &str, email: &str| {
+        let mut commit = create_test_commit(sha, Vec::new());
+        commit.commit.author.as_mut().unwrap().email = Some(email.to_string());
+        commit
+    };
+    let repo = Repo::builder()
+        .commits(vec![
+            commit_by("c1", "jane@work.com"),
+            commit_by("c2", "Jane@Personal.com"),
+        ])
+        .build();
+
+    assert_eq!(
+        StatsCalculator::unique_contributors(
+            &repo,
+            &AuthorIdentities::default(),
+            &BotAuthors::default()
+        ),
+        2
+    );
+
+    let mut identities = AuthorIdentities::default();
+    identities.insert("jane@personal.com", "jane@work.com");
+    let config = StatsConfig {
+        author_identities: identities,
+        ..StatsConfig::default()
+    };
+    let (metrics, _) = StatsCalculator::calculate_repo_stats_with_config(&[repo], &config);
+    assert_eq!(metrics[0].contributors, 1);
+}
+
+// ============================================================================
+// Test 20: Stale Issues
+// ============================================================================
+
+#[test]
+fn test_stale_issue_ratio_uses_updated_at() {
+    let clock = FixedClock(Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap());
+    let issue = |state: &str, updated_at: &str| {
+        let mut issue = create_test_issue("Issue", state);
+        issue.updated_at = updated_at.to_string();
+        issue
+    };
+    let issues = vec![
+        issue("open", "2024-05-30T00:00:00Z"),
+        issue("open", "2024-04-15T00:00:00Z"),
+        issue("open", "2023-01-01T00:00:00Z"),
+        issue("open", "2024-01-01T00:00:00Z"),
+        issue("open", "not a date"),
+        issue("closed", "2020-01-01T00:00:00Z"),
+    ];
+
+    assert_eq!(StatsCalculator::stale_issue_ratio(&issues, 90, &clock), 0.5);
+    assert_eq!(
+        StatsCalculator::stale_issue_ratio(&issues, 30, &clock),
+        0.75
+    );
+    assert_eq!(
+        StatsCalculator::stale_issue_ratio(&issues, 1000, &clock),
+        0.0
+    );
+    assert_eq!(StatsCalculator::stale_issue_ratio(&[], 90, &clock), 0.0);
+}
+
+#[test]
+fn test_repo_metrics_report_stale_issue_ratio() {
+    let clock = FixedClock(Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap());
+    let mut repo = create_test_repo("test-repo", "owner1", 100, 5, 0);
+    let mut stale = create_test_issue("Old", "open");
+    stale.updated_at = "2024-01-01T00:00:00Z".to_string();
+    let mut fresh = create_test_issue("New", "open");
+    fresh.updated_at = "2024-05-31T00:00:00Z".to_string();
+    repo.issues = vec![stale, fresh];
+    let config = StatsConfig {
+        stale_issue_days: 60,
+        ..StatsConfig::default()
+    };
+
+    let (metrics, _) = StatsCalculator::calculate_repo_stats_with_clock(&[repo], &config, &clock);
+
+    assert_eq!(metrics[0].stale_issue_ratio, 0.5);
+}
+
+// ============================================================================
+// Test 21: Merge Commits
+// ============================================================================
+
+#[test]
+fn test_exclude_merge_commits_from_top_files() {
+    let mut merge = create_test_commit(
+        "merge",
+        vec![create_test_file("vendor/generated.c", 5000, 0, 5000)],
+    );
+    merge.parents = vec!["p1".to_string(), "p2".to_string()];
+    let repo = Repo::builder()
+        .commits(vec![
+            merge,
+            create_test_commit("work", vec![create_test_file("src/main.c", 10, 2, 12)]),
+        ])
+        .build();
+
+    let (kept, _) = StatsCalculator::calculate_repo_stats_with_config(
+        std::slice::from_ref(&repo),
+        &StatsConfig::default(),
+    );
+    assert_eq!(kept[0].top_files, vec!["vendor/generated.c", "src/main.c"]);
+
+    let config = StatsConfig {
+        exclude_merge_commits: true,
+        ..StatsConfig::default()
+    };
+    let (excluded, _) = StatsCalculator::calculate_repo_stats_with_config(&[repo], &config);
+    assert_eq!(excluded[0].top_files, vec!["src/main.c"]);
+    assert_eq!(excluded[0].merge_commit_ratio, 0.5);
+}
+
+// ============================================================================
+// Test 22: Bot Authors
+// ============================================================================
+
+#[test]
+fn test_bot_commits_are_left_out_of_contributors_and_churn() {
+    let mut bump = create_test_commit("bump", vec![create_test_file("Cargo.lock", 800, 600, 1400)]);
+    bump.commit.author = Some(CommitAuthor {
+        name: Some("dependabot[bot]".to_string()),
+        email: Some("49699333+dependabot[bot]@users.noreply.github.com".to_string()),
+        date: Some("2024-01-15T00:00:00Z".to_string()),
+    });
+    let repo = Repo::builder()
+        .commits(vec![
+            bump,
+            create_test_commit("work", vec![create_test_file("src/main.c", 10, 2, 12)]),
+        ])
+        .build();
+
+    let (metrics, _) = StatsCalculator::calculate_repo_stats_with_config(
+        std::slice::from_ref(&repo),
+        &StatsConfig::default(),
+    );
+    assert_eq!(metrics[0].contributors, 1);
+    assert_eq!(metrics[0].top_files, vec!["src/main.c"]);
+
+    // An empty list turns the filter off.
+    let config = StatsConfig {
+        bot_authors: BotAuthors::parse(""),
+        ..StatsConfig::default()
+    };
+    let (metrics, _) = StatsCalculator::calculate_repo_stats_with_config(&[repo], &config);
+    assert_eq!(metrics[0].contributors, 2);
+    assert_eq!(metrics[0].top_files, vec!["Cargo.lock", "src/main.c"]);
+}
+
+#[test]
+fn test_bot_author_patterns() {
+    let bots = BotAuthors::parse("renovate*, *-ci, *robot*, github-actions");
+
+    assert!(bots.matches("Renovate[bot]"));
+    assert!(bots.matches("nightly-ci"));
+    assert!(bots.matches("the-robot-account"));
+    assert!(bots.matches("GitHub-Actions"));
+    assert!(!bots.matches("github-actions-fan"));
+    assert!(!bots.matches("jane"));
+    assert!(BotAuthors::default().matches("renovate[bot]"));
+}
+
 // ============================================================================
 // Helper Functions (same as in app/mod.rs)
 // ============================================================================
@@ -479,5 +1125,6 @@ fn create_test_commit_with_date(sha: &str, date: &str) -> Commit {
             committer: None,
         },
         files: Vec::new(),
+        parents: Vec::new(),
     }
 }